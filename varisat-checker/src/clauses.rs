@@ -87,6 +87,10 @@ pub struct Clause {
     /// For checking the formula is a multiset of clauses. This is necessary as the generating
     /// solver might not check for duplicated clauses.
     ref_count: [u32; 2],
+    /// Hash of the clause's literals using all bits.
+    ///
+    /// This is kept up to date by [`rehash`](crate::hash::rehash).
+    pub full_hash: ClauseHash,
     /// Clause's literals.
     pub lits: ClauseLits,
 }
@@ -106,6 +110,19 @@ pub struct UnitClause {
     pub value: bool,
 }
 
+/// Ids assigned to an input clause.
+#[derive(Copy, Clone, Debug)]
+pub struct InputClauseIds {
+    /// Id of the step reporting the input clause.
+    ///
+    /// These are increasing in the order the input clauses were added.
+    pub step_id: u64,
+    /// Id of the stored clause.
+    ///
+    /// Duplicated clauses use the id of the first copy and tautological clauses are `None`.
+    pub clause_id: Option<u64>,
+}
+
 /// Return type of [`store_clause`]
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum StoreClauseResult {
@@ -130,6 +147,10 @@ pub struct Clauses {
     pub literal_buffer: Vec<Lit>,
     /// Number of literals in the buffer which are from deleted clauses.
     garbage_size: usize,
+    /// Number of garbage collections of the literal buffer.
+    pub garbage_collections: u64,
+    /// Largest size of the literal buffer before a garbage collection.
+    pub peak_literal_buffer_len: usize,
     /// Stores all known non-unit clauses indexed by their hash.
     pub clauses: HashMap<ClauseHash, SmallVec<[Clause; 1]>>,
     /// Stores known unit clauses and propagations during a clause check.
@@ -139,6 +160,8 @@ pub struct Clauses {
     /// Our representation for unit clauses doesn't support conflicting units so this is used as a
     /// workaround.
     pub unit_conflict: Option<[u64; 2]>,
+    /// Ids of each input clause in the order they were added.
+    pub input_clause_ids: Vec<InputClauseIds>,
 }
 
 impl Clauses {
@@ -147,6 +170,43 @@ impl Clauses {
         self.unit_clauses[lit.index()]
             .map(|unit_clause| (unit_clause.value ^ lit.is_negative(), unit_clause))
     }
+
+    /// How often the given non-unit clause is present as irredundant and as redundant clause.
+    ///
+    /// `lits` must be sorted and free of duplicates.
+    pub fn ref_count(&self, hash: ClauseHash, lits: &[Lit]) -> [u32; 2] {
+        self.clauses
+            .get(&hash)
+            .and_then(|candidates| {
+                candidates
+                    .iter()
+                    .find(|candidate| candidate.lits.slice(&self.literal_buffer) == lits)
+            })
+            .map_or([0, 0], |candidate| candidate.ref_count)
+    }
+
+    /// Number of stored clauses, including unit clauses.
+    pub fn clause_count(&self) -> usize {
+        let long_clauses: usize = self
+            .clauses
+            .values()
+            .map(|candidates| candidates.len())
+            .sum();
+        let unit_clauses = self
+            .unit_clauses
+            .iter()
+            .filter(|unit| {
+                matches!(
+                    unit,
+                    Some(UnitClause {
+                        id: UnitId::Global(_),
+                        ..
+                    })
+                )
+            })
+            .count();
+        long_clauses + unit_clauses
+    }
 }
 
 /// Adds a clause to the checker.
@@ -161,15 +221,20 @@ pub fn add_clause<'a>(
     let (tmp_data, mut ctx) = ctx.split_part_mut(TmpDataP);
 
     if copy_canonical(&mut tmp_data.tmp, clause) {
-        let (clauses, mut ctx) = ctx.split_part_mut(ClausesP);
+        let clauses = ctx.part_mut(ClausesP);
+        let id = clauses.next_clause_id;
+        clauses.next_clause_id += 1;
+        clauses.input_clause_ids.push(InputClauseIds {
+            step_id: id,
+            clause_id: None,
+        });
         process_step(
             ctx.borrow(),
             &CheckedProofStep::TautologicalClause {
-                id: clauses.next_clause_id,
+                id,
                 clause: &tmp_data.tmp,
             },
         )?;
-        clauses.next_clause_id += 1;
         return Ok(());
     }
 
@@ -179,10 +244,14 @@ pub fn add_clause<'a>(
 
     let (id, added) = store_clause(ctx.borrow(), &tmp_data.tmp, false);
 
-    let (clauses, mut ctx) = ctx.split_part_mut(ClausesP);
-
     match added {
         StoreClauseResult::New => {
+            ctx.part_mut(ClausesP)
+                .input_clause_ids
+                .push(InputClauseIds {
+                    step_id: id,
+                    clause_id: Some(id),
+                });
             process_step(
                 ctx.borrow(),
                 &CheckedProofStep::AddClause {
@@ -202,17 +271,24 @@ pub fn add_clause<'a>(
                 )?;
             }
 
+            // This is a duplicated clause. We want to ensure that the clause ids match the input
+            // order so we skip a clause id.
+            let clauses = ctx.part_mut(ClausesP);
+            let duplicate_id = clauses.next_clause_id;
+            clauses.next_clause_id += 1;
+            clauses.input_clause_ids.push(InputClauseIds {
+                step_id: duplicate_id,
+                clause_id: Some(id),
+            });
+
             process_step(
                 ctx.borrow(),
                 &CheckedProofStep::DuplicatedClause {
-                    id: clauses.next_clause_id,
+                    id: duplicate_id,
                     same_as_id: id,
                     clause: &tmp_data.tmp,
                 },
             )?;
-            // This is a duplicated clause. We want to ensure that the clause ids match the input
-            // order so we skip a clause id.
-            clauses.next_clause_id += 1;
         }
     }
 
@@ -250,14 +326,18 @@ pub fn store_clause(
         }
         [lit] => store_unit_clause(ctx.borrow(), lit),
         _ => {
-            let hash = ctx.part(ClauseHasherP).clause_hash(&lits);
+            let hasher = ctx.part(ClauseHasherP);
+            let full_hash = hasher.full_clause_hash(lits);
+            let hash = hasher.truncate_hash(full_hash);
 
             let (clauses, mut ctx) = ctx.split_part_mut(ClausesP);
 
             let candidates = clauses.clauses.entry(hash).or_default();
 
             for candidate in candidates.iter_mut() {
-                if candidate.lits.slice(&clauses.literal_buffer) == &lits[..] {
+                if candidate.full_hash == full_hash
+                    && candidate.lits.slice(&clauses.literal_buffer) == lits
+                {
                     let result = if !redundant && candidate.ref_count[0] == 0 {
                         // first irredundant copy
                         StoreClauseResult::NewlyIrredundant
@@ -279,7 +359,8 @@ pub fn store_clause(
             candidates.push(Clause {
                 id,
                 ref_count,
-                lits: ClauseLits::new(&lits, &mut clauses.literal_buffer),
+                full_hash,
+                lits: ClauseLits::new(lits, &mut clauses.literal_buffer),
             });
 
             clauses.next_clause_id += 1;
@@ -362,7 +443,9 @@ pub fn delete_clause(
         ));
     }
 
-    let hash = ctx.part(ClauseHasherP).clause_hash(lits);
+    let hasher = ctx.part(ClauseHasherP);
+    let full_hash = hasher.full_clause_hash(lits);
+    let hash = hasher.truncate_hash(full_hash);
 
     let clauses = ctx.part_mut(ClausesP);
 
@@ -376,7 +459,8 @@ pub fn delete_clause(
     let garbage_size = &mut clauses.garbage_size;
 
     candidates.retain(|candidate| {
-        if found || candidate.lits.slice(literal_buffer) != lits {
+        if found || candidate.full_hash != full_hash || candidate.lits.slice(literal_buffer) != lits
+        {
             true
         } else {
             found = true;
@@ -436,9 +520,12 @@ fn collect_garbage(mut ctx: partial!(Context, mut ClausesP)) {
         return;
     }
 
-    let mut new_buffer = vec![];
+    clauses.garbage_collections += 1;
+    clauses.peak_literal_buffer_len = clauses
+        .peak_literal_buffer_len
+        .max(clauses.literal_buffer.len());
 
-    new_buffer.reserve(clauses.literal_buffer.len());
+    let mut new_buffer = Vec::with_capacity(clauses.literal_buffer.len());
 
     for (_, candidates) in clauses.clauses.iter_mut() {
         for clause in candidates.iter_mut() {
@@ -0,0 +1,299 @@
+//! Checking of proofs in the DRAT format.
+use std::{io, mem::take};
+
+use partial_ref::{partial, PartialRef};
+
+use varisat_formula::Lit;
+use varisat_internal_proof::drat::DratParser;
+
+use crate::{
+    clauses::{delete_clause, store_clause, DeleteClauseResult, StoreClauseResult},
+    context::{parts::*, Context},
+    hash::rehash,
+    processing::{process_step, CheckedProofStep},
+    rup::check_clause_with_hashes,
+    sorted_lits::copy_canonical,
+    state::{process_unit_conflicts, report_progress},
+    variables::ensure_var,
+    CheckerError,
+};
+
+mod propagator;
+
+use propagator::Propagator;
+
+/// Checks a proof in the DRAT format.
+///
+/// Each added lemma is checked for being an asymmetric tautology (RUP) and if that fails, for
+/// being a resolution asymmetric tautology (RAT) using the first literal as pivot. Deletions of
+/// unit clauses and of unknown clauses are ignored. Checking stops as soon as the empty clause is
+/// derived.
+pub fn check_drat_proof<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut CheckerStateP,
+        mut ClauseHasherP,
+        mut ClausesP,
+        mut ProcessingP<'a>,
+        mut RupCheckP,
+        mut TmpDataP,
+        mut VariablesP,
+    ),
+    input: impl io::Read,
+) -> Result<(), CheckerError> {
+    let mut parser = DratParser::default();
+    let mut input = io::BufReader::new(input);
+
+    if ctx.part(ClauseHasherP).rename_in_buffered_solver_var_names {
+        rehash(ctx.borrow());
+    }
+
+    let mut propagator = Propagator::new(ctx.part(ClausesP));
+
+    while !ctx.part(CheckerStateP).unsat {
+        ctx.part_mut(CheckerStateP).step += 1;
+
+        let step = ctx.part(CheckerStateP).step;
+
+        report_progress(ctx.borrow(), step);
+
+        match parser.parse_step(&mut input) {
+            Ok(Some(step)) if step.add => check_lemma(ctx.borrow(), &mut propagator, step.clause)?,
+            Ok(Some(step)) => delete_lemma(ctx.borrow(), &mut propagator, step.clause)?,
+            Ok(None) => {
+                return Err(CheckerError::check_failed(
+                    step,
+                    "proof does not derive the empty clause".to_string(),
+                ))
+            }
+            Err(err) => match err.downcast::<io::Error>() {
+                Ok(io_err) => {
+                    if io_err.kind() == io::ErrorKind::UnexpectedEof {
+                        return Err(CheckerError::ProofIncomplete { step });
+                    } else {
+                        return Err(CheckerError::IoError {
+                            step,
+                            cause: io_err,
+                        });
+                    }
+                }
+                Err(err) => return Err(CheckerError::ParseError { step, cause: err }),
+            },
+        }
+    }
+
+    process_unit_conflicts(ctx.borrow())
+}
+
+/// Check and add a lemma of a DRAT proof.
+fn check_lemma<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut CheckerStateP,
+        mut ClauseHasherP,
+        mut ClausesP,
+        mut ProcessingP<'a>,
+        mut RupCheckP,
+        mut TmpDataP,
+        mut VariablesP,
+    ),
+    propagator: &mut Propagator,
+    lits: &[Lit],
+) -> Result<(), CheckerError> {
+    let mut tmp = take(&mut ctx.part_mut(TmpDataP).tmp);
+
+    if copy_canonical(&mut tmp, lits) {
+        // Tautologies are always redundant and never needed as they cannot propagate.
+        ctx.part_mut(TmpDataP).tmp = tmp;
+        return Ok(());
+    }
+
+    for &lit in tmp.iter() {
+        ensure_var(ctx.borrow(), lit.var());
+    }
+
+    let mut propagation_hashes = vec![];
+
+    if propagator.find_propagation_hashes(
+        ctx.part(ClausesP),
+        ctx.part(ClauseHasherP),
+        &tmp,
+        &mut propagation_hashes,
+    ) {
+        check_clause_with_hashes(ctx.borrow(), &tmp, &propagation_hashes)?;
+
+        let (id, added) = store_lemma(ctx.borrow(), propagator, &tmp);
+
+        if added == StoreClauseResult::New {
+            let (rup_check, mut ctx) = ctx.split_part(RupCheckP);
+            process_step(
+                ctx.borrow(),
+                &CheckedProofStep::AtClause {
+                    id,
+                    redundant: true,
+                    clause: &tmp,
+                    propagations: &rup_check.trace_ids,
+                },
+            )?;
+        }
+    } else if let Some(true) = lits
+        .first()
+        .map(|&pivot| is_rat(ctx.borrow(), propagator, &tmp, pivot))
+        .transpose()?
+    {
+        if ctx.part(ProcessingP).has_processors() {
+            return Err(CheckerError::check_failed(
+                ctx.part(CheckerStateP).step,
+                format!("RAT lemma {:?} cannot be passed to proof processors", tmp),
+            ));
+        }
+
+        store_lemma(ctx.borrow(), propagator, &tmp);
+    } else {
+        return Err(CheckerError::check_failed(
+            ctx.part(CheckerStateP).step,
+            format!("RUP and RAT check failed for {:?}", tmp),
+        ));
+    }
+
+    ctx.part_mut(TmpDataP).tmp = tmp;
+
+    Ok(())
+}
+
+/// Delete a clause as specified by a DRAT proof.
+fn delete_lemma<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut ClausesP,
+        mut ProcessingP<'a>,
+        mut TmpDataP,
+        mut VariablesP,
+        CheckerStateP,
+        ClauseHasherP,
+    ),
+    propagator: &mut Propagator,
+    lits: &[Lit],
+) -> Result<(), CheckerError> {
+    let mut tmp = take(&mut ctx.part_mut(TmpDataP).tmp);
+
+    // Like other DRAT checkers, ignore deletion of unit clauses.
+    if !copy_canonical(&mut tmp, lits) && tmp.len() >= 2 {
+        let hash = ctx.part(ClauseHasherP).clause_hash(&tmp);
+        let ref_count = ctx.part(ClausesP).ref_count(hash, &tmp);
+
+        if ref_count == [0, 0] {
+            log::warn!(
+                "step {}: ignoring deletion of unknown clause {:?}",
+                ctx.part(CheckerStateP).step,
+                tmp
+            );
+        } else {
+            let (id, deleted) = delete_clause(ctx.borrow(), &tmp, ref_count[1] > 0)?;
+
+            if deleted == DeleteClauseResult::Removed {
+                propagator.delete_clause(id);
+
+                // DRAT doesn't distinguish between irredundant and redundant clauses. Deleting any
+                // clause is fine for a refutation, so all deletions are reported the same way.
+                process_step(
+                    ctx.borrow(),
+                    &CheckedProofStep::DeleteClause { id, clause: &tmp },
+                )?;
+            }
+        }
+    }
+
+    ctx.part_mut(TmpDataP).tmp = tmp;
+
+    Ok(())
+}
+
+/// Add a checked lemma to the checker and the propagator.
+///
+/// `lits` must be sorted and free of duplicates.
+fn store_lemma(
+    mut ctx: partial!(
+        Context,
+        mut CheckerStateP,
+        mut ClausesP,
+        mut VariablesP,
+        ClauseHasherP
+    ),
+    propagator: &mut Propagator,
+    lits: &[Lit],
+) -> (u64, StoreClauseResult) {
+    let (id, added) = store_clause(ctx.borrow(), lits, true);
+
+    if added == StoreClauseResult::New {
+        match *lits {
+            [] => (),
+            [lit] => propagator.add_unit(lit),
+            _ => propagator.add_clause(id, lits),
+        }
+    }
+
+    (id, added)
+}
+
+/// Check whether a clause is a resolution asymmetric tautology on the given pivot.
+///
+/// `lits` must be sorted and free of duplicates.
+fn is_rat<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut ClauseHasherP,
+        mut ClausesP,
+        mut ProcessingP<'a>,
+        mut RupCheckP,
+        mut VariablesP,
+        CheckerStateP,
+    ),
+    propagator: &mut Propagator,
+    lits: &[Lit],
+    pivot: Lit,
+) -> Result<bool, CheckerError> {
+    let clauses = ctx.part(ClausesP);
+
+    let mut partners: Vec<Vec<Lit>> = vec![];
+
+    for candidates in clauses.clauses.values() {
+        for clause in candidates.iter() {
+            let clause_lits = clause.lits.slice(&clauses.literal_buffer);
+            if clause_lits.contains(&!pivot) {
+                partners.push(clause_lits.to_vec());
+            }
+        }
+    }
+
+    if let Some((true, _)) = clauses.lit_value(!pivot) {
+        partners.push(vec![!pivot]);
+    }
+
+    let mut resolvent = vec![];
+    let mut canonical_resolvent = vec![];
+    let mut propagation_hashes = vec![];
+
+    for partner in partners.iter() {
+        resolvent.clear();
+        resolvent.extend_from_slice(lits);
+        resolvent.extend(partner.iter().filter(|&&lit| lit != !pivot));
+
+        if copy_canonical(&mut canonical_resolvent, &resolvent) {
+            continue;
+        }
+
+        if !propagator.find_propagation_hashes(
+            ctx.part(ClausesP),
+            ctx.part(ClauseHasherP),
+            &canonical_resolvent,
+            &mut propagation_hashes,
+        ) {
+            return Ok(false);
+        }
+
+        check_clause_with_hashes(ctx.borrow(), &canonical_resolvent, &propagation_hashes)?;
+    }
+
+    Ok(true)
+}
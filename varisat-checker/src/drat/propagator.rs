@@ -0,0 +1,362 @@
+//! Unit propagation used to find the justification of DRAT lemmas.
+use std::mem::take;
+
+use rustc_hash::FxHashMap as HashMap;
+
+use varisat_formula::{Lit, Var};
+use varisat_internal_proof::ClauseHash;
+
+use crate::{clauses::Clauses, hash::ClauseHasher};
+
+/// A clause of at least two literals known to the propagator.
+struct PropClause {
+    /// Literals of the clause, the first two are watched.
+    ///
+    /// When the clause propagates, the propagated literal is the first.
+    lits: Vec<Lit>,
+    /// Whether the clause was deleted. Watches of deleted clauses are removed lazily.
+    deleted: bool,
+}
+
+/// What a search for a conflict found.
+enum Conflict {
+    /// All literals of the clause are false.
+    Clause(usize),
+    /// A literal of the checked clause is already true.
+    Implied(Lit),
+}
+
+/// Watchlist based unit propagation over the checker's clauses.
+///
+/// DRAT proofs do not say which clauses propagate to justify a lemma. This finds a sequence of
+/// propagating clauses, which is then checked by
+/// [`check_clause_with_hashes`](crate::rup::check_clause_with_hashes) like the hints of a native
+/// proof.
+///
+/// The propagations implied by the current unit clauses are kept between checks.
+#[derive(Default)]
+pub struct Propagator {
+    /// Clauses indexed by the order in which they were added.
+    clauses: Vec<PropClause>,
+    /// Maps clause ids to indices into `clauses`.
+    indices: HashMap<u64, usize>,
+    /// Indices of the clauses watching a literal, indexed by the literal's code.
+    watches: Vec<Vec<usize>>,
+    /// Current value of each variable.
+    assignment: Vec<Option<bool>>,
+    /// Clause that propagated a variable, `None` for unit clauses and the checked clause.
+    reasons: Vec<Option<usize>>,
+    /// Assigned literals in the order of assignment.
+    trail: Vec<Lit>,
+    /// Number of trail entries that were propagated.
+    queue_head: usize,
+    /// Length of the trail without the assignments of the current check.
+    top_level: usize,
+    /// A clause that is in conflict with the unit clauses.
+    top_level_conflict: Option<usize>,
+    /// Whether the top level assignment has to be recomputed.
+    ///
+    /// This is set when the reason of a top level propagation is deleted.
+    invalid: bool,
+    /// Flags used during conflict analysis.
+    seen: Vec<bool>,
+}
+
+impl Propagator {
+    /// Create a propagator for the clauses currently known to the checker.
+    pub fn new(clauses: &Clauses) -> Propagator {
+        let mut propagator = Propagator::default();
+
+        for candidates in clauses.clauses.values() {
+            for clause in candidates.iter() {
+                propagator.add_clause(clause.id, clause.lits.slice(&clauses.literal_buffer));
+            }
+        }
+
+        propagator.assign_units(clauses);
+
+        propagator
+    }
+
+    /// Add a clause of at least two literals that was added to the checker.
+    pub fn add_clause(&mut self, id: u64, lits: &[Lit]) {
+        for &lit in lits.iter() {
+            self.resize(lit.index() + 1);
+        }
+
+        let mut lits = lits.to_vec();
+
+        // Watch literals that are not false if possible.
+        for watch in 0..2 {
+            if let Some(pos) =
+                (watch..lits.len()).find(|&pos| self.lit_value(lits[pos]) != Some(false))
+            {
+                lits.swap(watch, pos);
+            }
+        }
+
+        let index = self.clauses.len();
+
+        self.watches[lits[0].code()].push(index);
+        self.watches[lits[1].code()].push(index);
+        self.indices.insert(id, index);
+
+        let values = (self.lit_value(lits[0]), self.lit_value(lits[1]));
+        let first = lits[0];
+
+        self.clauses.push(PropClause {
+            lits,
+            deleted: false,
+        });
+
+        match values {
+            (None, Some(false)) => {
+                self.assign(first, Some(index));
+                self.propagate_top_level();
+            }
+            (Some(false), _) if self.top_level_conflict.is_none() => {
+                self.top_level_conflict = Some(index);
+            }
+            _ => (),
+        }
+    }
+
+    /// Add a unit clause that was added to the checker.
+    pub fn add_unit(&mut self, lit: Lit) {
+        self.resize(lit.index() + 1);
+
+        match self.lit_value(lit) {
+            None => {
+                self.assign(lit, None);
+                self.propagate_top_level();
+            }
+            Some(true) => self.reasons[lit.index()] = None,
+            Some(false) => self.invalid = true,
+        }
+    }
+
+    /// Remove a clause that was removed from the checker.
+    pub fn delete_clause(&mut self, id: u64) {
+        if let Some(index) = self.indices.remove(&id) {
+            let clause = &mut self.clauses[index];
+            let lits = take(&mut clause.lits);
+            clause.deleted = true;
+
+            if self.reasons[lits[0].index()] == Some(index)
+                || self.top_level_conflict == Some(index)
+            {
+                self.invalid = true;
+            }
+        }
+    }
+
+    /// Search for a sequence of clause hashes that shows that a clause is an asymmetric tautology.
+    ///
+    /// The hashes are written to `propagation_hashes` in the order in which the clauses propagate,
+    /// ending with the conflicting clause. If a literal of the clause is a unit clause the
+    /// sequence is empty.
+    ///
+    /// `lits` must be sorted and free of duplicates.
+    pub fn find_propagation_hashes(
+        &mut self,
+        clauses: &Clauses,
+        hasher: &ClauseHasher,
+        lits: &[Lit],
+        propagation_hashes: &mut Vec<ClauseHash>,
+    ) -> bool {
+        propagation_hashes.clear();
+
+        self.resize(clauses.unit_clauses.len());
+
+        if self.invalid {
+            self.invalid = false;
+            self.top_level_conflict = None;
+            self.backtrack(0);
+            self.assign_units(clauses);
+        }
+
+        let mut conflict = self.top_level_conflict.map(Conflict::Clause);
+
+        if conflict.is_none() {
+            for &lit in lits.iter() {
+                match self.lit_value(lit) {
+                    Some(true) => {
+                        conflict = Some(Conflict::Implied(lit));
+                        break;
+                    }
+                    Some(false) => (),
+                    None => self.assign(!lit, None),
+                }
+            }
+        }
+
+        if conflict.is_none() {
+            conflict = self.propagate().map(Conflict::Clause);
+        }
+
+        if let Some(conflict) = &conflict {
+            self.analyze(hasher, conflict, propagation_hashes);
+        }
+
+        self.backtrack(self.top_level);
+
+        conflict.is_some()
+    }
+
+    /// Make sure that all variables below `var_count` can be assigned.
+    fn resize(&mut self, var_count: usize) {
+        if self.assignment.len() < var_count {
+            self.assignment.resize(var_count, None);
+            self.reasons.resize(var_count, None);
+            self.seen.resize(var_count, false);
+            self.watches.resize_with(var_count * 2, Vec::new);
+        }
+    }
+
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.assignment[lit.index()].map(|value| value ^ lit.is_negative())
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        self.assignment[lit.index()] = Some(lit.is_positive());
+        self.reasons[lit.index()] = reason;
+        self.trail.push(lit);
+    }
+
+    fn backtrack(&mut self, trail_len: usize) {
+        for lit in self.trail.drain(trail_len..) {
+            self.assignment[lit.index()] = None;
+            self.reasons[lit.index()] = None;
+        }
+        self.queue_head = self.queue_head.min(trail_len);
+    }
+
+    /// Assign and propagate the checker's unit clauses.
+    fn assign_units(&mut self, clauses: &Clauses) {
+        self.resize(clauses.unit_clauses.len());
+
+        for (index, unit) in clauses.unit_clauses.iter().enumerate() {
+            if let Some(unit) = unit {
+                self.assign(Var::from_index(index).lit(unit.value), None);
+            }
+        }
+
+        self.propagate_top_level();
+    }
+
+    /// Propagate assignments implied by the unit clauses.
+    fn propagate_top_level(&mut self) {
+        if self.top_level_conflict.is_none() {
+            self.top_level_conflict = self.propagate();
+        }
+        self.top_level = self.trail.len();
+    }
+
+    /// Propagate all assignments on the trail.
+    ///
+    /// Returns a conflicting clause if one is found.
+    fn propagate(&mut self) -> Option<usize> {
+        while let Some(&lit) = self.trail.get(self.queue_head) {
+            self.queue_head += 1;
+
+            let false_lit = !lit;
+            let mut watches = take(&mut self.watches[false_lit.code()]);
+            let mut kept = 0;
+            let mut conflict = None;
+
+            for pos in 0..watches.len() {
+                let index = watches[pos];
+
+                if conflict.is_some() {
+                    watches[kept] = index;
+                    kept += 1;
+                    continue;
+                }
+
+                let assignment = &self.assignment;
+                let lit_value =
+                    |lit: Lit| assignment[lit.index()].map(|value| value ^ lit.is_negative());
+
+                let clause = &mut self.clauses[index];
+
+                if clause.deleted {
+                    continue;
+                }
+
+                if clause.lits[0] == false_lit {
+                    clause.lits.swap(0, 1);
+                }
+
+                let other = clause.lits[0];
+                let other_value = lit_value(other);
+
+                if other_value != Some(true) {
+                    if let Some(replacement) = (2..clause.lits.len())
+                        .find(|&pos| lit_value(clause.lits[pos]) != Some(false))
+                    {
+                        clause.lits.swap(1, replacement);
+                        self.watches[clause.lits[1].code()].push(index);
+                        continue;
+                    }
+                }
+
+                watches[kept] = index;
+                kept += 1;
+
+                match other_value {
+                    None => self.assign(other, Some(index)),
+                    Some(false) => conflict = Some(index),
+                    Some(true) => (),
+                }
+            }
+
+            watches.truncate(kept);
+            self.watches[false_lit.code()] = watches;
+
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Collect the hashes of all clauses involved in a conflict.
+    fn analyze(
+        &mut self,
+        hasher: &ClauseHasher,
+        conflict: &Conflict,
+        propagation_hashes: &mut Vec<ClauseHash>,
+    ) {
+        let conflict_hash = match *conflict {
+            Conflict::Clause(index) => {
+                let lits = &self.clauses[index].lits;
+                for &lit in lits.iter() {
+                    self.seen[lit.index()] = true;
+                }
+                Some(hasher.clause_hash(lits))
+            }
+            Conflict::Implied(lit) => {
+                self.seen[lit.index()] = true;
+                None
+            }
+        };
+
+        for &lit in self.trail.iter().rev() {
+            if !self.seen[lit.index()] {
+                continue;
+            }
+            self.seen[lit.index()] = false;
+
+            if let Some(reason) = self.reasons[lit.index()] {
+                let lits = &self.clauses[reason].lits;
+                for &reason_lit in lits[1..].iter() {
+                    self.seen[reason_lit.index()] = true;
+                }
+                propagation_hashes.push(hasher.clause_hash(lits));
+            }
+        }
+
+        propagation_hashes.reverse();
+        propagation_hashes.extend(conflict_hash);
+    }
+}
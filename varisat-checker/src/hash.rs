@@ -36,7 +36,14 @@ impl Default for ClauseHasher {
 impl ClauseHasher {
     /// Compute a clause hash of the current bit size
     pub fn clause_hash(&self, lits: &[Lit]) -> ClauseHash {
-        let shift_bits = ClauseHash::max_value().count_ones() - self.hash_bits;
+        self.truncate_hash(self.full_clause_hash(lits))
+    }
+
+    /// Compute a clause hash using all bits
+    ///
+    /// This is stored for each clause, so that changing the hash size doesn't require
+    /// recomputing all hashes.
+    pub fn full_clause_hash(&self, lits: &[Lit]) -> ClauseHash {
         let mut hash = 0;
         for &lit in lits.iter() {
             match self.solver_var_names.get(&lit.var()) {
@@ -44,15 +51,25 @@ impl ClauseHasher {
                 None => hash ^= lit_code_hash(lit.code() + Var::max_count() * 2),
             }
         }
-        hash >> shift_bits
+        hash
+    }
+
+    /// Reduce a clause hash using all bits to the current bit size
+    pub fn truncate_hash(&self, full_hash: ClauseHash) -> ClauseHash {
+        let shift_bits = ClauseHash::MAX.count_ones() - self.hash_bits;
+        full_hash >> shift_bits
     }
 }
 
 /// Recompute all clause hashes if necessary
+///
+/// The stored full hashes of the clauses are only recomputed when a variable was renamed.
 pub fn rehash(mut ctx: partial!(Context, mut ClauseHasherP, mut ClausesP)) {
     let (hasher, mut ctx) = ctx.split_part_mut(ClauseHasherP);
     let clauses = ctx.part_mut(ClausesP);
 
+    let renamed = hasher.rename_in_buffered_solver_var_names;
+
     for (global, solver) in hasher.buffered_solver_var_names.drain(..) {
         if let Some(solver) = solver {
             hasher.solver_var_names.insert(global, solver);
@@ -65,8 +82,12 @@ pub fn rehash(mut ctx: partial!(Context, mut ClauseHasherP, mut ClausesP)) {
     let mut old_clauses = take(&mut clauses.clauses);
 
     for (_, mut candidates) in old_clauses.drain() {
-        for clause in candidates.drain(..) {
-            let hash = hasher.clause_hash(clause.lits.slice(&clauses.literal_buffer));
+        for mut clause in candidates.drain(..) {
+            if renamed {
+                clause.full_hash =
+                    hasher.full_clause_hash(clause.lits.slice(&clauses.literal_buffer));
+            }
+            let hash = hasher.truncate_hash(clause.full_hash);
             let candidates = clauses.clauses.entry(hash).or_default();
             candidates.push(clause);
         }
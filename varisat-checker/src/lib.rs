@@ -3,7 +3,7 @@
 use std::io;
 
 use anyhow::Error;
-use partial_ref::{IntoPartialRefMut, PartialRef};
+use partial_ref::{IntoPartialRef, IntoPartialRefMut, PartialRef};
 use thiserror::Error;
 
 use varisat_dimacs::DimacsParser;
@@ -13,24 +13,36 @@ pub mod internal;
 
 mod clauses;
 mod context;
+mod drat;
 mod hash;
+mod lrat;
+mod model;
+mod parallel;
 mod processing;
 mod rup;
 mod sorted_lits;
 mod state;
+mod step_counter;
 mod tmp;
 mod transcript;
+mod unsat_core;
 mod variables;
 
+pub use model::CollectModel;
 pub use processing::{
     CheckedProofStep, CheckedSamplingMode, CheckedUserVar, CheckerData, ProofProcessor,
     ResolutionPropagations,
 };
+pub use state::CheckerStats;
+pub use step_counter::{StepCounter, StepCounts};
 pub use transcript::{ProofTranscriptProcessor, ProofTranscriptStep};
+pub use unsat_core::UnsatCore;
 
 use clauses::add_clause;
 use context::Context;
-use state::check_proof;
+use drat::check_drat_proof;
+use lrat::check_lrat_proof;
+use state::{check_proof, check_proof_parallel, checker_stats};
 
 /// Possible errors while checking a varisat proof.
 #[derive(Debug, Error)]
@@ -132,11 +144,88 @@ impl<'a> Checker<'a> {
         self.ctx.processing.transcript_processors.push(processor);
     }
 
+    /// Track the input clauses used to derive the final conflict.
+    ///
+    /// The tracking is available through [`unsat_core`](Checker::unsat_core). This has to be called
+    /// before loading any clauses or checking any proofs.
+    pub fn enable_unsat_core(&mut self) {
+        self.ctx.processing.unsat_core = Some(UnsatCore::new());
+    }
+
+    /// Input clauses used to derive the final conflict.
+    ///
+    /// Returns `None` if [`enable_unsat_core`](Checker::enable_unsat_core) was not called.
+    pub fn unsat_core(&self) -> Option<&UnsatCore> {
+        self.ctx.processing.unsat_core.as_ref()
+    }
+
+    /// Set a callback that is invoked periodically while checking a proof.
+    ///
+    /// The callback is invoked every 100k proof steps with the current step number. This has to be
+    /// called before checking any proofs.
+    pub fn set_progress_callback(&mut self, callback: &'a mut dyn FnMut(u64)) {
+        self.ctx.processing.progress_callback = Some(callback);
+    }
+
     /// Checks a proof in the native Varisat format.
     pub fn check_proof(&mut self, input: impl io::Read) -> Result<(), CheckerError> {
         let mut ctx = self.ctx.into_partial_ref_mut();
         check_proof(ctx.borrow(), input)
     }
+
+    /// Checks a proof in the native Varisat format using multiple threads.
+    ///
+    /// The proof steps are processed in order by the calling thread, while the RUP checks of
+    /// lemmas are buffered and performed by `threads` worker threads. This accepts and rejects the
+    /// same proofs as [`check_proof`](Checker::check_proof) and reports the same failing step.
+    /// Error messages of failed RUP checks may be less detailed. The speedup is limited, as looking
+    /// up the clauses involved in a check still happens sequentially.
+    ///
+    /// Proof processors need the checks to be performed in order, so when any are present, this
+    /// falls back to sequential checking.
+    pub fn check_proof_parallel(
+        &mut self,
+        input: impl io::Read,
+        threads: usize,
+    ) -> Result<(), CheckerError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        check_proof_parallel(ctx.borrow(), input, threads)
+    }
+
+    /// Checks a proof in the text or binary DRAT format.
+    ///
+    /// Lemmas are checked to be asymmetric tautologies (RUP) or, failing that, resolution
+    /// asymmetric tautologies (RAT) on their first literal. The proof has to derive the empty
+    /// clause. RUP lemmas are passed to proof processors like AT clauses of native proofs, RAT
+    /// lemmas cannot be processed and cause an error when processors are present.
+    ///
+    /// The propagations justifying a lemma are found using watchlists and then checked like the
+    /// propagation hashes of native proofs. Finding the partner clauses of a RAT lemma scans the
+    /// whole formula.
+    pub fn check_drat_proof(&mut self, input: impl io::Read) -> Result<(), CheckerError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        check_drat_proof(ctx.borrow(), input)
+    }
+
+    /// Checks a proof in the text or compressed LRAT format.
+    ///
+    /// This verifies LRAT proofs produced by other tools. The input clauses have the ids 1, 2, ...
+    /// in the order they were added to the checker, including duplicated and tautological clauses,
+    /// so this should be called on a checker that only has the formula loaded. Each lemma is
+    /// checked using only its hints, RAT lemmas using their first literal as pivot. The proof has
+    /// to derive the empty clause.
+    ///
+    /// LRAT steps cannot be passed to proof processors, so this fails when any are present.
+    pub fn check_lrat_proof(&mut self, input: impl io::Read) -> Result<(), CheckerError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        check_lrat_proof(ctx.borrow(), input)
+    }
+
+    /// Statistics about the work performed so far.
+    pub fn stats(&self) -> CheckerStats {
+        let mut ctx = self.ctx.into_partial_ref();
+        checker_stats(ctx.borrow())
+    }
 }
 
 #[cfg(test)]
@@ -145,7 +234,7 @@ mod tests {
 
     use varisat_internal_proof::{DeleteClauseProof, ProofStep};
 
-    use varisat_formula::{cnf_formula, lits, Var};
+    use varisat_formula::{cnf_formula, lit, lits, Var};
 
     fn expect_check_failed(result: Result<(), CheckerError>, contains: &str) {
         match result {
@@ -154,6 +243,347 @@ mod tests {
         }
     }
 
+    #[test]
+    fn drat_rup_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2, 3;
+                1, 2, -3;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        checker
+            .check_drat_proof(&b"c lemmas\n1 2 0\nd 1 2 -3 0\n1 0\n0\n"[..])
+            .unwrap();
+
+        assert!(checker.ctx.checker_state.unsat);
+    }
+
+    #[test]
+    fn drat_rat_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        // Defines 3 as the conjunction of 1 and 2 using RAT lemmas.
+        checker
+            .check_drat_proof(&b"3 -1 -2 0\n-3 1 0\n-3 2 0\n2 0\n0\n"[..])
+            .unwrap();
+    }
+
+    #[test]
+    fn drat_deleted_reason() {
+        let formula = cnf_formula![
+            1;
+            3;
+            -1, 2;
+            -3, 2;
+            -2, 4;
+            -2, -4;
+        ];
+
+        // One of these deletes the clause propagating 2, the other one still implies it.
+        for proof in [&b"d -1 2 0\n0\n"[..], &b"d -3 2 0\n0\n"[..]].iter() {
+            let mut checker = Checker::new();
+            checker.add_formula(&formula).unwrap();
+            checker.check_drat_proof(&proof[..]).unwrap();
+        }
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+
+        expect_check_failed(
+            checker.check_drat_proof(&b"d -1 2 0\nd -3 2 0\n0\n"[..]),
+            "RUP and RAT check failed",
+        );
+    }
+
+    #[test]
+    fn binary_drat_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        checker
+            .check_drat_proof(&[b'a', 4, 0, b'a', 0][..])
+            .unwrap();
+    }
+
+    #[test]
+    fn lrat_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        checker
+            .check_lrat_proof(&b"6 2 0 2 3 0\n6 d 1 2 3 0\n7 0 6 4 5 0\n"[..])
+            .unwrap();
+
+        assert!(checker.ctx.checker_state.unsat);
+    }
+
+    #[test]
+    fn lrat_rat_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        checker
+            .check_lrat_proof(&b"5 3 1 0 0\n6 -3 2 0 -5 1 0\n7 2 0 1 2 0\n8 0 7 3 4 0\n"[..])
+            .unwrap();
+    }
+
+    #[test]
+    fn binary_lrat_proof() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        checker
+            .check_lrat_proof(
+                &[
+                    b'a', 10, 4, 0, 2, 4, 0, b'd', 2, 0, b'a', 12, 0, 10, 6, 8, 0,
+                ][..],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn lrat_invalid_lemma() {
+        let formula = cnf_formula![
+            1, 2;
+            -1, 2;
+            1, -2;
+            -1, -2;
+        ];
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+        expect_check_failed(
+            checker.check_lrat_proof(&b"5 2 0 1 0\n"[..]),
+            "RUP check failed",
+        );
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+        expect_check_failed(
+            checker.check_lrat_proof(&b"5 2 0 3 0\n"[..]),
+            "hint clause 3 is satisfied",
+        );
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+        expect_check_failed(
+            checker.check_lrat_proof(&b"5 2 0 1 2 0\n"[..]),
+            "does not derive the empty clause",
+        );
+    }
+
+    #[test]
+    fn progress_callback() {
+        let mut steps = vec![];
+        let mut callback = |step| steps.push(step);
+
+        {
+            let mut checker = Checker::new();
+            checker.set_progress_callback(&mut callback);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    -1, 2;
+                    1, -2;
+                    -1, -2;
+                ])
+                .unwrap();
+
+            let mut proof = b"1 -1 0\n".repeat(250000);
+            proof.extend_from_slice(b"2 0\n0\n");
+
+            checker.check_drat_proof(&proof[..]).unwrap();
+        }
+
+        assert_eq!(steps, vec![100000, 200000]);
+    }
+
+    #[test]
+    fn input_clause_indices() {
+        #[derive(Default)]
+        struct InputClauses {
+            indices: Vec<(u64, Option<usize>)>,
+            same_as: Vec<Option<usize>>,
+        }
+
+        impl ProofProcessor for InputClauses {
+            fn process_step(
+                &mut self,
+                step: &CheckedProofStep,
+                data: CheckerData,
+            ) -> Result<(), Error> {
+                match *step {
+                    CheckedProofStep::AddClause { id, .. }
+                    | CheckedProofStep::TautologicalClause { id, .. } => {
+                        self.indices.push((id, data.input_clause_index(id)));
+                    }
+                    CheckedProofStep::DuplicatedClause { id, same_as_id, .. } => {
+                        self.indices.push((id, data.input_clause_index(id)));
+                        self.same_as.push(data.input_clause_index(same_as_id));
+                    }
+                    CheckedProofStep::AtClause { id, .. } => {
+                        assert_eq!(data.input_clause_index(id), None);
+                    }
+                    _ => (),
+                }
+                Ok(())
+            }
+        }
+
+        let mut input_clauses = InputClauses::default();
+
+        {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut input_clauses);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    2, 1;
+                    -1, 1;
+                    3;
+                    3;
+                    -3, 4;
+                ])
+                .unwrap();
+
+            let hash = checker.ctx.clause_hasher.clause_hash(&lits![-3, 4]);
+
+            checker
+                .self_check_step(ProofStep::AtClause {
+                    redundant: true,
+                    clause: &lits![4],
+                    propagation_hashes: &[hash],
+                })
+                .unwrap();
+
+            checker.add_clause(&lits![-4, 2]).unwrap();
+            checker.add_clause(&lits![-4]).unwrap();
+
+            // Input clauses are ignored once the checker is unsat.
+            checker.add_clause(&lits![1, 3]).unwrap();
+        }
+
+        let indices: Vec<_> = input_clauses
+            .indices
+            .iter()
+            .map(|&(_, index)| index)
+            .collect();
+        assert_eq!(indices, (0..8).map(Some).collect::<Vec<_>>());
+
+        let ids: Vec<_> = input_clauses.indices.iter().map(|&(id, _)| id).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(input_clauses.same_as, vec![Some(0), Some(3)]);
+    }
+
+    #[test]
+    fn checker_stats() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        assert_eq!(checker.stats().clauses, 4);
+
+        checker.check_drat_proof(&b"2 0\n0\n"[..]).unwrap();
+
+        let stats = checker.stats();
+
+        assert_eq!(stats.steps, 2);
+        assert!(stats.propagations > 0);
+        assert_eq!(stats.clauses, 5);
+    }
+
+    #[test]
+    fn drat_invalid_lemma() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2, 3;
+                -1, 2;
+            ])
+            .unwrap();
+
+        expect_check_failed(
+            checker.check_drat_proof(&b"-2 0\n0\n"[..]),
+            "RUP and RAT check failed",
+        );
+    }
+
+    #[test]
+    fn drat_no_empty_clause() {
+        let mut checker = Checker::new();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+            ])
+            .unwrap();
+
+        expect_check_failed(
+            checker.check_drat_proof(&b"2 0\n"[..]),
+            "does not derive the empty clause",
+        );
+    }
+
     #[test]
     fn conflicting_units() {
         let mut checker = Checker::new();
@@ -253,6 +683,57 @@ mod tests {
         )
     }
 
+    #[test]
+    fn change_hash_bits() {
+        let mut checker = Checker::new();
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                -1, 2;
+                1, -2;
+                -1, -2;
+            ])
+            .unwrap();
+
+        for &bits in [8, 64].iter() {
+            checker
+                .self_check_step(ProofStep::ChangeHashBits { bits })
+                .unwrap();
+        }
+
+        let hashes = [
+            checker.ctx.clause_hasher.clause_hash(&lits![1, 2]),
+            checker.ctx.clause_hasher.clause_hash(&lits![-1, 2]),
+        ];
+
+        checker
+            .self_check_step(ProofStep::AtClause {
+                redundant: false,
+                clause: [lit!(2)][..].into(),
+                propagation_hashes: hashes[..].into(),
+            })
+            .unwrap();
+
+        checker
+            .self_check_step(ProofStep::ChangeHashBits { bits: 16 })
+            .unwrap();
+
+        let hashes = [
+            checker.ctx.clause_hasher.clause_hash(&lits![1, -2]),
+            checker.ctx.clause_hasher.clause_hash(&lits![-1, -2]),
+        ];
+
+        checker
+            .self_check_step(ProofStep::AtClause {
+                redundant: false,
+                clause: [][..].into(),
+                propagation_hashes: hashes[..].into(),
+            })
+            .unwrap();
+
+        assert!(checker.ctx.checker_state.unsat);
+    }
+
     #[test]
     fn clause_check_failed() {
         let mut checker = Checker::new();
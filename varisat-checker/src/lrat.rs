@@ -0,0 +1,542 @@
+//! Checking of proofs in the LRAT format.
+use std::io::{self, BufRead};
+
+use anyhow::{anyhow, Error};
+use partial_ref::{partial, PartialRef};
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use varisat_formula::{Lit, Var};
+
+use crate::{
+    clauses::{UnitClause, UnitId},
+    context::{parts::*, Context},
+    state::report_progress,
+    CheckerError,
+};
+
+/// A single step of an LRAT proof.
+enum LratStep<'a> {
+    /// Add a clause using the given hints.
+    ///
+    /// Negative hints start the hints of a RAT candidate clause.
+    Add {
+        id: u64,
+        clause: &'a [Lit],
+        hints: &'a [i64],
+    },
+    /// Delete the clauses with the given ids.
+    Delete { ids: &'a [u64] },
+}
+
+/// Parser for LRAT proofs in the text or compressed binary format.
+///
+/// Binary proofs are detected by the first byte, as text steps start with a clause id.
+struct LratParser<R: BufRead> {
+    source: R,
+    /// Whether the proof uses the binary format, `None` before the first step.
+    binary: Option<bool>,
+    lit_buf: Vec<Lit>,
+    hint_buf: Vec<i64>,
+    id_buf: Vec<u64>,
+}
+
+impl<R: BufRead> LratParser<R> {
+    fn new(source: R) -> Self {
+        LratParser {
+            source,
+            binary: None,
+            lit_buf: vec![],
+            hint_buf: vec![],
+            id_buf: vec![],
+        }
+    }
+
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.source.fill_buf() {
+                Ok(buf) => return Ok(buf.first().cloned()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek_byte()?;
+        if byte.is_some() {
+            self.source.consume(1);
+        }
+        Ok(byte)
+    }
+
+    /// Parse the next step.
+    ///
+    /// Returns `None` at the end of the proof.
+    fn parse_step(&mut self) -> Result<Option<LratStep<'_>>, Error> {
+        self.lit_buf.clear();
+        self.hint_buf.clear();
+        self.id_buf.clear();
+
+        let binary = match self.binary {
+            Some(binary) => binary,
+            None => {
+                let binary = matches!(self.peek_byte()?, Some(b'a') | Some(b'd'));
+                self.binary = Some(binary);
+                binary
+            }
+        };
+
+        let add = if binary {
+            match self.next_byte()? {
+                None => return Ok(None),
+                Some(b'a') => Some(self.binary_number()?),
+                Some(b'd') => None,
+                Some(byte) => return Err(anyhow!("unexpected binary step marker {:#x}", byte)),
+            }
+        } else {
+            match self.text_number()? {
+                None => return Ok(None),
+                Some(id) if !self.text_delete()? => Some(id),
+                Some(_) => None,
+            }
+        };
+
+        let next_number = |parser: &mut Self| -> Result<i64, Error> {
+            if binary {
+                parser.binary_number()
+            } else {
+                parser
+                    .text_number()?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+            }
+        };
+
+        match add {
+            Some(id) => {
+                let id = clause_id(id)?;
+                loop {
+                    match next_number(self)? {
+                        0 => break,
+                        lit if lit.unsigned_abs() as usize > Var::max_count() => {
+                            return Err(anyhow!("literal index is too large"))
+                        }
+                        lit => self.lit_buf.push(Lit::from_dimacs(lit as isize)),
+                    }
+                }
+                loop {
+                    match next_number(self)? {
+                        0 => break,
+                        hint => self.hint_buf.push(hint),
+                    }
+                }
+                Ok(Some(LratStep::Add {
+                    id,
+                    clause: &self.lit_buf,
+                    hints: &self.hint_buf,
+                }))
+            }
+            None => {
+                loop {
+                    match next_number(self)? {
+                        0 => break,
+                        id => self.id_buf.push(clause_id(id)?),
+                    }
+                }
+                Ok(Some(LratStep::Delete { ids: &self.id_buf }))
+            }
+        }
+    }
+
+    /// Skip whitespace, returning whether the input continues with a `d`.
+    fn text_delete(&mut self) -> io::Result<bool> {
+        while matches!(self.peek_byte()?, Some(byte) if byte.is_ascii_whitespace()) {
+            self.source.consume(1);
+        }
+        if self.peek_byte()? == Some(b'd') {
+            self.source.consume(1);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Parse a number in the text format, returning `None` at the end of the input.
+    fn text_number(&mut self) -> Result<Option<i64>, Error> {
+        while matches!(self.peek_byte()?, Some(byte) if byte.is_ascii_whitespace()) {
+            self.source.consume(1);
+        }
+
+        let negative = self.peek_byte()? == Some(b'-');
+        if negative {
+            self.source.consume(1);
+        }
+
+        let mut number: Option<i64> = None;
+
+        loop {
+            match self.peek_byte()? {
+                Some(byte @ b'0'..=b'9') => {
+                    self.source.consume(1);
+                    number = Some(
+                        number
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|value| value.checked_add((byte - b'0') as i64))
+                            .ok_or_else(|| anyhow!("number is too large"))?,
+                    );
+                }
+                None if number.is_none() && !negative => return Ok(None),
+                None | Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') if number.is_some() => {
+                    let number = number.unwrap();
+                    return Ok(Some(if negative { -number } else { number }));
+                }
+                None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Some(byte) => return Err(anyhow!("unexpected character {:?}", byte as char)),
+            }
+        }
+    }
+
+    /// Parse a number in the binary format.
+    ///
+    /// Numbers are stored as twice their absolute value plus one for negative numbers using a
+    /// variable length encoding.
+    fn binary_number(&mut self) -> Result<i64, Error> {
+        let mut code: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self
+                .next_byte()?
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            if shift >= 63 {
+                return Err(anyhow!("number encoding is too long"));
+            }
+            code |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let value = (code >> 1) as i64;
+        Ok(if code & 1 == 1 { -value } else { value })
+    }
+}
+
+/// Convert a parsed number into a clause id.
+fn clause_id(id: i64) -> Result<u64, Error> {
+    if id > 0 {
+        Ok(id as u64)
+    } else {
+        Err(anyhow!("invalid clause id {}", id))
+    }
+}
+
+/// Clauses and assignment used for checking an LRAT proof.
+#[derive(Default)]
+struct LratCheck {
+    /// Literals of all present clauses indexed by their LRAT id.
+    clauses: HashMap<u64, Vec<Lit>>,
+    /// Value of each variable, indexed by variable.
+    values: Vec<Option<bool>>,
+    /// Assigned variables, to undo assignments after a check.
+    trail: Vec<usize>,
+}
+
+/// Outcome of propagating a single hint clause.
+enum Propagation {
+    Conflict,
+    Unit,
+}
+
+impl LratCheck {
+    /// Value of a literal.
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.values
+            .get(lit.index())
+            .cloned()
+            .flatten()
+            .map(|value| value ^ lit.is_negative())
+    }
+
+    /// Make a literal true.
+    fn assign(&mut self, lit: Lit) {
+        if self.values.len() <= lit.index() {
+            self.values.resize(lit.index() + 1, None);
+        }
+        self.values[lit.index()] = Some(lit.is_positive());
+        self.trail.push(lit.index());
+    }
+
+    /// Undo all assignments made after the trail had the given length.
+    fn backtrack(&mut self, trail_len: usize) {
+        for index in self.trail.drain(trail_len..) {
+            self.values[index] = None;
+        }
+    }
+
+    /// Make all literals of a clause false.
+    ///
+    /// Returns true if this is a conflict as the clause contains a true literal.
+    fn assign_negated(&mut self, lits: &[Lit]) -> bool {
+        for &lit in lits.iter() {
+            match self.lit_value(lit) {
+                Some(true) => return true,
+                Some(false) => (),
+                None => self.assign(!lit),
+            }
+        }
+        false
+    }
+
+    /// Propagate the clause with the given id, which has to be unit or falsified.
+    fn propagate(&mut self, id: u64) -> Result<Propagation, String> {
+        let clause = self
+            .clauses
+            .get(&id)
+            .ok_or_else(|| format!("unknown hint clause id {}", id))?;
+
+        let mut unassigned_count = 0;
+        let mut unassigned_lit = None;
+
+        for &lit in clause.iter() {
+            match self.lit_value(lit) {
+                Some(true) => return Err(format!("hint clause {} is satisfied", id)),
+                Some(false) => (),
+                None => {
+                    unassigned_count += 1;
+                    unassigned_lit = Some(lit);
+                }
+            }
+        }
+
+        match unassigned_lit {
+            None => Ok(Propagation::Conflict),
+            Some(lit) if unassigned_count == 1 => {
+                self.assign(lit);
+                Ok(Propagation::Unit)
+            }
+            _ => Err(format!("hint clause {} is not unit", id)),
+        }
+    }
+
+    /// Propagate hint clauses until a conflict is found.
+    ///
+    /// Returns whether a conflict was found.
+    fn propagate_hints(&mut self, hints: &[i64]) -> Result<bool, String> {
+        for &hint in hints.iter() {
+            if let Propagation::Conflict = self.propagate(hint as u64)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Check that a clause is a RUP or RAT lemma using the given hints.
+    fn check_lemma(&mut self, clause: &[Lit], hints: &[i64]) -> Result<(), String> {
+        if self.assign_negated(clause) {
+            // Tautologies are always redundant.
+            return Ok(());
+        }
+
+        let rup_hints = hints.iter().take_while(|&&hint| hint > 0).count();
+
+        if self.propagate_hints(&hints[..rup_hints])? {
+            return Ok(());
+        }
+
+        let rat_hints = &hints[rup_hints..];
+
+        let pivot = match clause.first() {
+            Some(&pivot) => pivot,
+            None => return Err("RUP check failed for the empty clause".to_string()),
+        };
+
+        let candidates: Vec<u64> = self
+            .clauses
+            .iter()
+            .filter(|(_, lits)| lits.contains(&!pivot))
+            .map(|(&id, _)| id)
+            .collect();
+
+        if rat_hints.is_empty() && !candidates.is_empty() {
+            return Err(format!("RUP check failed for {:?}", clause));
+        }
+
+        let mut candidate_hints: HashMap<u64, &[i64]> = HashMap::default();
+
+        for (index, &hint) in rat_hints.iter().enumerate() {
+            if hint < 0 {
+                let end = rat_hints[index + 1..]
+                    .iter()
+                    .position(|&hint| hint < 0)
+                    .map_or(rat_hints.len(), |len| index + 1 + len);
+                candidate_hints.insert(hint.unsigned_abs(), &rat_hints[index + 1..end]);
+            }
+        }
+
+        for id in candidates {
+            let lits = self.clauses[&id].clone();
+
+            if lits
+                .iter()
+                .any(|&lit| lit != !pivot && clause.contains(&!lit))
+            {
+                // The resolvent is a tautology.
+                continue;
+            }
+
+            let hints = candidate_hints.get(&id).ok_or_else(|| {
+                format!(
+                    "RAT check failed for {:?}, missing hints for clause {}",
+                    clause, id
+                )
+            })?;
+
+            let trail_len = self.trail.len();
+
+            let lits: Vec<Lit> = lits.into_iter().filter(|&lit| lit != !pivot).collect();
+            let conflict = self.assign_negated(&lits) || self.propagate_hints(hints)?;
+
+            self.backtrack(trail_len);
+
+            if !conflict {
+                return Err(format!(
+                    "RAT check failed for {:?} on clause {}",
+                    clause, id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks a proof in the text or compressed LRAT format.
+///
+/// The LRAT ids of the input clauses are their positions in the order they were added, starting
+/// at one. Each lemma is checked using the given hints only, RAT lemmas use their first literal as
+/// pivot. Deletion of unknown clauses is ignored. Checking stops as soon as the empty clause is
+/// derived.
+pub fn check_lrat_proof<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut CheckerStateP,
+        mut ProcessingP<'a>,
+        ClausesP,
+    ),
+    input: impl io::Read,
+) -> Result<(), CheckerError> {
+    if ctx.part(CheckerStateP).unsat {
+        return Ok(());
+    }
+
+    if ctx.part(ProcessingP).has_processors() {
+        return Err(CheckerError::check_failed(
+            ctx.part(CheckerStateP).step,
+            "LRAT proofs cannot be passed to proof processors".to_string(),
+        ));
+    }
+
+    let mut check = LratCheck::default();
+
+    load_input_clauses(ctx.borrow(), &mut check);
+
+    let mut parser = LratParser::new(io::BufReader::new(input));
+
+    loop {
+        ctx.part_mut(CheckerStateP).step += 1;
+
+        let step = ctx.part(CheckerStateP).step;
+
+        report_progress(ctx.borrow(), step);
+
+        match parser.parse_step() {
+            Ok(Some(LratStep::Add { id, clause, hints })) => {
+                if check.clauses.contains_key(&id) {
+                    return Err(CheckerError::check_failed(
+                        step,
+                        format!("clause id {} is already in use", id),
+                    ));
+                }
+
+                let result = check.check_lemma(clause, hints);
+                check.backtrack(0);
+                result.map_err(|msg| CheckerError::check_failed(step, msg))?;
+
+                if clause.is_empty() {
+                    ctx.part_mut(CheckerStateP).unsat = true;
+                    return Ok(());
+                }
+
+                check.clauses.insert(id, clause.to_vec());
+            }
+            Ok(Some(LratStep::Delete { ids })) => {
+                // Deleting unknown clauses is harmless, this also happens when input clauses are
+                // deleted that the checker doesn't store, like tautologies.
+                for id in ids.iter() {
+                    check.clauses.remove(id);
+                }
+            }
+            Ok(None) => {
+                return Err(CheckerError::check_failed(
+                    step,
+                    "proof does not derive the empty clause".to_string(),
+                ))
+            }
+            Err(err) => match err.downcast::<io::Error>() {
+                Ok(io_err) => {
+                    if io_err.kind() == io::ErrorKind::UnexpectedEof {
+                        return Err(CheckerError::ProofIncomplete { step });
+                    } else {
+                        return Err(CheckerError::IoError {
+                            step,
+                            cause: io_err,
+                        });
+                    }
+                }
+                Err(err) => return Err(CheckerError::ParseError { step, cause: err }),
+            },
+        }
+    }
+}
+
+/// Copy the input clauses from the checker's clause storage using their LRAT ids.
+fn load_input_clauses(ctx: partial!(Context, ClausesP), check: &mut LratCheck) {
+    let clauses = ctx.part(ClausesP);
+
+    let input_ids: HashSet<u64> = clauses
+        .input_clause_ids
+        .iter()
+        .filter_map(|ids| ids.clause_id)
+        .collect();
+
+    let mut lits_by_id: HashMap<u64, Vec<Lit>> = HashMap::default();
+
+    for candidates in clauses.clauses.values() {
+        for clause in candidates.iter() {
+            if input_ids.contains(&clause.id) {
+                lits_by_id.insert(
+                    clause.id,
+                    clause.lits.slice(&clauses.literal_buffer).to_vec(),
+                );
+            }
+        }
+    }
+
+    for (index, unit) in clauses.unit_clauses.iter().enumerate() {
+        if let Some(UnitClause {
+            id: UnitId::Global(id),
+            value,
+        }) = *unit
+        {
+            if input_ids.contains(&id) {
+                lits_by_id.insert(id, vec![Var::from_index(index).lit(value)]);
+            }
+        }
+    }
+
+    for (index, ids) in clauses.input_clause_ids.iter().enumerate() {
+        if let Some(lits) = ids.clause_id.and_then(|id| lits_by_id.get(&id)) {
+            check.clauses.insert(index as u64 + 1, lits.clone());
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Collection of models verified by the checker.
+use anyhow::Error;
+
+use varisat_formula::Lit;
+
+use crate::processing::{CheckedProofStep, CheckerData, ProofProcessor};
+
+/// Proof processor that stores the last model verified by the checker.
+///
+/// When self checking a satisfiable result, this provides a model that was checked to satisfy
+/// the formula and assumptions, instead of trusting the solver's own model. The model contains
+/// the assigned user variables and may not satisfy clauses added after it was checked.
+#[derive(Default)]
+pub struct CollectModel {
+    model: Option<Vec<Lit>>,
+}
+
+impl CollectModel {
+    /// Create a new model collecting processor.
+    pub fn new() -> CollectModel {
+        CollectModel::default()
+    }
+
+    /// The last verified model.
+    ///
+    /// Returns `None` if no model was checked.
+    pub fn model(&self) -> Option<&[Lit]> {
+        self.model.as_deref()
+    }
+}
+
+impl ProofProcessor for CollectModel {
+    fn process_step(&mut self, step: &CheckedProofStep, data: CheckerData) -> Result<(), Error> {
+        if let CheckedProofStep::Model { assignment } = *step {
+            let model = self.model.get_or_insert_with(Vec::new);
+            model.clear();
+            model.extend(assignment.iter().flat_map(|&lit| {
+                data.user_from_proof_var(lit.var())
+                    .map(|var| var.lit(lit.is_positive()))
+            }));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use varisat_formula::{cnf_formula, lits};
+    use varisat_internal_proof::ProofStep;
+
+    use crate::{internal::SelfChecker, Checker};
+
+    #[test]
+    fn collect_model() {
+        let mut collect_model = CollectModel::new();
+
+        {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut collect_model);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    -1, 3;
+                ])
+                .unwrap();
+
+            assert!(checker
+                .self_check_step(ProofStep::Model {
+                    assignment: &lits![-1, 3],
+                })
+                .is_err());
+
+            checker
+                .self_check_step(ProofStep::Model {
+                    assignment: &lits![-1, 2, 3],
+                })
+                .unwrap();
+        }
+
+        assert_eq!(collect_model.model(), Some(&lits![-1, 2, 3][..]));
+    }
+}
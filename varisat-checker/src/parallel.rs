@@ -0,0 +1,321 @@
+//! Parallel execution of RUP checks.
+//!
+//! When checking a native proof in parallel, the clause database is still maintained by a single
+//! thread processing the proof steps in order. Instead of performing the RUP check of a lemma right
+//! away, that thread looks up the clauses given by the propagation hashes and copies them together
+//! with the current unit assignment into a [`DeferredChecks`] buffer. When the buffer is full, the
+//! buffered checks are split among worker threads.
+//!
+//! This preserves the result of the sequential checker: a RUP check only reads the clause database
+//! and the unit clauses at the time of its proof step and it never changes the state of the
+//! checker, as the checked lemma is added in either case. Copying the relevant part of that state
+//! makes each buffered check independent of all later steps. Lemmas that would fail the check are
+//! added too, but as the first failing check is reported in step order, any later error found by
+//! the sequential thread is discarded in favor of an earlier failed check.
+use std::{ops::Range, thread};
+
+use varisat_formula::Lit;
+use varisat_internal_proof::ClauseHash;
+
+use crate::{clauses::Clauses, CheckerError};
+
+/// Number of buffered literals after which the buffered checks are performed.
+const BUFFER_LIMIT: usize = 1 << 22;
+
+/// A RUP check with copies of the involved clauses.
+struct DeferredCheck {
+    step: u64,
+    /// Range of the checked clause in `lits`.
+    lemma: Range<usize>,
+    /// Range of the propagating clauses in `clauses`.
+    clauses: Range<usize>,
+}
+
+/// Buffer of RUP checks to be performed in parallel.
+pub struct DeferredChecks {
+    threads: usize,
+    checks: Vec<DeferredCheck>,
+    /// Literal ranges of the propagating clauses of all checks.
+    clauses: Vec<Range<usize>>,
+    /// Literals of the checked and propagating clauses of all checks.
+    lits: Vec<Lit>,
+}
+
+impl DeferredChecks {
+    /// Create an empty buffer for checks that will be performed by the given number of threads.
+    pub fn new(threads: usize) -> DeferredChecks {
+        DeferredChecks {
+            threads: threads.max(1),
+            checks: vec![],
+            clauses: vec![],
+            lits: vec![],
+        }
+    }
+
+    /// Add a check whether `lemma` is implied by clauses of the given hashes.
+    ///
+    /// `lemma` must be sorted, free of duplicates and not contain a literal that is true by a unit
+    /// clause. Clauses satisfied by unit clauses are skipped and literals falsified by unit
+    /// clauses are removed when copying the propagating clauses. Returns true if the buffer is
+    /// full and [`run`](DeferredChecks::run) should be called.
+    pub fn add_check(
+        &mut self,
+        clauses: &Clauses,
+        step: u64,
+        lemma: &[Lit],
+        propagation_hashes: &[ClauseHash],
+    ) -> Result<bool, CheckerError> {
+        let lemma_begin = self.lits.len();
+        self.lits.extend_from_slice(lemma);
+        let lemma_range = lemma_begin..self.lits.len();
+
+        let clauses_begin = self.clauses.len();
+
+        for &hash in propagation_hashes.iter() {
+            let candidates = match clauses.clauses.get(&hash) {
+                Some(candidates) if !candidates.is_empty() => candidates,
+                _ => {
+                    self.lits.truncate(lemma_begin);
+                    self.clauses.truncate(clauses_begin);
+                    return Err(CheckerError::check_failed(
+                        step,
+                        format!("no clause found for hash {:x}", hash),
+                    ));
+                }
+            };
+
+            'candidates: for clause in candidates.iter() {
+                let begin = self.lits.len();
+                for &lit in clause.lits.slice(&clauses.literal_buffer).iter() {
+                    match clauses.lit_value(lit) {
+                        Some((true, _)) => {
+                            self.lits.truncate(begin);
+                            continue 'candidates;
+                        }
+                        Some((false, _)) => (),
+                        None => self.lits.push(lit),
+                    }
+                }
+                self.clauses.push(begin..self.lits.len());
+            }
+        }
+
+        self.checks.push(DeferredCheck {
+            step,
+            lemma: lemma_range,
+            clauses: clauses_begin..self.clauses.len(),
+        });
+
+        Ok(self.lits.len() >= BUFFER_LIMIT)
+    }
+
+    /// Perform all buffered checks and clear the buffer.
+    ///
+    /// Returns the number of propagations or the error of the first failed check in step order.
+    pub fn run(&mut self) -> Result<u64, CheckerError> {
+        let chunk_size = self.checks.len().div_ceil(self.threads);
+
+        let results: Vec<(u64, Option<usize>)> = if chunk_size == 0 {
+            vec![]
+        } else {
+            let (checks, clauses, lits) = (&self.checks, &self.clauses, &self.lits);
+            thread::scope(|scope| {
+                let workers: Vec<_> = checks
+                    .chunks(chunk_size)
+                    .enumerate()
+                    .map(|(chunk_index, chunk)| {
+                        scope.spawn(move || {
+                            let mut worker = Worker::default();
+                            let failed = chunk.iter().position(|check| {
+                                !worker.check(
+                                    &lits[check.lemma.clone()],
+                                    &clauses[check.clauses.clone()],
+                                    lits,
+                                )
+                            });
+                            (
+                                worker.propagations,
+                                failed.map(|index| chunk_index * chunk_size + index),
+                            )
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .map(|worker| worker.join().unwrap())
+                    .collect()
+            })
+        };
+
+        let failed = results.iter().find_map(|&(_, failed)| failed);
+
+        let result = match failed {
+            Some(index) => {
+                let check = &self.checks[index];
+                Err(CheckerError::check_failed(
+                    check.step,
+                    format!("AT check failed for {:?}", &self.lits[check.lemma.clone()]),
+                ))
+            }
+            None => Ok(results.iter().map(|&(propagations, _)| propagations).sum()),
+        };
+
+        self.checks.clear();
+        self.clauses.clear();
+        self.lits.clear();
+
+        result
+    }
+}
+
+/// Assignment used by a single thread performing buffered checks.
+#[derive(Default)]
+struct Worker {
+    /// Value of each variable, indexed by variable.
+    values: Vec<Option<bool>>,
+    /// Assigned variables, to undo assignments after a check.
+    trail: Vec<usize>,
+    propagations: u64,
+}
+
+impl Worker {
+    /// Value of a literal.
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.values
+            .get(lit.index())
+            .cloned()
+            .flatten()
+            .map(|value| value ^ lit.is_negative())
+    }
+
+    /// Make a literal true.
+    fn assign(&mut self, lit: Lit) {
+        if self.values.len() <= lit.index() {
+            self.values.resize(lit.index() + 1, None);
+        }
+        self.values[lit.index()] = Some(lit.is_positive());
+        self.trail.push(lit.index());
+    }
+
+    /// Check whether unit propagation of the clauses in order refutes the negated lemma.
+    fn check(&mut self, lemma: &[Lit], clauses: &[Range<usize>], lits: &[Lit]) -> bool {
+        for &lit in lemma.iter() {
+            self.assign(!lit);
+        }
+
+        let mut conflict = false;
+
+        'clauses: for clause in clauses.iter() {
+            let mut unassigned_count = 0;
+            let mut unassigned_lit = None;
+
+            for &lit in lits[clause.clone()].iter() {
+                match self.lit_value(lit) {
+                    Some(true) => continue 'clauses,
+                    Some(false) => (),
+                    None => {
+                        unassigned_count += 1;
+                        unassigned_lit = Some(lit);
+                    }
+                }
+            }
+
+            match unassigned_lit {
+                None => {
+                    conflict = true;
+                    break;
+                }
+                Some(lit) if unassigned_count == 1 => {
+                    self.propagations += 1;
+                    self.assign(lit);
+                }
+                _ => (),
+            }
+        }
+
+        for index in self.trail.drain(..) {
+            self.values[index] = None;
+        }
+
+        conflict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use varisat_formula::{cnf_formula, lits, Var};
+    use varisat_internal_proof::{binary_format::write_step, clause_hash, ProofStep};
+
+    use crate::{Checker, CheckerError};
+
+    fn failed_step(result: Result<(), CheckerError>) -> Option<u64> {
+        match result {
+            Ok(()) => None,
+            Err(CheckerError::CheckFailed { step, .. }) => Some(step),
+            Err(err) => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn failed_check_step() {
+        let formula = cnf_formula![
+            1, 2;
+            -1, 2;
+            1, -2;
+            -1, -2;
+        ];
+
+        let mut proof = vec![];
+
+        for index in 0..2 {
+            let var = Var::from_index(index);
+            let step = ProofStep::SolverVarName {
+                global: var,
+                solver: Some(var),
+            };
+            write_step(&mut proof, &step).unwrap();
+        }
+
+        let steps = [
+            (
+                lits![2].to_vec(),
+                vec![lits![1, 2].to_vec(), lits![-1, 2].to_vec()],
+            ),
+            (lits![1].to_vec(), vec![lits![1, 2].to_vec()]),
+            (
+                lits![].to_vec(),
+                vec![
+                    lits![2].to_vec(),
+                    lits![1, -2].to_vec(),
+                    lits![-1, -2].to_vec(),
+                ],
+            ),
+        ];
+
+        for (clause, propagations) in steps.iter() {
+            let propagation_hashes: Vec<_> = propagations
+                .iter()
+                .map(|clause| clause_hash(clause))
+                .collect();
+            let step = ProofStep::AtClause {
+                redundant: true,
+                clause,
+                propagation_hashes: &propagation_hashes,
+            };
+            write_step(&mut proof, &step).unwrap();
+        }
+
+        write_step(&mut proof, &ProofStep::End).unwrap();
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+        assert_eq!(failed_step(checker.check_proof(&proof[..])), Some(4));
+
+        let mut checker = Checker::new();
+        checker.add_formula(&formula).unwrap();
+        assert_eq!(
+            failed_step(checker.check_proof_parallel(&proof[..], 2)),
+            Some(4)
+        );
+    }
+}
@@ -7,6 +7,7 @@ use varisat_formula::{Lit, Var};
 use crate::{
     context::{parts::*, Context},
     transcript::{self, ProofTranscriptProcessor},
+    unsat_core::UnsatCore,
     variables::SamplingMode,
     CheckerError,
 };
@@ -15,6 +16,16 @@ use crate::{
 ///
 /// Clauses are identified by a unique increasing id assigned by the checker. Whenever the literals
 /// of a clause are included in a step, they are sorted and free of duplicates.
+///
+/// Every input clause added before the checker derived unsatisfiability, including duplicated and
+/// tautological clauses, is reported by exactly one of the
+/// [`AddClause`](CheckedProofStep::AddClause),
+/// [`DuplicatedClause`](CheckedProofStep::DuplicatedClause) or
+/// [`TautologicalClause`](CheckedProofStep::TautologicalClause) steps, with a fresh id in the
+/// order the clauses were added. Input clauses added after that are ignored and not reported. Ids
+/// only depend on the sequence of input clauses and proof steps, not on the hashes used while
+/// checking, so they are the same across runs. [`CheckerData::input_clause_index`] maps these ids
+/// back to positions in the input.
 #[derive(Debug)]
 pub enum CheckedProofStep<'a> {
     /// Updates the corresponding user variable for a proof variable.
@@ -111,9 +122,24 @@ pub struct ResolutionPropagations {
 
 /// Checker data available to proof processors.
 #[derive(Copy, Clone)]
-pub struct CheckerData<'a, 'b>(pub partial!('a Context<'b>, VariablesP));
+pub struct CheckerData<'a, 'b>(pub partial!('a Context<'b>, ClausesP, VariablesP));
 
 impl<'a, 'b> CheckerData<'a, 'b> {
+    /// Position of an input clause in the order the input clauses were added, starting at zero.
+    ///
+    /// This takes the id of an [`AddClause`](CheckedProofStep::AddClause),
+    /// [`DuplicatedClause`](CheckedProofStep::DuplicatedClause) or
+    /// [`TautologicalClause`](CheckedProofStep::TautologicalClause) step and returns `None` for
+    /// all other ids. Positions count all reported input clauses, including duplicated and
+    /// tautological ones.
+    pub fn input_clause_index(self, id: u64) -> Option<usize> {
+        self.0
+            .part(ClausesP)
+            .input_clause_ids
+            .binary_search_by_key(&id, |ids| ids.step_id)
+            .ok()
+    }
+
     /// User variable corresponding to proof variable.
     ///
     /// Returns `None` if the proof variable is an internal or hidden variable.
@@ -149,17 +175,33 @@ pub struct Processing<'a> {
     pub processors: Vec<&'a mut dyn ProofProcessor>,
     /// Registered transcript processors.
     pub transcript_processors: Vec<&'a mut dyn ProofTranscriptProcessor>,
+    /// Unsat core tracking owned by the checker.
+    pub unsat_core: Option<UnsatCore>,
     /// Proof step to transcript step conversion.
     transcript: transcript::Transcript,
+    /// Callback invoked periodically with the current step while checking a proof.
+    pub progress_callback: Option<&'a mut dyn FnMut(u64)>,
 }
 
 impl<'a> Processing<'a> {
+    /// Whether any proof processor is present.
+    ///
+    /// This includes the checker owned unsat core tracking.
+    pub fn has_processors(&self) -> bool {
+        !self.processors.is_empty() || self.unsat_core.is_some()
+    }
+
     /// Process a single step
     pub fn step<'b>(
         &mut self,
         step: &CheckedProofStep<'b>,
         data: CheckerData,
     ) -> Result<(), CheckerError> {
+        if let Some(unsat_core) = &mut self.unsat_core {
+            if let Err(err) = unsat_core.process_step(step, data) {
+                return Err(CheckerError::ProofProcessorError { cause: err });
+            }
+        }
         for processor in self.processors.iter_mut() {
             if let Err(err) = processor.process_step(step, data) {
                 return Err(CheckerError::ProofProcessorError { cause: err });
@@ -181,7 +223,7 @@ impl<'a> Processing<'a> {
 
 /// Process a single step
 pub fn process_step<'a, 'b>(
-    mut ctx: partial!(Context<'a>, mut ProcessingP<'a>, VariablesP),
+    mut ctx: partial!(Context<'a>, mut ProcessingP<'a>, ClausesP, VariablesP),
     step: &CheckedProofStep<'b>,
 ) -> Result<(), CheckerError> {
     let (processing, mut ctx) = ctx.split_part_mut(ProcessingP);
@@ -10,6 +10,7 @@ use crate::{
     clauses::{UnitClause, UnitId},
     context::{parts::*, Context},
     hash::rehash,
+    parallel::DeferredChecks,
     variables::ensure_var,
     CheckerError,
 };
@@ -31,6 +32,12 @@ pub struct RupCheck {
     trace_edges: Vec<LitIdx>,
     /// Just the ids of `trace`.
     pub trace_ids: Vec<u64>,
+    /// Number of propagations performed by all checks.
+    pub propagations: u64,
+    /// Buffer for checks that are performed later in parallel.
+    ///
+    /// When this is set, no trace is recorded.
+    pub deferred: Option<DeferredChecks>,
 }
 
 /// Check whether a clause is implied by clauses of the given hashes.
@@ -81,6 +88,15 @@ pub fn check_clause_with_hashes<'a>(
         }
     }
 
+    if let Some(deferred) = &mut rup.deferred {
+        rup.trace_ids.clear();
+        let step = ctx.part(CheckerStateP).step;
+        if deferred.add_check(clauses, step, lits, propagation_hashes)? {
+            rup.propagations += deferred.run()?;
+        }
+        return Ok(());
+    }
+
     // Set all lits to false
     for &lit in lits.iter() {
         rup.trail.push((lit, clauses.unit_clauses[lit.index()]));
@@ -156,6 +172,8 @@ pub fn check_clause_with_hashes<'a>(
                     break 'hashes;
                 }
                 Some(lit) if unassigned_count == 1 => {
+                    rup.propagations += 1;
+
                     rup.trail.push((lit, clauses.unit_clauses[lit.index()]));
 
                     clauses.unit_clauses[lit.index()] = Some(UnitClause {
@@ -174,7 +192,7 @@ pub fn check_clause_with_hashes<'a>(
         }
     }
 
-    if rup_is_unsat && !ctx.part(ProcessingP).processors.is_empty() {
+    if rup_is_unsat && ctx.part(ProcessingP).has_processors() {
         for i in (0..rup.trace.len()).rev() {
             if !rup.trace[i].unused {
                 let edges = rup.trace[i].edges.clone();
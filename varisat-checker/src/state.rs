@@ -1,6 +1,6 @@
 //! Checker state and checking of proof steps.
 
-use std::{io, mem::replace};
+use std::io;
 
 use partial_ref::{partial, PartialRef};
 use rustc_hash::FxHashSet as HashSet;
@@ -15,6 +15,7 @@ use crate::{
     },
     context::{parts::*, Context},
     hash::rehash,
+    parallel::DeferredChecks,
     processing::{
         process_step, CheckedProofStep, CheckedSamplingMode, CheckedUserVar, ResolutionPropagations,
     },
@@ -138,10 +139,15 @@ pub fn check_step<'a>(
     };
 
     if let Err(CheckerError::CheckFailed {
-        ref mut debug_step, ..
+        step: failed_step,
+        ref mut debug_step,
+        ..
     }) = result
     {
-        *debug_step = format!("{:?}", step)
+        // Failed checks deferred by parallel checking are reported during a later step.
+        if failed_step == ctx.part(CheckerStateP).step {
+            *debug_step = format!("{:?}", step)
+        }
     }
     result
 }
@@ -272,7 +278,7 @@ fn check_at_clause_step<'a>(
     clause: &[Lit],
     propagation_hashes: &[ClauseHash],
 ) -> Result<(), CheckerError> {
-    let mut tmp = replace(&mut ctx.part_mut(TmpDataP).tmp, vec![]);
+    let mut tmp = std::mem::take(&mut ctx.part_mut(TmpDataP).tmp);
 
     if copy_canonical(&mut tmp, clause) {
         return Err(CheckerError::check_failed(
@@ -281,7 +287,7 @@ fn check_at_clause_step<'a>(
         ));
     }
 
-    check_clause_with_hashes(ctx.borrow(), &tmp, &*propagation_hashes)?;
+    check_clause_with_hashes(ctx.borrow(), &tmp, propagation_hashes)?;
 
     let (id, added) = store_clause(ctx.borrow(), &tmp, redundant);
 
@@ -333,7 +339,7 @@ fn check_delete_clause_step<'a>(
     clause: &[Lit],
     proof: DeleteClauseProof,
 ) -> Result<(), CheckerError> {
-    let mut tmp = replace(&mut ctx.part_mut(TmpDataP).tmp, vec![]);
+    let mut tmp = std::mem::take(&mut ctx.part_mut(TmpDataP).tmp);
 
     if copy_canonical(&mut tmp, clause) {
         return Err(CheckerError::check_failed(
@@ -505,7 +511,7 @@ fn check_model_step<'a>(
     for (_, candidates) in ctx.part(ClausesP).clauses.iter() {
         for clause in candidates.iter() {
             let lits = clause.lits.slice(&ctx.part(ClausesP).literal_buffer);
-            if !lits.iter().any(|lit| assignments.contains(&lit)) {
+            if !lits.iter().any(|lit| assignments.contains(lit)) {
                 return Err(CheckerError::check_failed(
                     ctx.part(CheckerStateP).step,
                     format!("model does not satisfy clause {:?}", lits),
@@ -534,7 +540,7 @@ fn check_failed_assumptions_step<'a>(
     failed_core: &[Lit],
     propagation_hashes: &[ClauseHash],
 ) -> Result<(), CheckerError> {
-    let mut tmp = replace(&mut ctx.part_mut(TmpDataP).tmp, vec![]);
+    let mut tmp = std::mem::take(&mut ctx.part_mut(TmpDataP).tmp);
 
     let direct_conflict = copy_canonical(&mut tmp, failed_core);
 
@@ -575,6 +581,35 @@ fn check_failed_assumptions_step<'a>(
     Ok(())
 }
 
+/// Statistics collected while checking proofs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheckerStats {
+    /// Number of proof steps checked.
+    pub steps: u64,
+    /// Number of unit propagations performed by RUP checks.
+    pub propagations: u64,
+    /// Number of garbage collections of the clause literal storage.
+    pub garbage_collections: u64,
+    /// Largest number of literals held by the clause literal storage.
+    pub peak_literal_buffer_len: usize,
+    /// Number of clauses currently stored, including unit clauses.
+    pub clauses: usize,
+}
+
+/// Current statistics of the checker.
+pub fn checker_stats(ctx: partial!(Context, CheckerStateP, ClausesP, RupCheckP)) -> CheckerStats {
+    let clauses = ctx.part(ClausesP);
+    CheckerStats {
+        steps: ctx.part(CheckerStateP).step,
+        propagations: ctx.part(RupCheckP).propagations,
+        garbage_collections: clauses.garbage_collections,
+        peak_literal_buffer_len: clauses
+            .peak_literal_buffer_len
+            .max(clauses.literal_buffer.len()),
+        clauses: clauses.clause_count(),
+    }
+}
+
 /// Checks a proof in the native Varisat format.
 pub fn check_proof<'a>(
     mut ctx: partial!(
@@ -597,9 +632,7 @@ pub fn check_proof<'a>(
 
         let step = ctx.part(CheckerStateP).step;
 
-        if step % 100000 == 0 {
-            log::info!("checking step {}k", step / 1000);
-        }
+        report_progress(ctx.borrow(), step);
 
         match parser.parse_step(&mut buffer) {
             Ok(step) => check_step(ctx.borrow(), step)?,
@@ -622,6 +655,50 @@ pub fn check_proof<'a>(
     process_unit_conflicts(ctx.borrow())
 }
 
+/// Checks a proof in the native Varisat format, performing RUP checks in parallel.
+///
+/// See [`parallel`](crate::parallel) for why this gives the same result as [`check_proof`].
+pub fn check_proof_parallel<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut CheckerStateP,
+        mut ClauseHasherP,
+        mut ClausesP,
+        mut ProcessingP<'a>,
+        mut RupCheckP,
+        mut TmpDataP,
+        mut VariablesP,
+    ),
+    input: impl io::Read,
+    threads: usize,
+) -> Result<(), CheckerError> {
+    if threads <= 1 || ctx.part(ProcessingP).has_processors() {
+        return check_proof(ctx.borrow(), input);
+    }
+
+    ctx.part_mut(RupCheckP).deferred = Some(DeferredChecks::new(threads));
+
+    let result = check_proof(ctx.borrow(), input);
+
+    let rup = ctx.part_mut(RupCheckP);
+    let mut deferred = rup.deferred.take().unwrap();
+
+    // Any failed deferred check precedes the step that produced `result`.
+    rup.propagations += deferred.run()?;
+
+    result
+}
+
+/// Log progress and invoke the progress callback every 100k steps.
+pub fn report_progress<'a>(mut ctx: partial!(Context<'a>, mut ProcessingP<'a>), step: u64) {
+    if step.is_multiple_of(100000) {
+        log::info!("checking step {}k", step / 1000);
+        if let Some(callback) = &mut ctx.part_mut(ProcessingP).progress_callback {
+            callback(step);
+        }
+    }
+}
+
 /// Process unit conflicts detected during clause loading.
 pub fn process_unit_conflicts<'a>(
     mut ctx: partial!(Context<'a>, mut ProcessingP<'a>, ClausesP, VariablesP),
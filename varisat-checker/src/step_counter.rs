@@ -0,0 +1,147 @@
+//! Counting of checked proof steps.
+use anyhow::Error;
+
+use crate::processing::{CheckedProofStep, CheckerData, ProofProcessor};
+
+/// Number of checked proof steps of each kind.
+///
+/// See [`CheckedProofStep`] for a description of each kind of step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepCounts {
+    pub user_var: u64,
+    pub add_clause: u64,
+    pub duplicated_clause: u64,
+    pub tautological_clause: u64,
+    pub at_clause: u64,
+    pub delete_clause: u64,
+    pub delete_at_clause: u64,
+    pub delete_rat_clause: u64,
+    pub make_irredundant: u64,
+    pub model: u64,
+    pub assumptions: u64,
+    pub failed_assumptions: u64,
+}
+
+/// Proof processor that counts the checked proof steps of each kind.
+#[derive(Default)]
+pub struct StepCounter {
+    counts: StepCounts,
+}
+
+impl StepCounter {
+    /// Create a new step counting processor.
+    pub fn new() -> StepCounter {
+        StepCounter::default()
+    }
+
+    /// Number of processed steps of each kind.
+    pub fn counts(&self) -> &StepCounts {
+        &self.counts
+    }
+}
+
+impl ProofProcessor for StepCounter {
+    fn process_step(&mut self, step: &CheckedProofStep, _data: CheckerData) -> Result<(), Error> {
+        let counts = &mut self.counts;
+        let count = match step {
+            CheckedProofStep::UserVar { .. } => &mut counts.user_var,
+            CheckedProofStep::AddClause { .. } => &mut counts.add_clause,
+            CheckedProofStep::DuplicatedClause { .. } => &mut counts.duplicated_clause,
+            CheckedProofStep::TautologicalClause { .. } => &mut counts.tautological_clause,
+            CheckedProofStep::AtClause { .. } => &mut counts.at_clause,
+            CheckedProofStep::DeleteClause { .. } => &mut counts.delete_clause,
+            CheckedProofStep::DeleteAtClause { .. } => &mut counts.delete_at_clause,
+            CheckedProofStep::DeleteRatClause { .. } => &mut counts.delete_rat_clause,
+            CheckedProofStep::MakeIrredundant { .. } => &mut counts.make_irredundant,
+            CheckedProofStep::Model { .. } => &mut counts.model,
+            CheckedProofStep::Assumptions { .. } => &mut counts.assumptions,
+            CheckedProofStep::FailedAssumptions { .. } => &mut counts.failed_assumptions,
+        };
+        *count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use varisat_formula::{cnf_formula, lits};
+    use varisat_internal_proof::{DeleteClauseProof, ProofStep};
+
+    use crate::{internal::SelfChecker, Checker};
+
+    #[test]
+    fn count_steps() {
+        let mut step_counter = StepCounter::new();
+
+        {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut step_counter);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    -1, 2;
+                    1, -2;
+                    -1, -2;
+                    2, -1;
+                    3, -3;
+                ])
+                .unwrap();
+
+            let hashes = [
+                checker.ctx.clause_hasher.clause_hash(&lits![1, 2]),
+                checker.ctx.clause_hasher.clause_hash(&lits![-1, 2]),
+            ];
+
+            checker
+                .self_check_step(ProofStep::AtClause {
+                    redundant: true,
+                    clause: &lits![2, 3],
+                    propagation_hashes: &hashes,
+                })
+                .unwrap();
+
+            checker
+                .self_check_step(ProofStep::DeleteClause {
+                    clause: &lits![2, 3],
+                    proof: DeleteClauseProof::Redundant,
+                })
+                .unwrap();
+
+            checker
+                .self_check_step(ProofStep::AtClause {
+                    redundant: false,
+                    clause: &lits![2],
+                    propagation_hashes: &hashes,
+                })
+                .unwrap();
+
+            let hashes = [
+                checker.ctx.clause_hasher.clause_hash(&lits![1, -2]),
+                checker.ctx.clause_hasher.clause_hash(&lits![-1, -2]),
+            ];
+
+            checker
+                .self_check_step(ProofStep::AtClause {
+                    redundant: false,
+                    clause: &[],
+                    propagation_hashes: &hashes,
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            step_counter.counts(),
+            &StepCounts {
+                add_clause: 4,
+                duplicated_clause: 1,
+                tautological_clause: 1,
+                at_clause: 3,
+                delete_clause: 1,
+                ..StepCounts::default()
+            }
+        );
+    }
+}
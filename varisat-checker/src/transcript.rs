@@ -41,7 +41,7 @@ impl Transcript {
         &mut self,
         step: &CheckedProofStep,
         data: CheckerData,
-    ) -> Option<ProofTranscriptStep> {
+    ) -> Option<ProofTranscriptStep<'_>> {
         match step {
             CheckedProofStep::UserVar { var, user_var } => match user_var {
                 None => Some(ProofTranscriptStep::HideVar {
@@ -0,0 +1,195 @@
+//! Extraction of the input clauses used by a proof.
+use anyhow::Error;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::processing::{CheckedProofStep, CheckerData, ProofProcessor};
+
+/// Proof processor that computes the input clauses used to show unsatisfiability.
+///
+/// This records the dependencies of all derived clauses. After checking a proof that derives the
+/// empty clause or a failed assumptions step, [`unsat_core`](UnsatCore::unsat_core) returns the
+/// ids of all input clauses the final conflict transitively depends on. Input clause ids are
+/// assigned consecutively starting at zero, so for a formula loaded before the proof they are the
+/// indices of the clauses in the formula. When clauses are added incrementally, derived clauses
+/// also use ids, so [`unsat_core_indices`](UnsatCore::unsat_core_indices) can be used to obtain
+/// the indices of the clauses in the order they were added.
+#[derive(Default)]
+pub struct UnsatCore {
+    /// Ids of the input clauses.
+    input_clauses: HashSet<u64>,
+    /// Ids of all input clauses in the order they were added.
+    ///
+    /// This includes duplicated and tautological clauses.
+    input_order: Vec<u64>,
+    /// Clauses used to derive each derived clause.
+    dependencies: HashMap<u64, Vec<u64>>,
+    /// Clauses used to derive the final conflict.
+    final_conflict: Option<Vec<u64>>,
+}
+
+impl UnsatCore {
+    /// Create a new unsat core processor.
+    pub fn new() -> UnsatCore {
+        UnsatCore::default()
+    }
+
+    /// Sorted ids of the input clauses used to derive the final conflict.
+    ///
+    /// Returns `None` if no conflict was derived.
+    pub fn unsat_core(&self) -> Option<Vec<u64>> {
+        let final_conflict = self.final_conflict.as_ref()?;
+
+        let mut visited: HashSet<u64> = HashSet::default();
+        let mut stack = final_conflict.clone();
+        let mut core = vec![];
+
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if self.input_clauses.contains(&id) {
+                core.push(id);
+            }
+            if let Some(dependencies) = self.dependencies.get(&id) {
+                stack.extend_from_slice(dependencies);
+            }
+        }
+
+        core.sort_unstable();
+
+        Some(core)
+    }
+
+    /// Sorted indices of the input clauses used to derive the final conflict.
+    ///
+    /// The indices count all added input clauses, including duplicated and tautological clauses, in
+    /// the order they were added. A duplicated clause is never part of the core, instead the first
+    /// copy is. Returns `None` if no conflict was derived.
+    pub fn unsat_core_indices(&self) -> Option<Vec<usize>> {
+        let core = self.unsat_core()?;
+        Some(
+            core.into_iter()
+                .map(|id| {
+                    self.input_order
+                        .binary_search(&id)
+                        .expect("unsat core contains unknown input clause")
+                })
+                .collect(),
+        )
+    }
+}
+
+impl ProofProcessor for UnsatCore {
+    fn process_step(&mut self, step: &CheckedProofStep, _data: CheckerData) -> Result<(), Error> {
+        match *step {
+            CheckedProofStep::AddClause { id, .. } => {
+                self.input_clauses.insert(id);
+                self.input_order.push(id);
+            }
+            CheckedProofStep::DuplicatedClause { id, .. }
+            | CheckedProofStep::TautologicalClause { id, .. } => {
+                self.input_order.push(id);
+            }
+            CheckedProofStep::AtClause {
+                id,
+                clause,
+                propagations,
+                ..
+            } => {
+                if clause.is_empty() {
+                    self.final_conflict = Some(propagations.to_vec());
+                } else {
+                    self.dependencies.insert(id, propagations.to_vec());
+                }
+            }
+            CheckedProofStep::FailedAssumptions { propagations, .. } => {
+                self.final_conflict = Some(propagations.to_vec());
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use varisat_formula::cnf_formula;
+    use varisat_internal_proof::ProofStep;
+
+    use crate::{internal::SelfChecker, Checker};
+
+    #[test]
+    fn drat_proof_core() {
+        let mut unsat_core = UnsatCore::new();
+
+        {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut unsat_core);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    3, 4;
+                    -1, 2;
+                    1, -2;
+                    -3, -4;
+                    -1, -2;
+                ])
+                .unwrap();
+
+            checker.check_drat_proof(&b"2 0\n0\n"[..]).unwrap();
+        }
+
+        assert_eq!(unsat_core.unsat_core(), Some(vec![0, 2, 3, 5]));
+    }
+
+    #[test]
+    fn unit_conflict_core() {
+        let mut unsat_core = UnsatCore::new();
+
+        {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut unsat_core);
+
+            checker
+                .add_formula(&cnf_formula![
+                    1, 2;
+                    3;
+                    -3;
+                ])
+                .unwrap();
+
+            checker.check_drat_proof(&b""[..]).unwrap();
+        }
+
+        assert_eq!(unsat_core.unsat_core(), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn incremental_core_indices() {
+        let mut checker = Checker::new();
+        checker.enable_unsat_core();
+
+        checker
+            .add_formula(&cnf_formula![
+                1, 2;
+                1, 2;
+                -1, 1;
+                3;
+            ])
+            .unwrap();
+
+        for clause in cnf_formula![-1; -3, 5; -3;].iter() {
+            checker
+                .self_check_step(ProofStep::AddClause { clause })
+                .unwrap();
+        }
+
+        checker.self_check_delayed_steps().unwrap();
+
+        let unsat_core = checker.unsat_core().unwrap();
+        assert_eq!(unsat_core.unsat_core_indices(), Some(vec![3, 6]));
+    }
+}
@@ -2,9 +2,10 @@ use std::{fs, io};
 
 use anyhow::Error;
 use clap::{App, ArgMatches, SubCommand};
+use env_logger::Target;
 
 use varisat::checker::{Checker, CheckerError};
-use varisat_lrat::WriteLrat;
+use varisat_lrat::{WriteDrat, WriteLrat};
 
 use super::{banner, init_logging};
 
@@ -18,10 +19,14 @@ pub fn check_args() -> App<'static, 'static> {
         .arg_from_usage(
             "[clrat-file] --write-clrat=[FILE] 'Convert the proof to compressed (binary) LRAT.'",
         )
+        .arg_from_usage("[drat-file] --write-drat=[FILE] 'Convert the proof to DRAT.'")
+        .arg_from_usage(
+            "[binary-drat-file] --write-binary-drat=[FILE] 'Convert the proof to binary DRAT.'",
+        )
 }
 
 pub fn check_main(matches: &ArgMatches) -> Result<i32, Error> {
-    init_logging();
+    init_logging(Target::Stdout);
     banner();
 
     let mut checker = Checker::default();
@@ -62,6 +67,20 @@ pub fn check_main(matches: &ArgMatches) -> Result<i32, Error> {
         checker.add_processor(&mut clrat_processor);
     }
 
+    let mut drat_processor;
+
+    if let Some(drat_path) = matches.value_of("drat-file") {
+        drat_processor = WriteDrat::new(fs::File::create(drat_path)?, false);
+        checker.add_processor(&mut drat_processor);
+    }
+
+    let mut binary_drat_processor;
+
+    if let Some(binary_drat_path) = matches.value_of("binary-drat-file") {
+        binary_drat_processor = WriteDrat::new(fs::File::create(binary_drat_path)?, true);
+        checker.add_processor(&mut binary_drat_processor);
+    }
+
     checker.add_dimacs_cnf(file)?;
 
     let path = matches.value_of("proof-file").unwrap();
@@ -0,0 +1,108 @@
+use std::fs;
+
+use anyhow::Error;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use env_logger::Target;
+
+use varisat::checker::{Checker, CheckerError};
+use varisat_lrat::{WriteDrat, WriteLrat};
+
+use super::{banner, init_logging};
+
+pub fn convert_args() -> App<'static, 'static> {
+    SubCommand::with_name("convert-proof")
+        .about("Convert a proof to a different format by checking it")
+        .arg_from_usage("<cnf-file> --cnf=<FILE> 'The formula the proof refers to.'")
+        .arg(
+            Arg::from_usage("[from] --from=[FORMAT] 'The format of the input proof.'")
+                .possible_values(&["varisat", "drat"])
+                .default_value("varisat")
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::from_usage("<to> --to=<FORMAT> 'The format of the output proof.'")
+                .possible_values(&["lrat", "clrat", "drat", "binary-drat"])
+                .case_insensitive(true),
+        )
+        .arg_from_usage("<INPUT> 'The proof file to convert.'")
+        .arg_from_usage("<OUTPUT> 'The file to write the converted proof to.'")
+        .after_help(
+            "DRAT input proofs can be in the text or binary DRAT format, which is detected \
+             automatically.",
+        )
+}
+
+pub fn convert_main(matches: &ArgMatches) -> Result<i32, Error> {
+    init_logging(Target::Stdout);
+    banner();
+
+    let from = matches.value_of("from").unwrap().to_ascii_lowercase();
+    let to = matches.value_of("to").unwrap().to_ascii_lowercase();
+
+    let output_path = matches.value_of("OUTPUT").unwrap();
+    let output = fs::File::create(output_path)?;
+
+    let mut lrat_processor = None;
+    let mut drat_processor = None;
+
+    match &to[..] {
+        "lrat" | "clrat" => lrat_processor = Some(WriteLrat::new(output, to == "clrat")),
+        "drat" | "binary-drat" => {
+            drat_processor = Some(WriteDrat::new(output, to == "binary-drat"))
+        }
+        _ => unreachable!(),
+    }
+
+    let mut checker = Checker::default();
+
+    if let Some(processor) = &mut lrat_processor {
+        checker.add_processor(processor);
+    }
+    if let Some(processor) = &mut drat_processor {
+        checker.add_processor(processor);
+    }
+
+    let cnf_path = matches.value_of("cnf-file").unwrap();
+    log::info!("Reading file '{}'", cnf_path);
+    checker.add_dimacs_cnf(fs::File::open(cnf_path)?)?;
+
+    let input_path = matches.value_of("INPUT").unwrap();
+    let input = fs::File::open(input_path)?;
+
+    log::info!(
+        "Converting {} proof file '{}' to {} proof file '{}'",
+        from,
+        input_path,
+        to,
+        output_path
+    );
+
+    let result = match &from[..] {
+        "varisat" => checker.check_proof(input),
+        "drat" => checker.check_drat_proof(input),
+        _ => unreachable!(),
+    };
+
+    if let Err(err) = result {
+        log::error!("{}", err);
+        if let CheckerError::CheckFailed { debug_step, .. } = err {
+            if !debug_step.is_empty() {
+                log::error!("failed step was {}", debug_step)
+            }
+        }
+        return Ok(1);
+    }
+
+    drop(checker);
+
+    if let Some(processor) = &mut lrat_processor {
+        processor.flush()?;
+    }
+    if let Some(processor) = &mut drat_processor {
+        processor.flush()?;
+    }
+
+    log::info!("Proof converted");
+
+    Ok(0)
+}
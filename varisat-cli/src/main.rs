@@ -1,10 +1,11 @@
 use std::{
     env, fs,
     io::{self, Read, Write},
+    time::{Duration, Instant},
 };
 
 use anyhow::Error;
-use clap::{values_t, App, AppSettings, Arg};
+use clap::{value_t, values_t, App, AppSettings, Arg};
 use env_logger::{fmt, Builder, Target};
 use log::{error, info, Level, LevelFilter, Record};
 
@@ -15,6 +16,7 @@ use varisat::{
 use varisat_lrat::WriteLrat;
 
 mod check;
+mod convert;
 
 fn main() {
     let exit_code = match main_with_err() {
@@ -27,7 +29,7 @@ fn main() {
     std::process::exit(exit_code);
 }
 
-fn init_logging() {
+fn init_logging(target: Target) {
     let format = |buf: &mut fmt::Formatter, record: &Record| {
         if record.level() == Level::Info {
             writeln!(buf, "c {}", record.args())
@@ -38,7 +40,7 @@ fn init_logging() {
 
     let mut builder = Builder::new();
     builder
-        .target(Target::Stdout)
+        .target(target)
         .format(format)
         .filter(None, LevelFilter::Info);
 
@@ -58,7 +60,32 @@ fn banner() {
     );
 }
 
+/// Print solver statistics as `c key: value` comment lines.
+fn print_stats(target: &mut dyn Write, solver: &Solver, wall_time: Duration) -> io::Result<()> {
+    let stats = solver.stats();
+    let learned_clauses = stats.core_clauses + stats.mid_clauses + stats.local_clauses;
+
+    writeln!(target, "c conflicts: {}", stats.conflicts)?;
+    writeln!(target, "c decisions: {}", stats.decisions)?;
+    writeln!(target, "c propagations: {}", stats.propagations)?;
+    writeln!(target, "c restarts: {}", stats.restarts)?;
+    writeln!(target, "c learned-clauses: {}", learned_clauses)?;
+    writeln!(
+        target,
+        "c peak-memory-bytes: {}",
+        solver.peak_memory_usage()
+    )?;
+    writeln!(
+        target,
+        "c wall-time-seconds: {:.3}",
+        wall_time.as_secs_f64()
+    )?;
+    Ok(())
+}
+
 fn main_with_err() -> Result<i32, Error> {
+    let start_time = Instant::now();
+
     let matches = App::new("varisat")
         .version(env!("VARISAT_VERSION"))
         .setting(AppSettings::DisableHelpSubcommand)
@@ -80,20 +107,60 @@ fn main_with_err() -> Result<i32, Error> {
             Arg::from_usage(
                 "[proof-format] --proof-format=[FORMAT] 'Specify the proof format to use.'",
             )
-            .possible_values(&["varisat", "drat", "binary-drat", "lrat", "clrat"])
+            .possible_values(&[
+                "varisat",
+                "drat",
+                "binary-drat",
+                "drat-no-deletions",
+                "lrat",
+                "clrat",
+            ])
             .default_value("varisat")
             .case_insensitive(true),
         )
         .arg_from_usage(
             "--self-check 'Enable self checking by generating and verifying a proof on the fly'",
         )
+        .arg(
+            Arg::from_usage(
+                "[output-format] --output-format=[FORMAT] 'Specify the format used to report the result.'",
+            )
+            .long_help(
+                "Specify the format used to report the result. The json format prints a single \
+                 JSON object containing the result and the model if one was found. In that case \
+                 log messages and statistics are written to stderr instead of stdout.",
+            )
+            .possible_values(&["dimacs", "json"])
+            .default_value("dimacs")
+            .case_insensitive(true),
+        )
+        .arg_from_usage("--stats 'Print solver statistics after solving'")
+        .arg(
+            Arg::from_usage("[timeout] --timeout=[SECONDS] 'Stop solving after the given time'")
+                .long_help(
+                    "Stop solving after the given wall time in seconds and report an unknown \
+                     result. The time is measured from the start of the program. The limit is \
+                     only checked during search, between conflicts, so parsing the input is not \
+                     interrupted and the limit can be exceeded by the time until the next \
+                     conflict is found. A time too large to be represented means no limit.",
+                )
+                .validator(|value| match value.parse::<f64>() {
+                    Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(()),
+                    _ => Err("expected a non-negative number of seconds".to_string()),
+                }),
+        )
         .subcommand(check::check_args())
+        .subcommand(convert::convert_args())
         .get_matches();
 
     if let Some(matches) = matches.subcommand_matches("--check") {
         return check::check_main(matches);
     }
 
+    if let Some(matches) = matches.subcommand_matches("convert-proof") {
+        return convert::convert_main(matches);
+    }
+
     if values_t!(matches, "config-option", String)
         .unwrap_or_default()
         .iter()
@@ -103,7 +170,18 @@ fn main_with_err() -> Result<i32, Error> {
         return Ok(0);
     }
 
-    init_logging();
+    let timeout = value_t!(matches, "timeout", f64).ok();
+
+    let json_output = matches
+        .value_of("output-format")
+        .unwrap()
+        .eq_ignore_ascii_case("json");
+
+    init_logging(if json_output {
+        Target::Stderr
+    } else {
+        Target::Stdout
+    });
     banner();
 
     let mut config_update = SolverConfigUpdate::new();
@@ -152,6 +230,7 @@ fn main_with_err() -> Result<i32, Error> {
         let proof_format = match &proof_format_str[..] {
             "drat" => Some(ProofFormat::Drat),
             "binary-drat" => Some(ProofFormat::BinaryDrat),
+            "drat-no-deletions" => Some(ProofFormat::DratNoDeletions),
             "varisat" => Some(ProofFormat::Varisat),
             "lrat" | "clrat" => {
                 lrat_processor =
@@ -173,26 +252,64 @@ fn main_with_err() -> Result<i32, Error> {
         solver.enable_self_checking();
     }
 
+    // A timeout too large to represent as a point in time doesn't limit solving.
+    let deadline = timeout
+        .and_then(|timeout| Duration::try_from_secs_f64(timeout).ok())
+        .and_then(|timeout| start_time.checked_add(timeout));
+
+    if let Some(deadline) = deadline {
+        solver.set_terminate_callback(move || Instant::now() >= deadline);
+    }
+
     solver.add_dimacs_cnf(file)?;
 
-    match solver.solve() {
-        Ok(true) => {
-            println!("s SATISFIABLE");
-            print!("v");
-            for l in solver.model().unwrap() {
-                print!(" {}", l);
-            }
-            println!(" 0");
-            Ok(10)
+    let result = solver.solve();
+
+    if matches.is_present("stats") {
+        if json_output {
+            print_stats(&mut io::stderr(), &solver, start_time.elapsed())?;
+        } else {
+            print_stats(&mut io::stdout(), &solver, start_time.elapsed())?;
         }
-        Ok(false) => {
-            println!("s UNSATISFIABLE");
-            Ok(20)
+    }
+
+    if let Err(err) = &result {
+        log::error!("{}", err);
+    }
+
+    if json_output {
+        match result {
+            Ok(true) => {
+                print!("{{\"result\":\"sat\",\"model\":[");
+                for (i, l) in solver.model().unwrap().into_iter().enumerate() {
+                    if i > 0 {
+                        print!(",");
+                    }
+                    print!("{}", l);
+                }
+                println!("]}}");
+            }
+            Ok(false) => println!("{{\"result\":\"unsat\"}}"),
+            Err(_) => println!("{{\"result\":\"unknown\"}}"),
         }
-        Err(err) => {
-            log::error!("{}", err);
-            println!("s UNKNOWN");
-            Ok(0)
+    } else {
+        match result {
+            Ok(true) => {
+                println!("s SATISFIABLE");
+                print!("v");
+                for l in solver.model().unwrap() {
+                    print!(" {}", l);
+                }
+                println!(" 0");
+            }
+            Ok(false) => println!("s UNSATISFIABLE"),
+            Err(_) => println!("s UNKNOWN"),
         }
     }
+
+    match result {
+        Ok(true) => Ok(10),
+        Ok(false) => Ok(20),
+        Err(_) => Ok(0),
+    }
 }
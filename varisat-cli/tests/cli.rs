@@ -0,0 +1,32 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Run the solver binary on the given DIMACS CNF input and return its exit code.
+fn solve_stdin(args: &[&str], input: &str) -> Option<i32> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_varisat"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait().unwrap().code()
+}
+
+#[test]
+fn huge_timeout() {
+    let input = "p cnf 2 2\n1 2 0\n-1 0\n";
+
+    assert_eq!(solve_stdin(&["--timeout", "1e20"], input), Some(10));
+    assert_eq!(solve_stdin(&["--timeout", "1e300"], input), Some(10));
+}
@@ -0,0 +1,365 @@
+//! Tokenizer shared by the DIMACS CNF and WCNF parsers.
+use std::str::SplitWhitespace;
+
+use varisat_formula::Var;
+
+use crate::{LineContext, ParserError};
+
+/// Receives the tokens found by a [`Lexer`].
+pub trait Handler {
+    /// The largest number that is valid at the current position.
+    fn number_limit(&self) -> u64 {
+        Var::max_count() as u64
+    }
+
+    /// Error for a number exceeding [`number_limit`](Handler::number_limit).
+    ///
+    /// The number consists of the digits of `partial` followed by `digit`.
+    fn number_too_large(&self, location: &Location, partial: u64, digit: u64) -> ParserError {
+        ParserError::LiteralTooLarge {
+            line: location.line(),
+            index: partial as usize,
+            final_digit: digit as usize,
+            context: location.context(),
+        }
+    }
+
+    /// Called for a `-` sign preceding a number.
+    fn sign(&mut self, _location: &Location) -> Result<(), ParserError> {
+        Ok(())
+    }
+
+    /// Called for each complete, possibly negated number.
+    fn number(
+        &mut self,
+        location: &Location,
+        negative: bool,
+        value: u64,
+    ) -> Result<(), ParserError>;
+
+    /// Called for a `p` at the start of a line, returns whether a header is expected.
+    fn begin_header(&mut self, location: &Location) -> Result<bool, ParserError>;
+
+    /// Called with the complete header line, including the leading `p`.
+    fn header(&mut self, location: &Location, header_line: &str) -> Result<(), ParserError>;
+
+    /// Called with the text of each comment line when comments are collected.
+    fn comment(&mut self, _text: &[u8]) {}
+
+    /// Called for any other byte outside of a number, returns whether the byte is valid.
+    fn other(&mut self, _location: &Location, _byte: u8) -> Result<bool, ParserError> {
+        Ok(false)
+    }
+}
+
+/// Position of the current byte, used to report errors.
+pub struct Location<'a> {
+    line: usize,
+    line_start: Option<&'a [u8]>,
+    rest: &'a [u8],
+}
+
+impl<'a> Location<'a> {
+    /// Current line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Contents of the current line, if captured.
+    pub fn context(&self) -> LineContext {
+        LineContext(self.line_start.map(|line_start| {
+            let mut line = line_start.to_vec();
+            line.extend(
+                self.rest
+                    .iter()
+                    .take_while(|&&byte| byte != b'\n' && byte != b'\r'),
+            );
+            String::from_utf8_lossy(&line).into_owned()
+        }))
+    }
+
+    /// Error for a header line with invalid syntax.
+    pub fn invalid_header(&self, header_line: &str) -> ParserError {
+        ParserError::InvalidHeader {
+            line: self.line,
+            header: header_line.to_owned(),
+            context: self.context(),
+        }
+    }
+
+    /// Parse a `p <format> <var count> <clause count>` header line.
+    ///
+    /// Returns the variable and clause counts and any further fields.
+    pub fn parse_header<'h>(
+        &self,
+        header_line: &'h str,
+        format: &str,
+    ) -> Result<(usize, usize, SplitWhitespace<'h>), ParserError> {
+        if !header_line.starts_with("p ") {
+            return Err(self.invalid_header(header_line));
+        }
+
+        let mut header_values = header_line[2..].split_whitespace();
+
+        if header_values.next() != Some(format) {
+            return Err(self.invalid_header(header_line));
+        }
+
+        let var_count: usize = match header_values
+            .next()
+            .and_then(|value| str::parse(value).ok())
+        {
+            None => return Err(self.invalid_header(header_line)),
+            Some(value) => value,
+        };
+
+        if var_count > Var::max_count() {
+            return Err(ParserError::LiteralTooLarge {
+                line: self.line,
+                index: var_count / 10,
+                final_digit: var_count % 10,
+                context: self.context(),
+            });
+        }
+
+        let clause_count: usize = match header_values
+            .next()
+            .and_then(|value| str::parse(value).ok())
+        {
+            None => return Err(self.invalid_header(header_line)),
+            Some(value) => value,
+        };
+
+        Ok((var_count, clause_count, header_values))
+    }
+}
+
+/// Splits DIMACS style input into comments, header lines and numbers.
+pub struct Lexer {
+    line_number: usize,
+    partial_number: u64,
+    negate_next_number: bool,
+
+    in_number: bool,
+    in_comment_or_header: bool,
+    in_header: bool,
+    start_of_line: bool,
+    error: bool,
+
+    header_line: Vec<u8>,
+
+    collect_comments: bool,
+    in_comment: bool,
+    comment_line: Vec<u8>,
+
+    line_context: bool,
+    line_buffer: Vec<u8>,
+}
+
+impl Default for Lexer {
+    fn default() -> Lexer {
+        Lexer {
+            line_number: 1,
+            partial_number: 0,
+            negate_next_number: false,
+
+            in_number: false,
+            in_comment_or_header: false,
+            in_header: false,
+            start_of_line: true,
+            error: false,
+
+            header_line: vec![],
+
+            collect_comments: false,
+            in_comment: false,
+            comment_line: vec![],
+
+            line_context: false,
+            line_buffer: vec![],
+        }
+    }
+}
+
+impl Lexer {
+    /// Enable or disable capturing the current line for errors.
+    pub fn set_line_context(&mut self, line_context: bool) {
+        self.line_context = line_context;
+    }
+
+    /// Enable or disable passing comment lines to the handler.
+    pub fn set_collect_comments(&mut self, collect_comments: bool) {
+        self.collect_comments = collect_comments;
+    }
+
+    /// Current line number.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    fn location<'a>(&'a self, rest: &'a [u8]) -> Location<'a> {
+        Location {
+            line: self.line_number,
+            line_start: if self.line_context {
+                Some(&self.line_buffer)
+            } else {
+                None
+            },
+            rest,
+        }
+    }
+
+    /// Tokenize a chunk of input.
+    ///
+    /// If this method returns an error, the lexer is in an invalid state and cannot process
+    /// further chunks.
+    pub fn parse_chunk(
+        &mut self,
+        chunk: &[u8],
+        handler: &mut impl Handler,
+    ) -> Result<(), ParserError> {
+        if self.error {
+            return Err(ParserError::PreviousError);
+        }
+        let result = self.parse_chunk_inner(chunk, handler);
+        self.error = result.is_err();
+        result
+    }
+
+    fn parse_chunk_inner(
+        &mut self,
+        chunk: &[u8],
+        handler: &mut impl Handler,
+    ) -> Result<(), ParserError> {
+        for (position, &byte) in chunk.iter().enumerate() {
+            let rest = &chunk[position..];
+            if byte == b'\n' {
+                self.line_number += 1;
+            }
+            match byte {
+                b'\n' | b'\r' if self.in_comment_or_header => {
+                    self.finish_comment_or_header(rest, handler)?;
+                    self.start_of_line = true
+                }
+                _ if self.in_comment_or_header => {
+                    if self.in_header {
+                        self.header_line.push(byte);
+                    } else if self.in_comment {
+                        self.comment_line.push(byte);
+                    }
+                }
+                b'0'..=b'9' => {
+                    self.in_number = true;
+                    let digit = (byte - b'0') as u64;
+
+                    self.partial_number = match self
+                        .partial_number
+                        .checked_mul(10)
+                        .and_then(|number| number.checked_add(digit))
+                        .filter(|&number| number <= handler.number_limit())
+                    {
+                        Some(number) => number,
+                        None => {
+                            let location = self.location(rest);
+                            return Err(handler.number_too_large(
+                                &location,
+                                self.partial_number,
+                                digit,
+                            ));
+                        }
+                    };
+
+                    self.start_of_line = false
+                }
+                b'-' if !self.negate_next_number && !self.in_number => {
+                    handler.sign(&self.location(rest))?;
+                    self.negate_next_number = true;
+                    self.start_of_line = false
+                }
+                b' ' | b'\n' | b'\r' if !self.negate_next_number || self.in_number => {
+                    self.finish_number(rest, handler)?;
+                    self.start_of_line = byte != b' ';
+                }
+                b'c' if self.start_of_line => {
+                    self.in_comment_or_header = true;
+                    self.in_comment = self.collect_comments;
+                }
+                b'p' if self.start_of_line && handler.begin_header(&self.location(rest))? => {
+                    self.in_comment_or_header = true;
+                    self.in_header = true;
+                    self.header_line.push(b'p');
+                }
+                _ if !self.negate_next_number
+                    && !self.in_number
+                    && handler.other(&self.location(rest), byte)? =>
+                {
+                    self.start_of_line = false
+                }
+                _ => {
+                    return Err(ParserError::UnexpectedInput {
+                        line: self.line_number,
+                        unexpected: byte as char,
+                        context: self.location(rest).context(),
+                    });
+                }
+            }
+
+            if self.line_context {
+                if byte == b'\n' {
+                    self.line_buffer.clear();
+                } else {
+                    self.line_buffer.push(byte);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish tokenizing the input.
+    pub fn eof(&mut self, handler: &mut impl Handler) -> Result<(), ParserError> {
+        if self.in_comment_or_header {
+            self.finish_comment_or_header(&[], handler)?;
+        }
+        self.finish_number(&[], handler)
+    }
+
+    fn finish_comment_or_header(
+        &mut self,
+        rest: &[u8],
+        handler: &mut impl Handler,
+    ) -> Result<(), ParserError> {
+        if self.in_header {
+            self.in_header = false;
+            let header_line = String::from_utf8_lossy(&self.header_line).into_owned();
+            self.header_line.clear();
+            handler.header(&self.location(rest), &header_line)?;
+        }
+        if self.in_comment {
+            self.in_comment = false;
+            let line = self
+                .comment_line
+                .strip_prefix(b" ")
+                .unwrap_or(&self.comment_line);
+            handler.comment(line);
+            self.comment_line.clear();
+        }
+        self.in_comment_or_header = false;
+        Ok(())
+    }
+
+    fn finish_number(
+        &mut self,
+        rest: &[u8],
+        handler: &mut impl Handler,
+    ) -> Result<(), ParserError> {
+        if self.in_number {
+            let location = self.location(rest);
+            handler.number(&location, self.negate_next_number, self.partial_number)?;
+        }
+        self.negate_next_number = false;
+        self.in_number = false;
+        self.partial_number = 0;
+        Ok(())
+    }
+}
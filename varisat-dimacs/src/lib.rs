@@ -1,34 +1,56 @@
 //! DIMCAS CNF parser and writer for the Varisat SAT solver.
 
-use std::{borrow::Borrow, io, mem::replace};
+use std::{borrow::Borrow, fmt, io, mem::replace};
 
 use varisat_formula::{CnfFormula, ExtendFormula, Lit, Var};
 
 use anyhow::Error;
 use thiserror::Error;
 
+use lexer::{Lexer, Location};
+
+mod lexer;
+mod wcnf;
+
+pub use wcnf::{WcnfHeader, WcnfParser, WeightedFormula};
+
 /// Possible errors while parsing a DIMACS CNF formula.
+///
+/// Variants capturing the offending line store it in their `context` field, see
+/// [`with_line_context`](DimacsParser::with_line_context).
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ParserError {
     #[error(
-        "line {}: Unexpected character in DIMACS CNF input: '{}'",
+        "line {}: Unexpected character in DIMACS CNF input: '{}'{}",
         line,
-        unexpected
+        unexpected,
+        context
     )]
-    UnexpectedInput { line: usize, unexpected: char },
+    UnexpectedInput {
+        line: usize,
+        unexpected: char,
+        context: LineContext,
+    },
     #[error(
-        "line {}: Literal index is too large: {}{}...",
+        "line {}: Literal index is too large: {}{}...{}",
         line,
         index,
-        final_digit
+        final_digit,
+        context
     )]
     LiteralTooLarge {
         line: usize,
         index: usize,
         final_digit: usize,
+        context: LineContext,
+    },
+    #[error("line {}: Invalid header syntax: {}{}", line, header, context)]
+    InvalidHeader {
+        line: usize,
+        header: String,
+        context: LineContext,
     },
-    #[error("line {}: Invalid header syntax: {}", line, header)]
-    InvalidHeader { line: usize, header: String },
     #[error("line {}: Unterminated clause", line)]
     UnterminatedClause { line: usize },
     #[error(
@@ -49,10 +71,34 @@ pub enum ParserError {
         clause_count: usize,
         header_clause_count: usize,
     },
+    #[error("line {}: Clause without a weight", line)]
+    MissingWeight { line: usize },
+    #[error("line {}: Clause weight is too large", line)]
+    WeightTooLarge { line: usize },
     #[error("Parser invoked after a previous error")]
     PreviousError,
 }
 
+/// Contents of the line containing a parse error.
+///
+/// This is only captured when enabled using
+/// [`with_line_context`](DimacsParser::with_line_context).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineContext(pub Option<String>);
+
+impl fmt::Display for LineContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(line) => write!(f, " in {:?}", line),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The first two bytes of gzip compressed data.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 /// Variable and clause count present in a DIMACS CNF header.
 #[derive(Copy, Clone, Debug)]
 pub struct DimacsHeader {
@@ -60,50 +106,81 @@ pub struct DimacsHeader {
     pub clause_count: usize,
 }
 
+/// A comment line of a DIMACS CNF file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DimacsComment {
+    /// Text of the comment without the leading `c` and the following space.
+    pub text: String,
+    /// Whether the comment follows the header line.
+    pub after_header: bool,
+}
+
 /// Parser for DIMACS CNF files.
 ///
 /// This parser can consume the input in chunks while also producing the parsed result in chunks.
 #[derive(Default)]
 pub struct DimacsParser {
+    lexer: Lexer,
+    cnf: CnfHandler,
+}
+
+/// Builds the formula from the tokens of a DIMACS CNF file.
+#[derive(Default)]
+struct CnfHandler {
     formula: CnfFormula,
     partial_clause: Vec<Lit>,
     header: Option<DimacsHeader>,
 
-    line_number: usize,
     clause_count: usize,
-    partial_lit: usize,
-    negate_next_lit: bool,
+    seen_var_count: usize,
+
+    ignore_header_mismatch: bool,
 
-    in_lit: bool,
-    in_comment_or_header: bool,
-    in_header: bool,
-    start_of_line: bool,
-    error: bool,
+    comments: Vec<DimacsComment>,
 
-    header_line: Vec<u8>,
+    multi_formula: bool,
+    finished_formulas: Vec<CnfFormula>,
 }
 
 impl DimacsParser {
     /// Create a new DIMACS CNF parser.
     pub fn new() -> DimacsParser {
-        DimacsParser {
-            formula: CnfFormula::new(),
-            partial_clause: vec![],
-            header: None,
+        DimacsParser::default()
+    }
 
-            line_number: 1,
-            clause_count: 0,
-            partial_lit: 0,
-            negate_next_lit: false,
+    /// Capture the contents of the offending line in parse errors.
+    ///
+    /// This buffers the current line while parsing, so it is disabled by default.
+    pub fn with_line_context(mut self) -> Self {
+        self.lexer.set_line_context(true);
+        self
+    }
 
-            in_lit: false,
-            in_comment_or_header: false,
-            in_header: false,
-            start_of_line: true,
-            error: false,
+    /// Enable or disable collecting comment lines.
+    ///
+    /// This is disabled by default. Collected comments are available via
+    /// [`comments`](DimacsParser::comments).
+    pub fn set_collect_comments(&mut self, collect_comments: bool) {
+        self.lexer.set_collect_comments(collect_comments);
+    }
 
-            header_line: vec![],
-        }
+    /// Enable or disable ignoring a header that doesn't match the formula.
+    ///
+    /// This is disabled by default. When enabled, a variable or clause count that differs from the
+    /// header is logged as a warning instead of being reported as an error.
+    pub fn set_ignore_header_mismatch(&mut self, ignore_header_mismatch: bool) {
+        self.cnf.ignore_header_mismatch = ignore_header_mismatch;
+    }
+
+    /// Parse the given input, ignoring a header that doesn't match the formula.
+    ///
+    /// See [`set_ignore_header_mismatch`](DimacsParser::set_ignore_header_mismatch).
+    pub fn parse_lenient(input: impl io::Read) -> Result<CnfFormula, Error> {
+        let mut parser = Self::new();
+        parser.set_ignore_header_mismatch(true);
+        Ok(parser
+            .parse_incremental_with(input, |_| Ok(()))?
+            .take_formula())
     }
 
     /// Parse the given input and check the header if present.
@@ -115,19 +192,120 @@ impl DimacsParser {
         Ok(Self::parse_incremental(input, |_| Ok(()))?.take_formula())
     }
 
+    /// Parse the given input, collecting all comment lines, and check the header if present.
+    pub fn parse_with_comments(
+        input: impl io::Read,
+    ) -> Result<(CnfFormula, Vec<DimacsComment>), Error> {
+        let mut parser = Self::new();
+        parser.set_collect_comments(true);
+        let mut parser = parser.parse_incremental_with(input, |_| Ok(()))?;
+        let formula = parser.take_formula();
+        Ok((formula, parser.cnf.comments))
+    }
+
     /// Parse the given input incrementally and check the header if present.
     ///
     /// The callback is invoked repeatedly with a reference to the parser. The callback can process
     /// the formula incrementally by calling [`take_formula`](DimacsParser::take_formula) on the
     /// passed argument.
+    ///
+    /// When the `gzip` feature is enabled, gzip compressed input is detected by its magic bytes
+    /// and decompressed while parsing.
     pub fn parse_incremental(
+        input: impl io::Read,
+        callback: impl FnMut(&mut DimacsParser) -> Result<(), Error>,
+    ) -> Result<DimacsParser, Error> {
+        Self::new().parse_incremental_with(input, callback)
+    }
+
+    /// Parse the given input incrementally using this parser and check the header if present.
+    ///
+    /// This works like [`parse_incremental`](DimacsParser::parse_incremental) but allows
+    /// configuring the parser beforehand.
+    pub fn parse_incremental_with(
+        self,
+        input: impl io::Read,
+        callback: impl FnMut(&mut DimacsParser) -> Result<(), Error>,
+    ) -> Result<DimacsParser, Error> {
+        #[cfg(feature = "gzip")]
+        {
+            use io::Read;
+
+            let mut input = input;
+            let mut magic = [0u8; 2];
+            let mut magic_len = 0;
+
+            while magic_len < magic.len() {
+                match input.read(&mut magic[magic_len..]) {
+                    Ok(0) => break,
+                    Ok(len) => magic_len += len,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+
+            let input = (&magic[..magic_len]).chain(input);
+
+            if magic[..magic_len] == GZIP_MAGIC {
+                self.parse_buffered(flate2::read::GzDecoder::new(input), callback)
+            } else {
+                self.parse_buffered(input, callback)
+            }
+        }
+
+        #[cfg(not(feature = "gzip"))]
+        self.parse_buffered(input, callback)
+    }
+
+    /// Parse concatenated formulas, checking the header of each if present.
+    ///
+    /// Every header after the first starts a new formula. The callback is invoked with each
+    /// formula once it is complete, i.e. when the next header or the end of the input is reached.
+    /// The callback is always invoked for the last formula, even when the input is empty.
+    pub fn parse_multi(
+        input: impl io::Read,
+        mut callback: impl FnMut(CnfFormula),
+    ) -> Result<(), Error> {
+        let mut parser = Self::new();
+        parser.cnf.multi_formula = true;
+        let mut parser = parser.parse_incremental_with(input, |parser| {
+            for formula in parser.cnf.finished_formulas.drain(..) {
+                callback(formula);
+            }
+            Ok(())
+        })?;
+        callback(parser.take_formula());
+        Ok(())
+    }
+
+    /// Parse the given input clause by clause and check the header if present.
+    ///
+    /// The callback is invoked once for each parsed clause. Clauses are handed to the callback
+    /// after each chunk of input, so the memory used is bounded by the chunk size and not by the
+    /// size of the formula. The returned parser can be used to query the header and the variable
+    /// and clause counts.
+    pub fn parse_clauses(
+        input: impl io::Read,
+        mut callback: impl FnMut(&[Lit]),
+    ) -> Result<DimacsParser, Error> {
+        Self::parse_incremental(input, |parser| {
+            for clause in parser.take_formula().iter() {
+                callback(clause);
+            }
+            Ok(())
+        })
+    }
+
+    /// Parse the given uncompressed input incrementally and check the header if present.
+    fn parse_buffered(
+        mut self,
         input: impl io::Read,
         mut callback: impl FnMut(&mut DimacsParser) -> Result<(), Error>,
     ) -> Result<DimacsParser, Error> {
         use io::BufRead;
 
         let mut buffer = io::BufReader::new(input);
-        let mut parser = Self::new();
+        let parser = &mut self;
 
         loop {
             let data = buffer.fill_buf()?;
@@ -138,13 +316,13 @@ impl DimacsParser {
             let len = data.len();
             buffer.consume(len);
 
-            callback(&mut parser)?;
+            callback(parser)?;
         }
         parser.eof()?;
-        callback(&mut parser)?;
+        callback(parser)?;
         parser.check_header()?;
 
-        Ok(parser)
+        Ok(self)
     }
 
     /// Parse a chunk of input.
@@ -154,83 +332,7 @@ impl DimacsParser {
     /// If this method returns an error, the parser is in an invalid state and cannot parse further
     /// chunks.
     pub fn parse_chunk(&mut self, chunk: &[u8]) -> Result<(), ParserError> {
-        if self.error {
-            return Err(ParserError::PreviousError);
-        }
-        for &byte in chunk.iter() {
-            if byte == b'\n' {
-                self.line_number += 1;
-            }
-            match byte {
-                b'\n' | b'\r' if self.in_comment_or_header => {
-                    if self.in_header {
-                        self.in_header = false;
-                        self.parse_header_line()?;
-                    }
-                    self.in_comment_or_header = false;
-                    self.start_of_line = true
-                }
-                _ if self.in_comment_or_header => {
-                    if self.in_header {
-                        self.header_line.push(byte);
-                    }
-                }
-                b'0'..=b'9' => {
-                    self.in_lit = true;
-                    let digit = (byte - b'0') as usize;
-
-                    const CAN_OVERFLOW: usize = Var::max_count() / 10;
-                    const OVERFLOW_DIGIT: usize = Var::max_count() % 10;
-
-                    // Overflow check that is fast but still works if LitIdx has the same size as
-                    // usize
-                    if CAN_OVERFLOW <= self.partial_lit {
-                        let carry = (digit <= OVERFLOW_DIGIT) as usize;
-
-                        if CAN_OVERFLOW + carry <= self.partial_lit {
-                            self.error = true;
-                            return Err(ParserError::LiteralTooLarge {
-                                line: self.line_number,
-                                index: self.partial_lit,
-                                final_digit: digit,
-                            });
-                        }
-                    }
-
-                    self.partial_lit = self.partial_lit * 10 + digit;
-
-                    self.start_of_line = false
-                }
-                b'-' if !self.negate_next_lit && !self.in_lit => {
-                    self.negate_next_lit = true;
-                    self.start_of_line = false
-                }
-                b' ' | b'\n' | b'\r' if !self.negate_next_lit || self.in_lit => {
-                    self.finish_literal();
-                    self.negate_next_lit = false;
-                    self.in_lit = false;
-                    self.partial_lit = 0;
-                    self.start_of_line = byte != b' ';
-                }
-                b'c' if self.start_of_line => {
-                    self.in_comment_or_header = true;
-                }
-                b'p' if self.start_of_line && self.header.is_none() => {
-                    self.in_comment_or_header = true;
-                    self.in_header = true;
-                    self.header_line.push(b'p');
-                }
-                _ => {
-                    self.error = true;
-                    return Err(ParserError::UnexpectedInput {
-                        line: self.line_number,
-                        unexpected: byte as char,
-                    });
-                }
-            }
-        }
-
-        Ok(())
+        self.lexer.parse_chunk(chunk, &mut self.cnf)
     }
 
     /// Finish parsing the input.
@@ -238,15 +340,11 @@ impl DimacsParser {
     /// This does not check whether the header information was correct, call
     /// [`check_header`](DimacsParser::check_header) for this.
     pub fn eof(&mut self) -> Result<(), ParserError> {
-        if self.in_header {
-            self.parse_header_line()?;
-        }
-
-        self.finish_literal();
+        self.lexer.eof(&mut self.cnf)?;
 
-        if !self.partial_clause.is_empty() {
+        if !self.cnf.partial_clause.is_empty() {
             return Err(ParserError::UnterminatedClause {
-                line: self.line_number,
+                line: self.lexer.line_number(),
             });
         }
 
@@ -255,26 +353,10 @@ impl DimacsParser {
 
     /// Verifies the header information when present.
     ///
-    /// Does nothing when the input doesn't contain a header.
+    /// Does nothing when the input doesn't contain a header. Mismatches are only logged when
+    /// enabled using [`set_ignore_header_mismatch`](DimacsParser::set_ignore_header_mismatch).
     pub fn check_header(&self) -> Result<(), ParserError> {
-        if let Some(header) = self.header {
-            let var_count = self.formula.var_count();
-            if var_count != header.var_count {
-                return Err(ParserError::VarCount {
-                    var_count,
-                    header_var_count: header.var_count,
-                });
-            }
-
-            if self.clause_count != header.clause_count {
-                return Err(ParserError::ClauseCount {
-                    clause_count: self.clause_count,
-                    header_clause_count: header.clause_count,
-                });
-            }
-        }
-
-        Ok(())
+        self.cnf.check_header()
     }
 
     /// Returns the subformula of everything parsed since the last call to this method.
@@ -287,78 +369,131 @@ impl DimacsParser {
     /// and the variable count of the header if present.
     pub fn take_formula(&mut self) -> CnfFormula {
         let mut new_formula = CnfFormula::new();
-        new_formula.set_var_count(self.formula.var_count());
-        replace(&mut self.formula, new_formula)
+        new_formula.set_var_count(self.cnf.formula.var_count());
+        replace(&mut self.cnf.formula, new_formula)
     }
 
     /// Return the DIMACS CNF header data if present.
     pub fn header(&self) -> Option<DimacsHeader> {
-        self.header
+        self.cnf.header
+    }
+
+    /// Variable and clause count of the input parsed so far.
+    ///
+    /// This ignores the header if present and reports the highest variable used and the number of
+    /// clauses.
+    pub fn inferred_header(&self) -> DimacsHeader {
+        DimacsHeader {
+            var_count: self.cnf.seen_var_count,
+            clause_count: self.cnf.clause_count,
+        }
+    }
+
+    /// Comment lines collected so far.
+    ///
+    /// This is empty unless enabled using
+    /// [`set_collect_comments`](DimacsParser::set_collect_comments).
+    pub fn comments(&self) -> &[DimacsComment] {
+        &self.cnf.comments
     }
 
     /// Number of clauses parsed.
     pub fn clause_count(&self) -> usize {
-        self.clause_count
+        self.cnf.clause_count
     }
 
     /// Number of variables in the parsed formula.
     pub fn var_count(&self) -> usize {
-        self.formula.var_count()
+        self.cnf.formula.var_count()
     }
+}
 
-    fn finish_literal(&mut self) {
-        if self.in_lit {
-            if self.partial_lit == 0 {
-                self.formula.add_clause(&self.partial_clause);
-                self.partial_clause.clear();
-                self.clause_count += 1;
-            } else {
-                self.partial_clause
-                    .push(Var::from_dimacs(self.partial_lit as isize).lit(!self.negate_next_lit));
+impl CnfHandler {
+    fn check_header(&self) -> Result<(), ParserError> {
+        if let Some(header) = self.header {
+            let var_count = self.formula.var_count();
+            if var_count != header.var_count {
+                self.header_mismatch(ParserError::VarCount {
+                    var_count,
+                    header_var_count: header.var_count,
+                })?;
+            }
+
+            if self.clause_count != header.clause_count {
+                self.header_mismatch(ParserError::ClauseCount {
+                    clause_count: self.clause_count,
+                    header_clause_count: header.clause_count,
+                })?;
             }
         }
+
+        Ok(())
     }
 
-    fn parse_header_line(&mut self) -> Result<(), ParserError> {
-        let header_line = String::from_utf8_lossy(&self.header_line).into_owned();
+    fn header_mismatch(&self, err: ParserError) -> Result<(), ParserError> {
+        if self.ignore_header_mismatch {
+            log::warn!("{}", err);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
 
-        if !header_line.starts_with("p ") {
-            return self.invalid_header(header_line);
+    /// Finish the current formula when a following header is found.
+    fn finish_formula(&mut self, location: &Location) -> Result<(), ParserError> {
+        if !self.partial_clause.is_empty() {
+            return Err(ParserError::UnterminatedClause {
+                line: location.line(),
+            });
         }
 
-        let mut header_values = header_line[2..].split_whitespace();
+        self.check_header()?;
 
-        if header_values.next() != Some("cnf") {
-            return self.invalid_header(header_line);
-        }
+        let formula = replace(&mut self.formula, CnfFormula::new());
+        self.finished_formulas.push(formula);
+        self.header = None;
+        self.clause_count = 0;
+        self.seen_var_count = 0;
 
-        let var_count: usize = match header_values
-            .next()
-            .and_then(|value| str::parse(value).ok())
-        {
-            None => return self.invalid_header(header_line),
-            Some(value) => value,
-        };
+        Ok(())
+    }
+}
 
-        if var_count > Var::max_count() {
-            self.error = true;
-            return Err(ParserError::LiteralTooLarge {
-                line: self.line_number,
-                index: var_count / 10,
-                final_digit: var_count % 10,
-            });
+impl lexer::Handler for CnfHandler {
+    fn number(
+        &mut self,
+        _location: &Location,
+        negative: bool,
+        value: u64,
+    ) -> Result<(), ParserError> {
+        if value == 0 {
+            self.formula.add_clause(&self.partial_clause);
+            self.partial_clause.clear();
+            self.clause_count += 1;
+        } else {
+            let var = Var::from_dimacs(value as isize);
+            self.seen_var_count = self.seen_var_count.max(var.index() + 1);
+            self.partial_clause.push(var.lit(!negative));
         }
+        Ok(())
+    }
 
-        let clause_count: usize = match header_values
-            .next()
-            .and_then(|value| str::parse(value).ok())
-        {
-            None => return self.invalid_header(header_line),
-            Some(value) => value,
-        };
+    fn begin_header(&mut self, location: &Location) -> Result<bool, ParserError> {
+        if self.header.is_none() {
+            Ok(true)
+        } else if self.multi_formula {
+            self.finish_formula(location)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn header(&mut self, location: &Location, header_line: &str) -> Result<(), ParserError> {
+        let (var_count, clause_count, mut rest) = location.parse_header(header_line, "cnf")?;
 
-        if header_values.next().is_some() {
-            return self.invalid_header(header_line);
+        if rest.next().is_some() {
+            return Err(location.invalid_header(header_line));
         }
 
         self.header = Some(DimacsHeader {
@@ -371,12 +506,11 @@ impl DimacsParser {
         Ok(())
     }
 
-    fn invalid_header(&mut self, header_line: String) -> Result<(), ParserError> {
-        self.error = true;
-        Err(ParserError::InvalidHeader {
-            line: self.line_number,
-            header: header_line,
-        })
+    fn comment(&mut self, text: &[u8]) {
+        self.comments.push(DimacsComment {
+            text: String::from_utf8_lossy(text).into_owned(),
+            after_header: self.header.is_some(),
+        });
     }
 }
 
@@ -423,12 +557,206 @@ pub fn write_dimacs(target: &mut impl io::Write, formula: &CnfFormula) -> io::Re
     write_dimacs_clauses(&mut *target, formula.iter())
 }
 
+/// Write a formula as DIMACS CNF using the given header.
+///
+/// This allows declaring more variables than the formula uses, e.g. variables reserved for later
+/// use. Returns an error of kind [`io::ErrorKind::InvalidInput`] without writing anything if the
+/// header declares fewer variables or clauses than the formula contains.
+pub fn write_dimacs_with_header(
+    target: &mut impl io::Write,
+    formula: &CnfFormula,
+    header: DimacsHeader,
+) -> io::Result<()> {
+    if header.var_count < formula.var_count() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "header declares {} variables but formula has {}",
+                header.var_count,
+                formula.var_count()
+            ),
+        ));
+    }
+    if header.clause_count < formula.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "header declares {} clauses but formula has {}",
+                header.clause_count,
+                formula.len()
+            ),
+        ));
+    }
+    write_dimacs_header(&mut *target, header)?;
+    write_dimacs_clauses(&mut *target, formula.iter())
+}
+
+/// Width reserved for each count in the header written by [`DimacsWriter`].
+const HEADER_COUNT_WIDTH: usize = 20;
+
+/// Incremental DIMACS CNF writer that fills in the header after writing all clauses.
+///
+/// A placeholder header is written on creation. Calling [`finish`](DimacsWriter::finish) seeks
+/// back and overwrites it with the actual variable and clause counts, padded with spaces. This
+/// requires a seekable target. For non-seekable targets, write to an [`io::Cursor`] first or use
+/// [`write_dimacs_header`] and [`write_dimacs_clauses`] with known counts.
+pub struct DimacsWriter<W: io::Write + io::Seek> {
+    target: W,
+    header_position: u64,
+    var_count: usize,
+    clause_count: usize,
+}
+
+impl<W: io::Write + io::Seek> DimacsWriter<W> {
+    /// Create a writer, writing a placeholder header at the current position of the target.
+    pub fn new(mut target: W) -> io::Result<Self> {
+        let header_position = target.stream_position()?;
+        Self::write_padded_header(&mut target, 0, 0)?;
+        Ok(DimacsWriter {
+            target,
+            header_position,
+            var_count: 0,
+            clause_count: 0,
+        })
+    }
+
+    /// Make sure the header's variable count is at least the given value.
+    pub fn set_var_count(&mut self, var_count: usize) {
+        self.var_count = self.var_count.max(var_count);
+    }
+
+    /// Write a clause.
+    pub fn write_clause(&mut self, clause: &[Lit]) -> io::Result<()> {
+        for lit in clause {
+            self.var_count = self.var_count.max(lit.index() + 1);
+        }
+        self.clause_count += 1;
+        write_dimacs_clauses(&mut self.target, Some(clause))
+    }
+
+    /// Number of clauses written so far.
+    pub fn clause_count(&self) -> usize {
+        self.clause_count
+    }
+
+    /// Overwrite the placeholder header with the actual counts and return the target.
+    ///
+    /// The target is positioned at the end of the written clauses afterwards.
+    pub fn finish(mut self) -> io::Result<W> {
+        let end_position = self.target.stream_position()?;
+        self.target
+            .seek(io::SeekFrom::Start(self.header_position))?;
+        Self::write_padded_header(&mut self.target, self.var_count, self.clause_count)?;
+        self.target.seek(io::SeekFrom::Start(end_position))?;
+        self.target.flush()?;
+        Ok(self.target)
+    }
+
+    fn write_padded_header(
+        target: &mut W,
+        var_count: usize,
+        clause_count: usize,
+    ) -> io::Result<()> {
+        writeln!(
+            target,
+            "p cnf {:<width$} {:<width$}",
+            var_count,
+            clause_count,
+            width = HEADER_COUNT_WIDTH
+        )
+    }
+}
+
+/// Write the formula held by a parser as DIMACS CNF.
+///
+/// The header is computed using [`DimacsParser::inferred_header`] so it matches the parsed
+/// clauses even when the input had no header. This writes the clauses not yet retrieved using
+/// [`DimacsParser::take_formula`], so it is intended to be called on a parser that parsed the
+/// whole input without taking the formula.
+pub fn write_dimacs_from_parser(
+    target: &mut impl io::Write,
+    parser: &DimacsParser,
+) -> io::Result<()> {
+    write_dimacs_header(&mut *target, parser.inferred_header())?;
+    write_dimacs_clauses(&mut *target, parser.cnf.formula.iter())
+}
+
+/// Write a formula as DIMACS CNF preceded by comment lines.
+///
+/// Each line of each given comment is written as a separate comment line before the header.
+pub fn write_dimacs_with_comments(
+    target: &mut impl io::Write,
+    formula: &CnfFormula,
+    comments: impl IntoIterator<Item = impl AsRef<str>>,
+) -> io::Result<()> {
+    for comment in comments.into_iter() {
+        for line in comment.as_ref().split('\n') {
+            if line.is_empty() {
+                target.write_all(b"c\n")?;
+            } else {
+                writeln!(target, "c {}", line)?;
+            }
+        }
+    }
+    write_dimacs(target, formula)
+}
+
+/// Options for [`write_dimacs_with_options`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DimacsWriteOptions {
+    /// Sort the literals of each clause by their DIMACS value.
+    pub sort_literals: bool,
+    /// Sort the clauses lexicographically by the DIMACS values of their literals.
+    ///
+    /// When combined with `sort_literals`, the clauses are compared after sorting their literals.
+    pub sort_clauses: bool,
+}
+
+/// Write a formula as DIMACS CNF with the given options.
+///
+/// Sorting literals and clauses makes the output independent of the order in which clauses were
+/// added to the formula, which is useful for comparing generated formulas.
+pub fn write_dimacs_with_options(
+    target: &mut impl io::Write,
+    formula: &CnfFormula,
+    options: DimacsWriteOptions,
+) -> io::Result<()> {
+    if !options.sort_literals && !options.sort_clauses {
+        return write_dimacs(target, formula);
+    }
+
+    let mut clauses: Vec<Vec<Lit>> = formula.iter().map(|clause| clause.to_vec()).collect();
+
+    if options.sort_literals {
+        for clause in clauses.iter_mut() {
+            clause.sort_by_key(|lit| lit.to_dimacs());
+        }
+    }
+
+    if options.sort_clauses {
+        clauses.sort_by(|a, b| {
+            let a = a.iter().map(|lit| lit.to_dimacs());
+            let b = b.iter().map(|lit| lit.to_dimacs());
+            a.cmp(b)
+        });
+    }
+
+    write_dimacs_header(
+        &mut *target,
+        DimacsHeader {
+            var_count: formula.var_count(),
+            clause_count: formula.len(),
+        },
+    )?;
+    write_dimacs_clauses(&mut *target, clauses)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use anyhow::Error;
-    use proptest::{test_runner::TestCaseError, *};
+    use proptest::{prelude::*, test_runner::TestCaseError, *};
 
     use varisat_formula::{cnf::strategy::*, cnf_formula};
 
@@ -449,6 +777,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn comments() -> Result<(), Error> {
+        let (parsed, comments) = DimacsParser::parse_with_comments(
+            b"c first\nc\np cnf 2 1\nccompact\r\n1 -2 0\nc last" as &[_],
+        )?;
+
+        assert_eq!(parsed, cnf_formula![1, -2;]);
+
+        let expected = [
+            ("first", false),
+            ("", false),
+            ("compact", true),
+            ("last", true),
+        ];
+
+        assert_eq!(
+            comments,
+            expected
+                .iter()
+                .map(|&(text, after_header)| DimacsComment {
+                    text: text.to_string(),
+                    after_header
+                })
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
     macro_rules! expect_error {
         ( $input:expr, $( $cases:tt )* ) => {
             match DimacsParser::parse($input as &[_]) {
@@ -505,6 +862,154 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inferred_header() -> Result<(), Error> {
+        let parser = DimacsParser::parse_incremental(b"1 -3 0\n2 0\n-1 0\n" as &[_], |_| Ok(()))?;
+
+        let header = parser.inferred_header();
+        assert_eq!((header.var_count, header.clause_count), (3, 3));
+
+        let mut buf = vec![];
+        write_dimacs_from_parser(&mut buf, &parser)?;
+        assert_eq!(&buf[..], &b"p cnf 3 3\n1 -3 0\n2 0\n-1 0\n"[..]);
+
+        let mut parser = DimacsParser::new();
+        parser.parse_chunk(b"p cnf 5 1\n1 0\n")?;
+        parser.eof()?;
+        let header = parser.inferred_header();
+        assert_eq!((header.var_count, header.clause_count), (1, 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dimacs_writer() -> Result<(), Error> {
+        let formula = cnf_formula![
+            1, -4;
+            2;
+            -3, 1, 2;
+        ];
+
+        let mut writer = DimacsWriter::new(io::Cursor::new(vec![]))?;
+        for clause in formula.iter() {
+            writer.write_clause(clause)?;
+        }
+        assert_eq!(writer.clause_count(), 3);
+        let buf = writer.finish()?.into_inner();
+
+        let mut parser = DimacsParser::parse_incremental(&buf[..], |_| Ok(()))?;
+        let header = parser.header().unwrap();
+        assert_eq!((header.var_count, header.clause_count), (4, 3));
+        assert_eq!(parser.take_formula(), formula);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_clauses_checks_header() {
+        let mut clauses = vec![];
+        let result = DimacsParser::parse_clauses(b"p cnf 10 2\n 1 -2 0" as &[_], |clause| {
+            clauses.push(clause.to_vec())
+        });
+
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(ParserError::ClauseCount {
+                clause_count: 1,
+                header_clause_count: 2,
+            })) => (),
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+
+        assert_eq!(
+            clauses,
+            vec![vec![Lit::from_dimacs(1), Lit::from_dimacs(-2)]]
+        );
+    }
+
+    #[test]
+    fn concatenated_formulas() -> Result<(), Error> {
+        let input = b"c first\np cnf 3 2\n1 2 0\n-3 0\np cnf 2 1\nc second\n-1 2 0\n";
+        let mut formulas = vec![];
+        DimacsParser::parse_multi(&input[..], |formula| formulas.push(formula))?;
+
+        assert_eq!(formulas.len(), 2);
+        assert_eq!(formulas[0], cnf_formula![1, 2; -3;]);
+        assert_eq!(formulas[0].var_count(), 3);
+        assert_eq!(formulas[1], cnf_formula![-1, 2;]);
+        assert_eq!(formulas[1].var_count(), 2);
+
+        let input = b"p cnf 3 2\n1 2 0\np cnf 2 1\n-1 2 0\n";
+        let result = DimacsParser::parse_multi(&input[..], |_| ());
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(ParserError::ClauseCount {
+                clause_count: 1,
+                header_clause_count: 2,
+            })) => (),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        assert!(DimacsParser::parse(&b"p cnf 1 1\n1 0\np cnf 1 1\n1 0\n"[..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_header_mismatch() -> Result<(), Error> {
+        let input = b"p cnf 2 3\n1 2 0\n-1 3 0\n";
+
+        match DimacsParser::parse(&input[..]).map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(ParserError::VarCount {
+                var_count: 3,
+                header_var_count: 2,
+            })) => (),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let formula = DimacsParser::parse_lenient(&input[..])?;
+        assert_eq!(formula, cnf_formula![1, 2; -1, 3;]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn line_context() {
+        let input = b"p cnf 3 2\n1 2 0\n1 2 -x 0\n";
+
+        let result = DimacsParser::new()
+            .with_line_context()
+            .parse_incremental_with(&input[..], |_| Ok(()));
+
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(err @ ParserError::UnexpectedInput { .. })) => {
+                assert_eq!(
+                    err.to_string(),
+                    "line 3: Unexpected character in DIMACS CNF input: 'x' in \"1 2 -x 0\""
+                );
+            }
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+
+        let result = DimacsParser::parse(&input[..]);
+
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(ParserError::UnexpectedInput { context, .. })) => {
+                assert_eq!(context, LineContext(None));
+            }
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+
+        let result = DimacsParser::new()
+            .with_line_context()
+            .parse_incremental_with(&b"c comment\np cnf 3\n"[..], |_| Ok(()));
+
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(ParserError::InvalidHeader { context, .. })) => {
+                assert_eq!(context, LineContext(Some("p cnf 3".to_string())));
+            }
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn syntax_errors() {
         expect_error!(
@@ -557,6 +1062,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reserved_vars_in_header() -> Result<(), Error> {
+        let formula = cnf_formula![
+            1, -2;
+            2, 3;
+        ];
+
+        let mut buf = vec![];
+        write_dimacs_with_header(
+            &mut buf,
+            &formula,
+            DimacsHeader {
+                var_count: 10,
+                clause_count: 2,
+            },
+        )?;
+
+        assert_eq!(&buf[..], b"p cnf 10 2\n1 -2 0\n2 3 0\n" as &[_]);
+
+        let parsed = DimacsParser::parse(&buf[..])?;
+
+        assert_eq!(parsed.var_count(), 10);
+        assert_eq!(
+            parsed.iter().collect::<Vec<_>>(),
+            formula.iter().collect::<Vec<_>>()
+        );
+
+        for header in [
+            DimacsHeader {
+                var_count: 2,
+                clause_count: 2,
+            },
+            DimacsHeader {
+                var_count: 10,
+                clause_count: 1,
+            },
+        ] {
+            let mut buf = vec![];
+            let err = write_dimacs_with_header(&mut buf, &formula, header).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(buf.is_empty());
+        }
+
+        Ok(())
+    }
+
     proptest! {
 
         #[test]
@@ -569,5 +1120,132 @@ mod tests {
 
             prop_assert_eq!(parsed, input);
         }
+
+        #[test]
+        fn display_roundtrip(input in cnf_formula(1..100usize, 0..1000, 0..10)) {
+            let mut buf = vec![];
+
+            write_dimacs(&mut buf, &input)?;
+
+            let display = input.to_string();
+            prop_assert_eq!(display.as_bytes(), &buf[..]);
+
+            let parsed = DimacsParser::parse(display.as_bytes())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert_eq!(parsed, input);
+        }
+
+        #[test]
+        fn roundtrip_with_comments(
+            input in cnf_formula(1..100usize, 0..1000, 0..10),
+            comments in collection::vec("[^\r\n]*", 0..5),
+        ) {
+            let mut buf = vec![];
+
+            write_dimacs_with_comments(&mut buf, &input, &comments)?;
+
+            let (parsed, parsed_comments) = DimacsParser::parse_with_comments(&buf[..])
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert_eq!(parsed, input);
+
+            let expected_comments: Vec<_> = comments
+                .iter()
+                .map(|text| DimacsComment { text: text.clone(), after_header: false })
+                .collect();
+
+            prop_assert_eq!(parsed_comments, expected_comments);
+        }
+
+        #[test]
+        fn sorted_output_is_deterministic(
+            (input, shuffled) in cnf_formula(1..100usize, 0..200, 0..10).prop_flat_map(|formula| {
+                let clauses: Vec<Vec<Lit>> = formula.iter().map(|clause| clause.to_vec()).collect();
+                (Just(formula), Just(clauses).prop_shuffle())
+            }),
+        ) {
+            let mut reordered = CnfFormula::new();
+            reordered.set_var_count(input.var_count());
+            for clause in shuffled.iter() {
+                let reversed: Vec<Lit> = clause.iter().rev().cloned().collect();
+                reordered.add_clause(&reversed);
+            }
+
+            let options = DimacsWriteOptions {
+                sort_literals: true,
+                sort_clauses: true,
+            };
+
+            let mut buf = vec![];
+            write_dimacs_with_options(&mut buf, &input, options)?;
+
+            let mut reordered_buf = vec![];
+            write_dimacs_with_options(&mut reordered_buf, &reordered, options)?;
+
+            prop_assert_eq!(&buf, &reordered_buf);
+
+            let parsed = DimacsParser::parse(&buf[..]).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert_eq!(parsed.var_count(), input.var_count());
+            prop_assert_eq!(parsed.len(), input.len());
+
+            let parsed_clauses: Vec<Vec<isize>> = parsed
+                .iter()
+                .map(|clause| clause.iter().map(|lit| lit.to_dimacs()).collect())
+                .collect();
+
+            for clause in parsed_clauses.iter() {
+                prop_assert!(clause.windows(2).all(|pair| pair[0] <= pair[1]));
+            }
+            prop_assert!(parsed_clauses.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+
+        #[test]
+        fn default_options_match_write_dimacs(input in cnf_formula(1..100usize, 0..200, 0..10)) {
+            let mut buf = vec![];
+            write_dimacs(&mut buf, &input)?;
+
+            let mut options_buf = vec![];
+            write_dimacs_with_options(&mut options_buf, &input, DimacsWriteOptions::default())?;
+
+            prop_assert_eq!(buf, options_buf);
+        }
+
+        #[test]
+        fn parse_clauses(input in cnf_formula(1..100usize, 0..1000, 0..10)) {
+            let mut buf = vec![];
+
+            write_dimacs(&mut buf, &input)?;
+
+            let mut parsed = CnfFormula::new();
+
+            let parser = DimacsParser::parse_clauses(&buf[..], |clause| {
+                parsed.add_clause(clause);
+            }).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            parsed.set_var_count(parser.var_count());
+
+            prop_assert_eq!(parsed, input);
+        }
+
+        #[cfg(feature = "gzip")]
+        #[test]
+        fn gzip_roundtrip(input in cnf_formula(1..100usize, 0..1000, 0..10)) {
+            use flate2::{write::GzEncoder, Compression};
+            use io::Read;
+
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+
+            write_dimacs(&mut encoder, &input)?;
+
+            let buf = encoder.finish()?;
+
+            // Feed single bytes to make sure detection works with short reads.
+            let parsed = DimacsParser::parse((&buf[..1]).chain(&buf[1..]))
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert_eq!(parsed, input);
+        }
     }
 }
@@ -0,0 +1,449 @@
+//! Parser for weighted DIMACS CNF (WCNF) files.
+use std::{
+    io,
+    mem::{replace, take},
+};
+
+use varisat_formula::{CnfFormula, ExtendFormula, Lit, Var};
+
+use anyhow::Error;
+
+use crate::{
+    lexer::{Handler, Lexer, Location},
+    ParserError,
+};
+
+/// Hard and weighted soft clauses of a WCNF file.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct WeightedFormula {
+    /// Clauses that have to be satisfied.
+    pub hard: CnfFormula,
+    /// Clauses that should be satisfied, together with their weight.
+    pub soft: Vec<(u64, Vec<Lit>)>,
+}
+
+/// Variable count, clause count and top weight present in a WCNF header.
+#[derive(Copy, Clone, Debug)]
+pub struct WcnfHeader {
+    pub var_count: usize,
+    pub clause_count: usize,
+    /// Clauses with at least this weight are hard clauses.
+    pub top: Option<u64>,
+}
+
+/// Parser for weighted DIMACS CNF (WCNF) files.
+///
+/// Every clause is prefixed by its weight. Clauses with a weight of at least the top weight given
+/// in the `p wcnf` header are hard clauses. Clauses can also be marked as hard by prefixing them
+/// with `h` instead of a weight.
+///
+/// Like [`DimacsParser`](crate::DimacsParser) this parser can consume the input in chunks while
+/// also producing the parsed result in chunks.
+#[derive(Default)]
+pub struct WcnfParser {
+    lexer: Lexer,
+    wcnf: WcnfHandler,
+}
+
+/// Builds the weighted formula from the tokens of a WCNF file.
+struct WcnfHandler {
+    formula: WeightedFormula,
+    partial_clause: Vec<Lit>,
+    header: Option<WcnfHeader>,
+
+    clause_count: usize,
+    var_count: usize,
+
+    expect_weight: bool,
+    hard_clause: bool,
+    weight: u64,
+}
+
+impl Default for WcnfHandler {
+    fn default() -> WcnfHandler {
+        WcnfHandler {
+            formula: WeightedFormula::default(),
+            partial_clause: vec![],
+            header: None,
+
+            clause_count: 0,
+            var_count: 0,
+
+            expect_weight: true,
+            hard_clause: false,
+            weight: 0,
+        }
+    }
+}
+
+impl WcnfParser {
+    /// Create a new WCNF parser.
+    pub fn new() -> WcnfParser {
+        WcnfParser::default()
+    }
+
+    /// Capture the contents of the offending line in parse errors.
+    ///
+    /// See [`DimacsParser::with_line_context`](crate::DimacsParser::with_line_context).
+    pub fn with_line_context(mut self) -> Self {
+        self.lexer.set_line_context(true);
+        self
+    }
+
+    /// Parse the given input and check the header if present.
+    pub fn parse(input: impl io::Read) -> Result<WeightedFormula, Error> {
+        Ok(Self::parse_incremental(input, |_| Ok(()))?.take_formula())
+    }
+
+    /// Parse the given input incrementally and check the header if present.
+    ///
+    /// The callback is invoked repeatedly with a reference to the parser. The callback can process
+    /// the formula incrementally by calling [`take_formula`](WcnfParser::take_formula) on the
+    /// passed argument.
+    pub fn parse_incremental(
+        input: impl io::Read,
+        callback: impl FnMut(&mut WcnfParser) -> Result<(), Error>,
+    ) -> Result<WcnfParser, Error> {
+        Self::new().parse_incremental_with(input, callback)
+    }
+
+    /// Parse the given input incrementally using this parser and check the header if present.
+    ///
+    /// This works like [`parse_incremental`](WcnfParser::parse_incremental) but allows
+    /// configuring the parser beforehand.
+    pub fn parse_incremental_with(
+        mut self,
+        input: impl io::Read,
+        mut callback: impl FnMut(&mut WcnfParser) -> Result<(), Error>,
+    ) -> Result<WcnfParser, Error> {
+        use io::BufRead;
+
+        let mut buffer = io::BufReader::new(input);
+
+        loop {
+            let data = buffer.fill_buf()?;
+            if data.is_empty() {
+                break;
+            }
+            self.parse_chunk(data)?;
+            let len = data.len();
+            buffer.consume(len);
+
+            callback(&mut self)?;
+        }
+        self.eof()?;
+        callback(&mut self)?;
+        self.check_header()?;
+
+        Ok(self)
+    }
+
+    /// Parse a chunk of input.
+    ///
+    /// After parsing the last chunk call the [`eof`](WcnfParser::eof) method.
+    ///
+    /// If this method returns an error, the parser is in an invalid state and cannot parse further
+    /// chunks.
+    pub fn parse_chunk(&mut self, chunk: &[u8]) -> Result<(), ParserError> {
+        self.lexer.parse_chunk(chunk, &mut self.wcnf)
+    }
+
+    /// Finish parsing the input.
+    ///
+    /// This does not check whether the header information was correct, call
+    /// [`check_header`](WcnfParser::check_header) for this.
+    pub fn eof(&mut self) -> Result<(), ParserError> {
+        self.lexer.eof(&mut self.wcnf)?;
+
+        if !self.wcnf.expect_weight {
+            return Err(ParserError::UnterminatedClause {
+                line: self.lexer.line_number(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the header information when present.
+    ///
+    /// Does nothing when the input doesn't contain a header.
+    pub fn check_header(&self) -> Result<(), ParserError> {
+        let wcnf = &self.wcnf;
+        if let Some(header) = wcnf.header {
+            if wcnf.var_count != header.var_count {
+                return Err(ParserError::VarCount {
+                    var_count: wcnf.var_count,
+                    header_var_count: header.var_count,
+                });
+            }
+
+            if wcnf.clause_count != header.clause_count {
+                return Err(ParserError::ClauseCount {
+                    clause_count: wcnf.clause_count,
+                    header_clause_count: header.clause_count,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the hard and soft clauses parsed since the last call to this method.
+    ///
+    /// The variable count of the returned hard clauses will be the maximum of the variable count
+    /// so far, including variables of soft clauses, and the variable count of the header if
+    /// present.
+    pub fn take_formula(&mut self) -> WeightedFormula {
+        let wcnf = &mut self.wcnf;
+        let mut new_hard = CnfFormula::new();
+        new_hard.set_var_count(wcnf.var_count);
+        let mut formula = WeightedFormula {
+            hard: replace(&mut wcnf.formula.hard, new_hard),
+            soft: take(&mut wcnf.formula.soft),
+        };
+        formula.hard.set_var_count(wcnf.var_count);
+        formula
+    }
+
+    /// Return the WCNF header data if present.
+    pub fn header(&self) -> Option<WcnfHeader> {
+        self.wcnf.header
+    }
+
+    /// Number of clauses parsed.
+    pub fn clause_count(&self) -> usize {
+        self.wcnf.clause_count
+    }
+
+    /// Number of variables in the parsed formula.
+    pub fn var_count(&self) -> usize {
+        self.wcnf.var_count
+    }
+}
+
+impl Handler for WcnfHandler {
+    fn number_limit(&self) -> u64 {
+        if self.expect_weight {
+            u64::MAX
+        } else {
+            Var::max_count() as u64
+        }
+    }
+
+    fn number_too_large(&self, location: &Location, partial: u64, digit: u64) -> ParserError {
+        if self.expect_weight {
+            ParserError::WeightTooLarge {
+                line: location.line(),
+            }
+        } else {
+            ParserError::LiteralTooLarge {
+                line: location.line(),
+                index: partial as usize,
+                final_digit: digit as usize,
+                context: location.context(),
+            }
+        }
+    }
+
+    fn sign(&mut self, location: &Location) -> Result<(), ParserError> {
+        if self.expect_weight {
+            return Err(ParserError::MissingWeight {
+                line: location.line(),
+            });
+        }
+        Ok(())
+    }
+
+    fn number(
+        &mut self,
+        _location: &Location,
+        negative: bool,
+        value: u64,
+    ) -> Result<(), ParserError> {
+        if self.expect_weight {
+            self.expect_weight = false;
+            self.weight = value;
+            self.hard_clause = self
+                .header
+                .and_then(|header| header.top)
+                .is_some_and(|top| self.weight >= top);
+        } else if value == 0 {
+            if self.hard_clause {
+                self.formula.hard.add_clause(&self.partial_clause);
+            } else {
+                self.formula
+                    .soft
+                    .push((self.weight, self.partial_clause.clone()));
+            }
+            self.partial_clause.clear();
+            self.clause_count += 1;
+            self.expect_weight = true;
+            self.hard_clause = false;
+        } else {
+            let var = Var::from_dimacs(value as isize);
+            self.var_count = self.var_count.max(var.index() + 1);
+            self.partial_clause.push(var.lit(!negative));
+        }
+        Ok(())
+    }
+
+    fn begin_header(&mut self, _location: &Location) -> Result<bool, ParserError> {
+        Ok(self.header.is_none())
+    }
+
+    fn header(&mut self, location: &Location, header_line: &str) -> Result<(), ParserError> {
+        let (var_count, clause_count, mut rest) = location.parse_header(header_line, "wcnf")?;
+
+        let top: Option<u64> = match rest.next() {
+            None => None,
+            Some(value) => match str::parse(value) {
+                Ok(value) => Some(value),
+                Err(_) => return Err(location.invalid_header(header_line)),
+            },
+        };
+
+        if rest.next().is_some() {
+            return Err(location.invalid_header(header_line));
+        }
+
+        self.header = Some(WcnfHeader {
+            var_count,
+            clause_count,
+            top,
+        });
+
+        self.var_count = self.var_count.max(var_count);
+
+        Ok(())
+    }
+
+    fn other(&mut self, _location: &Location, byte: u8) -> Result<bool, ParserError> {
+        if byte == b'h' && self.expect_weight {
+            self.expect_weight = false;
+            self.hard_clause = true;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection, prelude::*, test_runner::TestCaseError};
+
+    use varisat_formula::{cnf::strategy::*, cnf_formula, lit::strategy::lit, lits};
+
+    #[test]
+    fn hard_and_soft_clauses() -> Result<(), Error> {
+        let parsed = WcnfParser::parse(
+            b"c comment\np wcnf 4 4 10\n10 1 -2 0\n3 -3 0\n 12 4 0\n1 0\n" as &[_],
+        )?;
+
+        assert_eq!(parsed.hard, cnf_formula![1, -2; 4;]);
+        assert_eq!(parsed.hard.var_count(), 4);
+        assert_eq!(parsed.soft, vec![(3, lits![-3].to_vec()), (1, vec![])]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hard_marker_without_header() -> Result<(), Error> {
+        let parsed = WcnfParser::parse(b"h 1 2 0\n5 -1 0\nh -2 0\n" as &[_])?;
+
+        assert_eq!(parsed.hard, cnf_formula![1, 2; -2;]);
+        assert_eq!(parsed.soft, vec![(5, lits![-1].to_vec())]);
+
+        Ok(())
+    }
+
+    fn parse_error(input: &[u8]) -> ParserError {
+        match WcnfParser::parse(input) {
+            Ok(parsed) => panic!("Expected error but got {:?}", parsed),
+            Err(err) => err.downcast().expect("unexpected error type"),
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse_error(b"p wcnf 2 1 5\n-1 2 0\n"),
+            ParserError::MissingWeight { line: 2 }
+        ));
+        assert!(matches!(
+            parse_error(b"p wcnf 2 1 5\n99999999999999999999 1 0\n"),
+            ParserError::WeightTooLarge { line: 2 }
+        ));
+        assert!(matches!(
+            parse_error(b"p wcnf 2 1 5\n3\n"),
+            ParserError::UnterminatedClause { .. }
+        ));
+        assert!(matches!(
+            parse_error(b"p wcnf 2 1 x\n"),
+            ParserError::InvalidHeader { .. }
+        ));
+        assert!(matches!(
+            parse_error(b"p wcnf 2 2 5\n1 1 0\n"),
+            ParserError::ClauseCount { .. }
+        ));
+    }
+
+    #[test]
+    fn line_context() {
+        let result = WcnfParser::new()
+            .with_line_context()
+            .parse_incremental_with(&b"p wcnf 2 2 5\n5 1 0\n3 x 0\n"[..], |_| Ok(()));
+
+        match result.map_err(|err| err.downcast::<ParserError>()) {
+            Err(Ok(err @ ParserError::UnexpectedInput { line: 3, .. })) => {
+                assert!(err.to_string().ends_with(" in \"3 x 0\""));
+            }
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(
+            hard in cnf_formula(1..50usize, 0..100, 0..10),
+            soft in collection::vec(
+                (0..100u64, collection::vec(lit(0..50usize), 0..10)),
+                0..100,
+            ),
+        ) {
+            let top = 100;
+            let mut buf = vec![];
+
+            let var_count = soft
+                .iter()
+                .flat_map(|(_, clause)| clause.iter())
+                .map(|lit| lit.index() + 1)
+                .fold(hard.var_count(), usize::max);
+
+            buf.extend_from_slice(
+                format!("p wcnf {} {} {}\n", var_count, hard.len() + soft.len(), top).as_bytes(),
+            );
+
+            for clause in hard.iter() {
+                buf.extend_from_slice(format!("{} ", top).as_bytes());
+                crate::write_dimacs_clauses(&mut buf, Some(clause))?;
+            }
+
+            for (weight, clause) in soft.iter() {
+                buf.extend_from_slice(format!("{} ", weight).as_bytes());
+                crate::write_dimacs_clauses(&mut buf, Some(clause))?;
+            }
+
+            let parsed =
+                WcnfParser::parse(&buf[..]).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            let mut expected_hard = hard;
+            expected_hard.set_var_count(var_count);
+
+            prop_assert_eq!(parsed.hard, expected_hard);
+            prop_assert_eq!(parsed.soft, soft);
+        }
+    }
+}
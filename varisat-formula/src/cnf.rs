@@ -1,5 +1,6 @@
 //! CNF formulas.
-use std::{cmp::max, fmt, ops::Range};
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+use core::{cmp::max, fmt, ops::Range};
 
 use crate::lit::{Lit, Var};
 
@@ -19,6 +20,85 @@ impl CnfFormula {
         CnfFormula::default()
     }
 
+    /// Create an empty CNF formula with preallocated space.
+    ///
+    /// Space for `clauses` clauses with a total of `lits` literals is allocated. As variables don't
+    /// need any storage, the variable count is set to `vars`, matching the header of a DIMACS CNF
+    /// file.
+    pub fn with_capacity(vars: usize, clauses: usize, lits: usize) -> CnfFormula {
+        CnfFormula {
+            var_count: vars,
+            literals: Vec::with_capacity(lits),
+            clause_ranges: Vec::with_capacity(clauses),
+        }
+    }
+
+    /// Create a CNF formula from a list of owned clauses.
+    ///
+    /// Unlike the generic [`From`] implementation this allocates the literal buffer only once, as
+    /// the total number of literals is known up front.
+    pub fn from_vecs(clauses: Vec<Vec<Lit>>) -> CnfFormula {
+        let lits = clauses.iter().map(|clause| clause.len()).sum();
+        let mut formula = CnfFormula::with_capacity(0, clauses.len(), lits);
+        for clause in clauses {
+            formula.add_clause(&clause);
+        }
+        formula
+    }
+
+    /// Reserve space for at least `clauses` more clauses with a total of `lits` more literals.
+    pub fn reserve(&mut self, clauses: usize, lits: usize) {
+        self.literals.reserve(lits);
+        self.clause_ranges.reserve(clauses);
+    }
+
+    /// Shrink the allocated space to the size of the formula.
+    pub fn shrink_to_fit(&mut self) {
+        self.literals.shrink_to_fit();
+        self.clause_ranges.shrink_to_fit();
+    }
+
+    /// Start a new clause that is built literal by literal.
+    ///
+    /// Literals of a clause that was started but not finished using
+    /// [`end_clause`](CnfFormula::end_clause) are discarded, but the variable count is not reduced.
+    ///
+    /// While a clause is being built, no clauses may be added or removed by other methods. This is
+    /// checked by debug assertions.
+    pub fn begin_clause(&mut self) {
+        let begin = self.pending_clause_begin();
+        self.literals.truncate(begin);
+    }
+
+    /// Append a literal to the clause started by [`begin_clause`](CnfFormula::begin_clause).
+    pub fn push_lit(&mut self, lit: Lit) {
+        self.var_count = max(lit.index() + 1, self.var_count);
+        self.literals.push(lit);
+    }
+
+    /// Finish the clause started by [`begin_clause`](CnfFormula::begin_clause) and add it.
+    ///
+    /// This adds the same clause as passing all pushed literals to
+    /// [`add_clause`](ExtendFormula::add_clause), without requiring an intermediate buffer.
+    pub fn end_clause(&mut self) {
+        let begin = self.pending_clause_begin();
+        self.clause_ranges.push(begin..self.literals.len());
+    }
+
+    /// Start of the literals pushed since the last added clause.
+    fn pending_clause_begin(&self) -> usize {
+        self.clause_ranges.last().map_or(0, |range| range.end)
+    }
+
+    /// Check that no clause is being built using [`begin_clause`](CnfFormula::begin_clause).
+    fn debug_assert_no_pending_clause(&self) {
+        debug_assert_eq!(
+            self.pending_clause_begin(),
+            self.literals.len(),
+            "clauses modified while a clause started by begin_clause is pending"
+        );
+    }
+
     /// Number of variables in the formula.
     ///
     /// This also counts missing variables if a variable with a higher index is present.
@@ -51,13 +131,182 @@ impl CnfFormula {
             .iter()
             .map(move |range| &literals[range.clone()])
     }
+
+    /// Iterator over all variables that occur in at least one clause, in ascending order.
+    pub fn occurring_vars(&self) -> impl Iterator<Item = Var> {
+        let mut occurs = vec![false; self.var_count];
+        for &lit in self.literals.iter() {
+            occurs[lit.index()] = true;
+        }
+        occurs
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, occurs)| occurs)
+            .map(|(index, _)| Var::from_index(index))
+    }
+
+    /// Whether every variable counted by [`var_count`](CnfFormula::var_count) occurs in a clause.
+    pub fn is_dense(&self) -> bool {
+        self.occurring_vars().count() == self.var_count
+    }
+
+    /// Minimum, maximum and mean length of the clauses.
+    ///
+    /// Returns `(0, 0, 0.0)` for a formula without clauses.
+    pub fn clause_len_stats(&self) -> (usize, usize, f64) {
+        if self.clause_ranges.is_empty() {
+            return (0, 0, 0.0);
+        }
+        let lens = self.clause_ranges.iter().map(|range| range.len());
+        let min = lens.clone().min().unwrap();
+        let max = lens.clone().max().unwrap();
+        let total: usize = lens.sum();
+        (min, max, total as f64 / self.clause_ranges.len() as f64)
+    }
+
+    /// Whether every clause has at most `k` literals.
+    pub fn is_cnf_k(&self, k: usize) -> bool {
+        self.clause_ranges.iter().all(|range| range.len() <= k)
+    }
+
+    /// Renumber the variables so that exactly the occurring variables are used.
+    ///
+    /// Returns the renumbered formula and a mapping from each new variable index to the original
+    /// variable. The occurring variables keep their relative order. The renumbered formula is
+    /// equisatisfiable to this formula and a model of it can be translated back into a model of
+    /// this formula by mapping each literal's variable using the returned mapping.
+    pub fn compact(&self) -> (CnfFormula, Vec<Var>) {
+        let original_vars: Vec<Var> = self.occurring_vars().collect();
+
+        let mut new_index = vec![0; self.var_count];
+        for (index, var) in original_vars.iter().enumerate() {
+            new_index[var.index()] = index;
+        }
+
+        let formula = CnfFormula {
+            var_count: original_vars.len(),
+            literals: self
+                .literals
+                .iter()
+                .map(|lit| lit.map_var(|var| Var::from_index(new_index[var.index()])))
+                .collect(),
+            clause_ranges: self.clause_ranges.clone(),
+        };
+
+        (formula, original_vars)
+    }
+
+    /// A copy of this formula without tautological and duplicated clauses.
+    ///
+    /// The literals of each clause are sorted and duplicated literals are removed. Clauses that
+    /// contain a literal and its negation are dropped, as are clauses that are equal to a previous
+    /// clause after sorting. The variable count is not changed.
+    pub fn normalized(&self) -> CnfFormula {
+        let mut formula = CnfFormula::new();
+        formula.set_var_count(self.var_count);
+
+        let mut seen: BTreeSet<Vec<Lit>> = BTreeSet::new();
+        let mut clause = vec![];
+
+        for lits in self.iter() {
+            clause.clear();
+            clause.extend_from_slice(lits);
+            clause.sort_unstable();
+            clause.dedup();
+
+            if clause.windows(2).any(|pair| pair[0] == !pair[1]) {
+                continue;
+            }
+
+            if seen.insert(clause.clone()) {
+                formula.add_clause(&clause);
+            }
+        }
+
+        formula
+    }
+
+    /// Whether both formulas contain the same clauses, ignoring the order of clauses and literals.
+    ///
+    /// This compares the multisets of clauses after sorting and deduplicating the literals of each
+    /// clause. It is a syntactic comparison, so logically equivalent formulas with different
+    /// clauses are not considered equal. The variable count is not compared.
+    pub fn equivalent_as_sets(&self, other: &CnfFormula) -> bool {
+        self.len() == other.len() && self.sorted_clauses() == other.sorted_clauses()
+    }
+
+    /// Sorted list of all clauses with sorted and deduplicated literals.
+    fn sorted_clauses(&self) -> Vec<Vec<Lit>> {
+        let mut clauses: Vec<Vec<Lit>> = self
+            .iter()
+            .map(|lits| {
+                let mut clause = lits.to_vec();
+                clause.sort_unstable();
+                clause.dedup();
+                clause
+            })
+            .collect();
+        clauses.sort_unstable();
+        clauses
+    }
+
+    /// Appends all clauses of another formula.
+    ///
+    /// The variable count is increased to the variable count of `other` if that is larger.
+    pub fn extend_formula(&mut self, other: &CnfFormula) {
+        self.debug_assert_no_pending_clause();
+        let offset = self.literals.len();
+        self.literals.extend_from_slice(&other.literals);
+        self.clause_ranges.reserve(other.clause_ranges.len());
+        self.clause_ranges.extend(
+            other
+                .clause_ranges
+                .iter()
+                .map(|range| range.start + offset..range.end + offset),
+        );
+        self.set_var_count(other.var_count);
+    }
+
+    /// Removes the clause at the given position.
+    ///
+    /// The variable count is not changed. Panics if `index` is out of bounds.
+    pub fn remove_clause(&mut self, index: usize) {
+        self.debug_assert_no_pending_clause();
+        let range = self.clause_ranges.remove(index);
+        let len = range.len();
+        self.literals.drain(range);
+        for range in self.clause_ranges[index..].iter_mut() {
+            *range = range.start - len..range.end - len;
+        }
+    }
+
+    /// Removes all clauses for which the predicate returns true.
+    ///
+    /// The remaining clauses keep their order. The variable count is not changed.
+    pub fn remove_clauses_where(&mut self, mut pred: impl FnMut(&[Lit]) -> bool) {
+        self.debug_assert_no_pending_clause();
+        let mut write_pos = 0;
+        let literals = &mut self.literals;
+        self.clause_ranges.retain_mut(|range| {
+            if pred(&literals[range.clone()]) {
+                false
+            } else {
+                let len = range.len();
+                literals.copy_within(range.clone(), write_pos);
+                *range = write_pos..write_pos + len;
+                write_pos += len;
+                true
+            }
+        });
+        self.literals.truncate(write_pos);
+    }
 }
 
 /// Convert an iterable of [`Lit`] slices into a CnfFormula
 impl<Clauses, Item> From<Clauses> for CnfFormula
 where
     Clauses: IntoIterator<Item = Item>,
-    Item: std::borrow::Borrow<[Lit]>,
+    Item: core::borrow::Borrow<[Lit]>,
 {
     fn from(clauses: Clauses) -> CnfFormula {
         let mut cnf_formula = CnfFormula::new();
@@ -75,6 +324,22 @@ impl fmt::Debug for CnfFormula {
     }
 }
 
+/// Formats the formula as DIMACS CNF, including the header.
+///
+/// The output is the same as produced by `varisat_dimacs::write_dimacs`.
+impl fmt::Display for CnfFormula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "p cnf {} {}", self.var_count(), self.len())?;
+        for clause in self.iter() {
+            for lit in clause {
+                write!(f, "{} ", lit)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
 impl PartialEq for CnfFormula {
     fn eq(&self, other: &CnfFormula) -> bool {
         self.var_count() == other.var_count()
@@ -103,20 +368,20 @@ pub trait ExtendFormula: Sized {
     }
 
     /// Iterator over multiple new variables.
-    fn new_var_iter(&mut self, count: usize) -> NewVarIter<Self> {
+    fn new_var_iter(&mut self, count: usize) -> NewVarIter<'_, Self> {
         NewVarIter {
             formula: self,
             vars_left: count,
-            phantom: std::marker::PhantomData,
+            phantom: core::marker::PhantomData,
         }
     }
 
     /// Iterator over multiple new literals.
-    fn new_lit_iter(&mut self, count: usize) -> NewVarIter<Self, Lit> {
+    fn new_lit_iter(&mut self, count: usize) -> NewVarIter<'_, Self, Lit> {
         NewVarIter {
             formula: self,
             vars_left: count,
-            phantom: std::marker::PhantomData,
+            phantom: core::marker::PhantomData,
         }
     }
 
@@ -144,7 +409,7 @@ pub trait ExtendFormula: Sized {
 pub struct NewVarIter<'a, F, V = Var> {
     formula: &'a mut F,
     vars_left: usize,
-    phantom: std::marker::PhantomData<V>,
+    phantom: core::marker::PhantomData<V>,
 }
 
 impl<'a, F, V> Iterator for NewVarIter<'a, F, V>
@@ -167,6 +432,7 @@ where
 
 impl ExtendFormula for CnfFormula {
     fn add_clause(&mut self, clause: &[Lit]) {
+        self.debug_assert_no_pending_clause();
         let begin = self.literals.len();
         self.literals.extend_from_slice(clause);
         let end = self.literals.len();
@@ -285,7 +551,9 @@ pub mod strategy {
 mod tests {
     use super::{strategy::*, *};
 
-    use proptest::*;
+    use proptest::prelude::*;
+
+    use rand::seq::SliceRandom;
 
     #[test]
     fn new_vars() {
@@ -298,6 +566,102 @@ mod tests {
         assert_eq!(formula.var_count(), 3);
     }
 
+    #[test]
+    fn display_dimacs() {
+        let mut formula = cnf_formula![
+            1, -2;
+            3;
+        ];
+        formula.add_clause(&[]);
+        formula.set_var_count(4);
+
+        assert_eq!(formula.to_string(), "p cnf 4 3\n1 -2 0\n3 0\n0\n");
+    }
+
+    #[test]
+    fn with_capacity() {
+        let clauses = cnf![
+            1, 2, 3;
+            -1, -2;
+            7, 2;
+        ];
+
+        let mut formula = CnfFormula::with_capacity(7, 3, 7);
+        assert_eq!(formula.var_count(), 7);
+
+        let literals_ptr = formula.literals.as_ptr();
+        let clause_ranges_ptr = formula.clause_ranges.as_ptr();
+
+        for clause in clauses.iter() {
+            formula.add_clause(clause);
+        }
+
+        assert_eq!(formula.literals.as_ptr(), literals_ptr);
+        assert_eq!(formula.clause_ranges.as_ptr(), clause_ranges_ptr);
+
+        formula.reserve(10, 100);
+        assert!(formula.literals.capacity() >= 107);
+        assert!(formula.clause_ranges.capacity() >= 13);
+
+        formula.shrink_to_fit();
+        assert_eq!(formula.literals.capacity(), formula.literals.len());
+        assert_eq!(
+            formula.clause_ranges.capacity(),
+            formula.clause_ranges.len()
+        );
+        assert_eq!(formula, CnfFormula::from(clauses.iter().cloned()));
+    }
+
+    #[test]
+    fn clause_len_stats() {
+        let formula = CnfFormula::new();
+        assert_eq!(formula.clause_len_stats(), (0, 0, 0.0));
+        assert!(formula.is_cnf_k(0));
+
+        let formula = cnf_formula![
+            1, 2;
+            -1, 3;
+            2, -3;
+        ];
+        assert_eq!(formula.clause_len_stats(), (2, 2, 2.0));
+        assert!(formula.is_cnf_k(2));
+        assert!(!formula.is_cnf_k(1));
+
+        let formula = cnf_formula![
+            1, 2, 3;
+            -1;
+            2, -3, 4, 5;
+            ;
+        ];
+        assert_eq!(formula.clause_len_stats(), (0, 4, 2.0));
+        assert!(formula.is_cnf_k(4));
+        assert!(!formula.is_cnf_k(3));
+    }
+
+    #[test]
+    fn equivalent_as_sets() {
+        let formula = cnf_formula![
+            1, 2, 3;
+            -1, -2;
+            -1, -2;
+        ];
+
+        assert!(formula.equivalent_as_sets(&cnf_formula![
+            -2, -1, -1;
+            3, 1, 2;
+            -1, -2;
+        ]));
+        assert!(!formula.equivalent_as_sets(&cnf_formula![
+            1, 2, 3;
+            -1, -2;
+            1, 2, 3;
+        ]));
+        assert!(!formula.equivalent_as_sets(&cnf_formula![
+            1, 2, 3;
+            -1, -2;
+        ]));
+    }
+
     #[test]
     fn simple_roundtrip() {
         let input = cnf![
@@ -317,6 +681,151 @@ mod tests {
         assert_eq!(formula.var_count(), 7);
     }
 
+    #[test]
+    fn occurring_vars() {
+        let mut formula = CnfFormula::from(
+            cnf![
+                1, -4;
+                -7, 4;
+                ;
+                9;
+            ]
+            .iter()
+            .cloned(),
+        );
+        formula.set_var_count(12);
+
+        assert_eq!(
+            formula.occurring_vars().collect::<Vec<_>>(),
+            vec![
+                Var::from_dimacs(1),
+                Var::from_dimacs(4),
+                Var::from_dimacs(7),
+                Var::from_dimacs(9)
+            ]
+        );
+        assert!(!formula.is_dense());
+
+        let dense = CnfFormula::from(cnf![1, -2; 2, 3;].iter().cloned());
+
+        assert_eq!(dense.occurring_vars().count(), 3);
+        assert!(dense.is_dense());
+        assert!(CnfFormula::new().is_dense());
+    }
+
+    #[test]
+    fn compact() {
+        let formula = CnfFormula::from(
+            cnf![
+                3, -7;
+                -9, 3;
+                9;
+            ]
+            .iter()
+            .cloned(),
+        );
+
+        let (compact, original_vars) = formula.compact();
+
+        assert_eq!(
+            compact,
+            CnfFormula::from(cnf![1, -2; -3, 1; 3;].iter().cloned())
+        );
+        assert_eq!(
+            original_vars,
+            vec![
+                Var::from_dimacs(3),
+                Var::from_dimacs(7),
+                Var::from_dimacs(9)
+            ]
+        );
+    }
+
+    #[test]
+    fn normalized() {
+        let formula = CnfFormula::from(
+            cnf![
+                3, 1, 3;
+                -2, 4, 2;
+                1, 3;
+                ;
+                5, -1;
+                ;
+            ]
+            .iter()
+            .cloned(),
+        );
+
+        assert_eq!(
+            formula.normalized(),
+            CnfFormula::from(cnf![1, 3; ; -1, 5;].iter().cloned())
+        );
+    }
+
+    #[test]
+    fn normalized_tautologies() {
+        let formula = CnfFormula::from(cnf![1, -1; 2, 3, -2; -4, 4;].iter().cloned());
+
+        let normalized = formula.normalized();
+
+        assert!(normalized.is_empty());
+        assert_eq!(normalized.var_count(), 4);
+    }
+
+    #[test]
+    fn remove_clause() {
+        let clauses = cnf![
+            1, 2, 3;
+            -1, -2;
+            7, 2;
+            4, 5;
+        ];
+
+        for index in 0..clauses.len() {
+            let mut formula = CnfFormula::from(clauses.iter().cloned());
+            formula.remove_clause(index);
+
+            let mut expected_clauses = clauses.to_vec();
+            expected_clauses.remove(index);
+
+            let mut expected = CnfFormula::from(expected_clauses);
+            expected.set_var_count(7);
+
+            assert_eq!(formula, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "while a clause started by begin_clause is pending")]
+    fn add_clause_while_pending() {
+        let mut formula = CnfFormula::new();
+        formula.begin_clause();
+        formula.push_lit(Lit::from_dimacs(1));
+        formula.add_clause(&[Lit::from_dimacs(2)]);
+    }
+
+    #[test]
+    fn remove_clauses_where() {
+        let mut formula = CnfFormula::from(
+            cnf![
+                1, 2, 3;
+                -1, -2;
+                7, 2;
+                4, 5;
+            ]
+            .iter()
+            .cloned(),
+        );
+
+        formula.remove_clauses_where(|clause| clause.contains(&Lit::from_dimacs(2)));
+
+        let mut expected = CnfFormula::from(cnf![-1, -2; 4, 5;].iter().cloned());
+        expected.set_var_count(7);
+
+        assert_eq!(formula, expected);
+    }
+
     proptest! {
         #[test]
         fn roundtrip_from_vec(input in vec_formula(1..200usize, 0..1000, 0..10)) {
@@ -335,6 +844,36 @@ mod tests {
             prop_assert_eq!(formula.var_count(), var_count);
         }
 
+        #[test]
+        fn from_vecs(input in vec_formula(1..200usize, 0..1000, 0..10)) {
+            let formula = CnfFormula::from_vecs(input.clone());
+
+            prop_assert_eq!(formula.literals.capacity(), formula.literals.len());
+            prop_assert_eq!(formula, CnfFormula::from(input));
+        }
+
+        #[test]
+        fn incremental_clauses(input in vec_formula(1..200usize, 0..1000, 0..10)) {
+            let mut formula = CnfFormula::new();
+
+            for clause in input.iter() {
+                formula.begin_clause();
+                for &lit in clause.iter() {
+                    formula.push_lit(lit);
+                }
+                formula.end_clause();
+
+                formula.begin_clause();
+                for &lit in clause.iter() {
+                    formula.push_lit(lit);
+                }
+            }
+
+            formula.begin_clause();
+
+            prop_assert_eq!(formula, CnfFormula::from(input));
+        }
+
         #[test]
         fn roundtrip_from_cnf(input in cnf_formula(1..100usize, 0..1000, 0..10)) {
             let roundtrip = CnfFormula::from(input.iter());
@@ -349,5 +888,54 @@ mod tests {
                 prop_assert_eq!(roundtrip, input);
             }
         }
+
+        #[test]
+        fn extend_formula(
+            a in cnf_formula(1..100usize, 0..500, 0..10),
+            b in cnf_formula(1..100usize, 0..500, 0..10),
+        ) {
+            let mut extended = a.clone();
+            extended.extend_formula(&b);
+
+            let mut expected = a.clone();
+            for clause in b.iter() {
+                expected.add_clause(clause);
+            }
+            expected.set_var_count(b.var_count());
+
+            prop_assert_eq!(extended, expected);
+        }
+
+        #[test]
+        fn equivalent_as_sets_permuted(
+            (formula, permuted) in cnf_formula(1..100usize, 0..500, 0..10).prop_perturb(
+                |formula, mut rng| {
+                    let mut clauses: Vec<Vec<Lit>> =
+                        formula.iter().map(|clause| clause.to_vec()).collect();
+                    for clause in clauses.iter_mut() {
+                        clause.shuffle(&mut rng);
+                    }
+                    clauses.shuffle(&mut rng);
+                    (formula, CnfFormula::from(clauses))
+                }
+            ),
+        ) {
+            prop_assert!(formula.equivalent_as_sets(&permuted));
+            prop_assert!(permuted.equivalent_as_sets(&formula));
+
+            let mut extended = permuted.clone();
+            extended.add_clause(&[]);
+            prop_assert!(!formula.equivalent_as_sets(&extended));
+
+            if !permuted.is_empty() {
+                let mut removed = permuted.clone();
+                removed.remove_clause(0);
+                removed.add_clause(&[]);
+                prop_assert_eq!(
+                    formula.equivalent_as_sets(&removed),
+                    permuted.iter().next().unwrap().is_empty()
+                );
+            }
+        }
     }
 }
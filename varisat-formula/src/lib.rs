@@ -1,4 +1,11 @@
 //! Basic formula data types used by the Varisat SAT solver.
+//!
+//! This crate only requires `alloc`. The default `std` feature adds implementations of
+//! `std::error::Error`. Without it the crate is `no_std`, e.g. for embedded targets. The
+//! `proptest-strategies` and `internal-testing` features require `std`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 /// Shortcut for tests
 #[cfg(any(test, feature = "internal-testing"))]
@@ -57,8 +64,11 @@ macro_rules! cnf_formula {
 pub mod cnf;
 pub mod lit;
 
+#[cfg(feature = "serde")]
+mod serialize;
+
 #[cfg(any(test, feature = "internal-testing"))]
 pub mod test;
 
 pub use cnf::{CnfFormula, ExtendFormula};
-pub use lit::{Lit, Var};
+pub use lit::{Lit, ParseLitError, Var};
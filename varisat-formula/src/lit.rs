@@ -1,5 +1,5 @@
 //! Literals and variables.
-use std::{fmt, ops};
+use core::{fmt, ops, str::FromStr};
 
 /// The backing type used to represent literals and variables.
 pub type LitIdx = u32;
@@ -39,6 +39,22 @@ impl Var {
         }
     }
 
+    /// Iterates over `count` consecutive variables starting at the 0-based index `start`.
+    ///
+    /// Panics if the range contains a variable past `Var::max_var()`.
+    pub fn iter_range(
+        start: usize,
+        count: usize,
+    ) -> impl DoubleEndedIterator<Item = Var> + ExactSizeIterator {
+        let end = start.checked_add(count);
+        assert!(
+            end.is_some_and(|end| end <= Var::max_count()),
+            "variable range exceeds the maximum supported variable count of {}",
+            Var::max_count()
+        );
+        (start..start + count).map(Var::from_index)
+    }
+
     /// The 1-based index representing this variable in the DIMACS CNF encoding.
     #[inline]
     pub fn to_dimacs(self) -> isize {
@@ -58,7 +74,7 @@ impl Var {
     pub const fn max_var() -> Var {
         // Allow for sign or tag bits
         Var {
-            index: LitIdx::max_value() >> 4,
+            index: LitIdx::MAX >> 4,
         }
     }
 
@@ -145,6 +161,17 @@ impl Lit {
         Lit::from_var(var, false)
     }
 
+    /// Iterates over the positive literals of `count` consecutive variables starting at the 0-based
+    /// index `start`.
+    ///
+    /// See [`Var::iter_range`].
+    pub fn iter_positive_range(
+        start: usize,
+        count: usize,
+    ) -> impl DoubleEndedIterator<Item = Lit> + ExactSizeIterator {
+        Var::iter_range(start, count).map(Lit::positive)
+    }
+
     /// Create a literal from a variable index and a `bool` that is `true` when the literal is
     /// positive.
     #[inline]
@@ -222,6 +249,22 @@ impl Lit {
         self.code as usize
     }
 
+    /// Negates the literal if `negate` is true.
+    ///
+    /// This is the same as `lit ^ negate`.
+    #[inline]
+    pub fn negate_if(self, negate: bool) -> Lit {
+        self ^ negate
+    }
+
+    /// The literal of the same variable with the given polarity.
+    ///
+    /// Returns the positive literal when `positive` is true and the negative literal otherwise.
+    #[inline]
+    pub fn with_polarity(self, positive: bool) -> Lit {
+        self.var().lit(positive)
+    }
+
     /// Apply a function to the variable of the literal, without changing the polarity.
     #[inline]
     pub fn map_var(self, f: impl FnOnce(Var) -> Var) -> Lit {
@@ -272,6 +315,74 @@ impl fmt::Display for Lit {
     }
 }
 
+/// Error returned when parsing a [`Var`] or [`Lit`] from a string fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseLitError {
+    /// The string is not an integer.
+    InvalidNumber,
+    /// The integer is zero, which does not represent a variable or literal.
+    Zero,
+    /// A negative integer was given for a variable.
+    NegativeVar,
+    /// The represented variable is past `Var::max_var()`.
+    OutOfRange,
+}
+
+impl fmt::Display for ParseLitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseLitError::InvalidNumber => write!(f, "invalid integer"),
+            ParseLitError::Zero => write!(f, "zero does not represent a variable or literal"),
+            ParseLitError::NegativeVar => write!(f, "variables cannot be negative"),
+            ParseLitError::OutOfRange => write!(f, "variable index too large"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseLitError {}
+
+/// Parses a DIMACS integer into its 1-based variable index and polarity.
+fn parse_dimacs(s: &str) -> Result<(usize, bool), ParseLitError> {
+    let (digits, positive) = match s.strip_prefix('-') {
+        Some(digits) => (digits, false),
+        None => (s, true),
+    };
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(ParseLitError::InvalidNumber);
+    }
+    let number: usize = digits.parse().map_err(|_| ParseLitError::OutOfRange)?;
+    if number == 0 {
+        Err(ParseLitError::Zero)
+    } else if number > Var::max_count() {
+        Err(ParseLitError::OutOfRange)
+    } else {
+        Ok((number, positive))
+    }
+}
+
+/// Parses the 1-based DIMACS CNF encoding.
+impl FromStr for Var {
+    type Err = ParseLitError;
+
+    fn from_str(s: &str) -> Result<Var, ParseLitError> {
+        match parse_dimacs(s)? {
+            (number, true) => Ok(Var::from_index(number - 1)),
+            (_, false) => Err(ParseLitError::NegativeVar),
+        }
+    }
+}
+
+/// Parses the 1-based DIMACS CNF encoding.
+impl FromStr for Lit {
+    type Err = ParseLitError;
+
+    fn from_str(s: &str) -> Result<Lit, ParseLitError> {
+        let (number, positive) = parse_dimacs(s)?;
+        Ok(Var::from_index(number - 1).lit(positive))
+    }
+}
+
 #[cfg(any(test, feature = "proptest-strategies"))]
 #[doc(hidden)]
 pub mod strategy {
@@ -286,3 +397,80 @@ pub mod strategy {
         (var(index), bool::ANY).prop_map(|(var, polarity)| var.lit(polarity))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_var() {
+        assert_eq!("3".parse::<Var>(), Ok(Var::from_dimacs(3)));
+        assert_eq!("0".parse::<Var>(), Err(ParseLitError::Zero));
+        assert_eq!("-3".parse::<Var>(), Err(ParseLitError::NegativeVar));
+        assert_eq!("x".parse::<Var>(), Err(ParseLitError::InvalidNumber));
+        assert_eq!("+3".parse::<Var>(), Err(ParseLitError::InvalidNumber));
+        assert_eq!(
+            Var::max_var().to_string().parse::<Var>(),
+            Ok(Var::max_var())
+        );
+        assert_eq!(
+            (Var::max_count() + 1).to_string().parse::<Var>(),
+            Err(ParseLitError::OutOfRange)
+        );
+        assert_eq!(
+            "99999999999999999999999".parse::<Var>(),
+            Err(ParseLitError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn var_range() {
+        let vars: Vec<Var> = Var::iter_range(2, 3).collect();
+        assert_eq!(
+            vars,
+            vec![Var::from_index(2), Var::from_index(3), Var::from_index(4)]
+        );
+        assert_eq!(Var::iter_range(5, 0).count(), 0);
+
+        let lits: Vec<Lit> = Lit::iter_positive_range(0, 2).rev().collect();
+        assert_eq!(lits, vec![Lit::from_dimacs(2), Lit::from_dimacs(1)]);
+
+        let last = Var::iter_range(Var::max_count() - 1, 1).last();
+        assert_eq!(last, Some(Var::max_var()));
+        assert_eq!(Var::iter_range(Var::max_count(), 0).len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "variable range exceeds the maximum supported variable count")]
+    fn var_range_past_max() {
+        let _ = Var::iter_range(Var::max_count() - 1, 2);
+    }
+
+    #[test]
+    fn polarity() {
+        let lit = Lit::from_dimacs(3);
+
+        assert_eq!(lit.negate_if(false), lit);
+        assert_eq!(lit.negate_if(true), !lit);
+        assert_eq!((!lit).negate_if(true), lit);
+
+        assert_eq!(lit.with_polarity(true), lit);
+        assert_eq!(lit.with_polarity(false), !lit);
+        assert_eq!((!lit).with_polarity(true), lit);
+        assert_eq!((!lit).with_polarity(false), !lit);
+    }
+
+    #[test]
+    fn parse_lit() {
+        assert_eq!("-3".parse::<Lit>(), Ok(Lit::from_dimacs(-3)));
+        assert_eq!("3".parse::<Lit>(), Ok(Lit::from_dimacs(3)));
+        assert_eq!("0".parse::<Lit>(), Err(ParseLitError::Zero));
+        assert_eq!("-0".parse::<Lit>(), Err(ParseLitError::Zero));
+        assert_eq!("-".parse::<Lit>(), Err(ParseLitError::InvalidNumber));
+        assert_eq!("".parse::<Lit>(), Err(ParseLitError::InvalidNumber));
+        assert_eq!(
+            format!("-{}", Var::max_count() + 1).parse::<Lit>(),
+            Err(ParseLitError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Serde support for formula data types.
+//!
+//! Variables and literals are serialized using their DIMACS integer representation.
+use alloc::{format, vec::Vec};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    cnf::{CnfFormula, ExtendFormula},
+    lit::{Lit, Var},
+};
+
+impl Serialize for Var {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.to_dimacs() as i64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Var {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Var, D::Error> {
+        let number = i64::deserialize(deserializer)?;
+        if number < 1 || number > Var::max_var().to_dimacs() as i64 {
+            return Err(de::Error::custom(format!(
+                "variable {} out of range",
+                number
+            )));
+        }
+        Ok(Var::from_dimacs(number as isize))
+    }
+}
+
+impl Serialize for Lit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.to_dimacs() as i64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Lit, D::Error> {
+        let number = i64::deserialize(deserializer)?;
+        if number == 0 || number.unsigned_abs() > Var::max_var().to_dimacs() as u64 {
+            return Err(de::Error::custom(format!(
+                "literal {} out of range",
+                number
+            )));
+        }
+        Ok(Lit::from_dimacs(number as isize))
+    }
+}
+
+/// Serialized representation of a [`CnfFormula`].
+#[derive(Serialize)]
+struct SerializeCnfFormula<'a> {
+    var_count: usize,
+    clauses: Vec<&'a [Lit]>,
+}
+
+/// Deserialized representation of a [`CnfFormula`].
+#[derive(Deserialize)]
+struct DeserializeCnfFormula {
+    var_count: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl Serialize for CnfFormula {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializeCnfFormula {
+            var_count: self.var_count(),
+            clauses: self.iter().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CnfFormula {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CnfFormula, D::Error> {
+        let serialized = DeserializeCnfFormula::deserialize(deserializer)?;
+        if serialized.var_count > Var::max_count() {
+            return Err(de::Error::custom(format!(
+                "variable count {} out of range",
+                serialized.var_count
+            )));
+        }
+        let mut formula = CnfFormula::new();
+        for clause in serialized.clauses.iter() {
+            formula.add_clause(clause);
+        }
+        formula.set_var_count(serialized.var_count);
+        Ok(formula)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use crate::cnf::strategy::cnf_formula;
+
+    #[test]
+    fn lit_as_dimacs() {
+        assert_eq!(serde_json::to_string(&lit!(-3)).unwrap(), "-3");
+        assert_eq!(serde_json::to_string(&var!(5)).unwrap(), "5");
+        assert_eq!(serde_json::from_str::<Lit>("7").unwrap(), lit!(7));
+        assert!(serde_json::from_str::<Lit>("0").is_err());
+        assert!(serde_json::from_str::<Var>("-1").is_err());
+    }
+
+    #[test]
+    fn formula_format() {
+        let mut formula = cnf_formula![
+            1, -2;
+            3;
+        ];
+        formula.set_var_count(5);
+
+        assert_eq!(
+            serde_json::to_string(&formula).unwrap(),
+            r#"{"var_count":5,"clauses":[[1,-2],[3]]}"#
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in cnf_formula(1..100usize, 0..100, 0..10)) {
+            let json = serde_json::to_string(&input).unwrap();
+            let roundtrip: CnfFormula = serde_json::from_str(&json).unwrap();
+
+            prop_assert_eq!(roundtrip, input);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse_quote, punctuated::Punctuated, Attribute, Fields, Ident, Lit, LitStr, Meta,
-    MetaNameValue, Token,
+    MetaNameValue, Token, Type,
 };
 use synstructure::decl_derive;
 
@@ -47,8 +47,20 @@ fn get_doc_field(name: &str, attrs: &[Attribute]) -> Option<LitStr> {
     None
 }
 
+/// Whether a type is an `Option`.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
 /// Derives a default instance from the documentation.
-fn derive_doc_default(s: synstructure::Structure) -> TokenStream {
+fn derive_doc_default(mut s: synstructure::Structure) -> TokenStream {
     let variant = match s.variants() {
         [variant] => variant,
         _ => panic!("DocDefault requires a struct"),
@@ -64,7 +76,7 @@ fn derive_doc_default(s: synstructure::Structure) -> TokenStream {
             .unwrap_or_else(|| parse_quote!(Default::default()))
     });
 
-    s.gen_impl(quote! {
+    s.underscore_const(true).gen_impl(quote! {
         gen impl Default for @Self {
             fn default() -> Self {
                 #body
@@ -121,9 +133,18 @@ fn derive_config_update(s: synstructure::Structure) -> TokenStream {
                 let range = range
                     .parse::<TokenStream>()
                     .expect("error parsing range expression");
-                quote! {
-                    if let Some(value) = &self.#ident {
-                        anyhow::ensure!((#range).contains(value), #error_msg, value);
+                // The range of an optional value only applies when a value is present.
+                if is_option(&field.ty) {
+                    quote! {
+                        if let Some(Some(value)) = &self.#ident {
+                            anyhow::ensure!((#range).contains(value), #error_msg, value);
+                        }
+                    }
+                } else {
+                    quote! {
+                        if let Some(value) = &self.#ident {
+                            anyhow::ensure!((#range).contains(value), #error_msg, value);
+                        }
                     }
                 }
             } else {
@@ -175,7 +196,7 @@ fn derive_config_update(s: synstructure::Structure) -> TokenStream {
 
     quote! {
         #[doc = #doc]
-        #[derive(Default, serde::Serialize, serde::Deserialize)]
+        #[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
         #[serde(deny_unknown_fields)]
         #vis struct #update_struct_ident {
             #update_struct_body
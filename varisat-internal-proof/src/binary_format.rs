@@ -168,32 +168,32 @@ impl Parser {
         let code = read_u64(&mut *source)?;
         match code {
             CODE_SOLVER_VAR_NAME_UPDATE => {
-                let global = Var::from_index(read_u64(&mut *source)? as usize);
-                let solver = Some(Var::from_index(read_u64(&mut *source)? as usize));
+                let global = read_var(&mut *source)?;
+                let solver = Some(read_var(&mut *source)?);
                 Ok(ProofStep::SolverVarName { global, solver })
             }
             CODE_SOLVER_VAR_NAME_REMOVE => {
-                let global = Var::from_index(read_u64(&mut *source)? as usize);
+                let global = read_var(&mut *source)?;
                 Ok(ProofStep::SolverVarName {
                     global,
                     solver: None,
                 })
             }
             CODE_USER_VAR_NAME_UPDATE => {
-                let global = Var::from_index(read_u64(&mut *source)? as usize);
-                let user = Some(Var::from_index(read_u64(&mut *source)? as usize));
+                let global = read_var(&mut *source)?;
+                let user = Some(read_var(&mut *source)?);
                 Ok(ProofStep::UserVarName { global, user })
             }
             CODE_USER_VAR_NAME_REMOVE => {
-                let global = Var::from_index(read_u64(&mut *source)? as usize);
+                let global = read_var(&mut *source)?;
                 Ok(ProofStep::UserVarName { global, user: None })
             }
             CODE_DELETE_VAR => {
-                let var = Var::from_index(read_u64(&mut *source)? as usize);
+                let var = read_var(&mut *source)?;
                 Ok(ProofStep::DeleteVar { var })
             }
             CODE_CHANGE_SAMPLING_MODE_SAMPLE | CODE_CHANGE_SAMPLING_MODE_WITNESS => {
-                let var = Var::from_index(read_u64(&mut *source)? as usize);
+                let var = read_var(&mut *source)?;
                 Ok(ProofStep::ChangeSamplingMode {
                     var,
                     sample: code == CODE_CHANGE_SAMPLING_MODE_SAMPLE,
@@ -260,11 +260,100 @@ impl Parser {
                 })
             }
             CODE_END => Ok(ProofStep::End),
-            _ => anyhow::bail!("parse error"),
+            _ => anyhow::bail!("unknown step code {}", code),
         }
     }
 }
 
+/// Parse a varisat proof, calling the given function for each step.
+///
+/// Parsing stops at the end marker, which is not passed to the function, or when the function
+/// returns an error. A proof without end marker results in an error after all complete steps were
+/// passed to the function.
+pub fn for_each_step(
+    input: impl io::Read,
+    mut f: impl FnMut(&ProofStep) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut source = io::BufReader::new(input);
+    let mut parser = Parser::default();
+
+    loop {
+        match parser.parse_step(&mut source)? {
+            ProofStep::End => return Ok(()),
+            step => f(&step)?,
+        }
+    }
+}
+
+/// Structure of a varisat proof as determined by [`validate_proof_structure`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ProofSummary {
+    /// Number of complete steps, excluding the end marker.
+    pub step_count: u64,
+    /// Whether the proof ends with the end marker.
+    ///
+    /// This is false for truncated proofs, including proofs truncated within a step.
+    pub complete: bool,
+}
+
+/// Scan the steps of a varisat proof without checking them.
+///
+/// This only verifies that the proof consists of a sequence of well-formed steps, optionally
+/// terminated by the end marker. It is much faster than checking the proof and helps to
+/// distinguish truncated proofs from invalid proofs. Unknown step codes and data after the end
+/// marker are reported as errors.
+pub fn validate_proof_structure(input: impl io::Read) -> Result<ProofSummary, Error> {
+    let mut source = io::BufReader::new(input);
+    let mut parser = Parser::default();
+    let mut step_count = 0;
+
+    loop {
+        if source.fill_buf()?.is_empty() {
+            break;
+        }
+
+        match parser.parse_step(&mut source) {
+            Ok(ProofStep::End) => {
+                if !source.fill_buf()?.is_empty() {
+                    anyhow::bail!("unexpected data after end of proof at step {}", step_count);
+                }
+                return Ok(ProofSummary {
+                    step_count,
+                    complete: true,
+                });
+            }
+            Ok(_) => step_count += 1,
+            Err(err) => match err.downcast_ref::<io::Error>() {
+                Some(io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => break,
+                _ => return Err(err.context(format!("invalid proof step {}", step_count))),
+            },
+        }
+    }
+
+    Ok(ProofSummary {
+        step_count,
+        complete: false,
+    })
+}
+
+/// Read a variable index, checking that it is supported.
+fn read_var(source: &mut impl BufRead) -> Result<Var, Error> {
+    let index = read_u64(&mut *source)?;
+    if index >= Var::max_count() as u64 {
+        anyhow::bail!("variable index {} out of range", index);
+    }
+    Ok(Var::from_index(index as usize))
+}
+
+/// Read a literal code, checking that its variable is supported.
+fn read_lit(source: &mut impl BufRead) -> Result<Lit, Error> {
+    let code = read_u64(&mut *source)?;
+    if code >= Var::max_count() as u64 * 2 {
+        anyhow::bail!("literal code {} out of range", code);
+    }
+    Ok(Lit::from_code(code as usize))
+}
+
 /// Writes a slice of literals for a varisat proof
 fn write_literals(target: &mut impl Write, literals: &[Lit]) -> io::Result<()> {
     write_u64(&mut *target, literals.len() as u64)?;
@@ -275,12 +364,14 @@ fn write_literals(target: &mut impl Write, literals: &[Lit]) -> io::Result<()> {
 }
 
 /// Read a slice of literals from a varisat proof
-fn read_literals(source: &mut impl BufRead, literals: &mut Vec<Lit>) -> Result<(), io::Error> {
+///
+/// A corrupted length can be arbitrarily large, so no space is reserved up front. Reading stops at
+/// the end of the input and the buffers of the parser are reused between steps.
+fn read_literals(source: &mut impl BufRead, literals: &mut Vec<Lit>) -> Result<(), Error> {
     literals.clear();
-    let len = read_u64(&mut *source)? as usize;
-    literals.reserve(len);
+    let len = read_u64(&mut *source)?;
     for _ in 0..len {
-        literals.push(Lit::from_code(read_u64(&mut *source)? as usize));
+        literals.push(read_lit(&mut *source)?);
     }
     Ok(())
 }
@@ -289,7 +380,7 @@ fn read_literals(source: &mut impl BufRead, literals: &mut Vec<Lit>) -> Result<(
 fn write_hashes(target: &mut impl Write, hashes: &[ClauseHash]) -> io::Result<()> {
     write_u64(&mut *target, hashes.len() as u64)?;
     for &hash in hashes {
-        write_u64(&mut *target, hash as u64)?;
+        write_u64(&mut *target, hash)?;
     }
     Ok(())
 }
@@ -297,8 +388,7 @@ fn write_hashes(target: &mut impl Write, hashes: &[ClauseHash]) -> io::Result<()
 /// Read a slice of clause hashes from a varisat proof
 fn read_hashes(source: &mut impl BufRead, hashes: &mut Vec<ClauseHash>) -> Result<(), io::Error> {
     hashes.clear();
-    let len = read_u64(&mut *source)? as usize;
-    hashes.reserve(len);
+    let len = read_u64(&mut *source)?;
     for _ in 0..len {
         hashes.push(read_u64(&mut *source)? as ClauseHash);
     }
@@ -310,7 +400,7 @@ fn write_unit_clauses(target: &mut impl Write, units: &[(Lit, ClauseHash)]) -> i
     write_u64(&mut *target, units.len() as u64)?;
     for &(lit, hash) in units {
         write_u64(&mut *target, lit.code() as u64)?;
-        write_u64(&mut *target, hash as u64)?;
+        write_u64(&mut *target, hash)?;
     }
     Ok(())
 }
@@ -319,14 +409,115 @@ fn write_unit_clauses(target: &mut impl Write, units: &[(Lit, ClauseHash)]) -> i
 fn read_unit_clauses(
     source: &mut impl BufRead,
     units: &mut Vec<(Lit, ClauseHash)>,
-) -> Result<(), io::Error> {
+) -> Result<(), Error> {
     units.clear();
-    let len = read_u64(&mut *source)? as usize;
-    units.reserve(len);
+    let len = read_u64(&mut *source)?;
     for _ in 0..len {
-        let lit = Lit::from_code(read_u64(&mut *source)? as usize);
+        let lit = read_lit(&mut *source)?;
         let hash = read_u64(&mut *source)? as ClauseHash;
         units.push((lit, hash));
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_proof() -> Vec<u8> {
+        let lits = |lits: &[isize]| -> Vec<Lit> {
+            lits.iter().map(|&lit| Lit::from_dimacs(lit)).collect()
+        };
+        let mut proof = vec![];
+        let steps = [
+            ProofStep::AddClause {
+                clause: &lits(&[1, 2, 3]),
+            },
+            ProofStep::ChangeHashBits { bits: 20 },
+            ProofStep::AtClause {
+                redundant: true,
+                clause: &lits(&[1, 2]),
+                propagation_hashes: &[1, 2, 3],
+            },
+            ProofStep::UnitClauses {
+                units: &[(Lit::from_dimacs(1), 4)],
+            },
+            ProofStep::DeleteClause {
+                clause: &lits(&[1, 2, 3]),
+                proof: DeleteClauseProof::Simplified,
+            },
+            ProofStep::End,
+        ];
+        for step in steps.iter() {
+            write_step(&mut proof, step).unwrap();
+        }
+        proof
+    }
+
+    #[test]
+    fn visit_steps() {
+        let proof = example_proof();
+        let mut steps = vec![];
+        for_each_step(&proof[..], |step| {
+            steps.push(format!("{:?}", step));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(steps.len(), 5);
+        assert!(steps[1].starts_with("ChangeHashBits"));
+
+        assert!(for_each_step(&proof[..proof.len() - 1], |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn well_formed_proof() {
+        let proof = example_proof();
+        assert_eq!(
+            validate_proof_structure(&proof[..]).unwrap(),
+            ProofSummary {
+                step_count: 5,
+                complete: true,
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_proof() {
+        let proof = example_proof();
+        let mut last_step_count = 0;
+        for len in 0..proof.len() {
+            let summary = validate_proof_structure(&proof[..len]).unwrap();
+            assert!(!summary.complete);
+            assert!(summary.step_count >= last_step_count);
+            last_step_count = summary.step_count;
+        }
+        assert_eq!(last_step_count, 5);
+
+        let mut prefix = vec![];
+        write_step(&mut prefix, &ProofStep::ChangeHashBits { bits: 20 }).unwrap();
+        assert_eq!(
+            validate_proof_structure(&prefix[..]).unwrap(),
+            ProofSummary {
+                step_count: 1,
+                complete: false,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_proof() {
+        let mut proof = example_proof();
+        proof.push(0x80);
+        assert!(validate_proof_structure(&proof[..]).is_err());
+
+        let mut proof = vec![];
+        write_u64(&mut proof, 1000).unwrap();
+        assert!(validate_proof_structure(&proof[..]).is_err());
+
+        let mut proof = vec![];
+        write_u64(&mut proof, CODE_ADD_CLAUSE).unwrap();
+        write_u64(&mut proof, 1).unwrap();
+        write_u64(&mut proof, u64::MAX).unwrap();
+        assert!(validate_proof_structure(&proof[..]).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Parser for proofs in the DRAT format.
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+};
+
+use anyhow::{anyhow, Error};
+
+use varisat_formula::{Lit, Var};
+
+/// A single step of a DRAT proof.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DratStep<'a> {
+    /// Whether the clause is added, otherwise it is deleted.
+    pub add: bool,
+    pub clause: &'a [Lit],
+}
+
+/// Parser for DRAT proofs in the text or binary format.
+///
+/// The binary format is detected by looking at the first bytes of the proof, so a parser must only
+/// be used for a single proof.
+#[derive(Default)]
+pub struct DratParser {
+    lit_buf: Vec<Lit>,
+    /// Bytes read for format detection but not yet parsed.
+    pending: VecDeque<u8>,
+    /// Whether the proof uses the binary format, `None` before the first step.
+    binary: Option<bool>,
+}
+
+impl DratParser {
+    /// Parse the next step.
+    ///
+    /// Returns `None` at the end of the proof.
+    pub fn parse_step<'a>(
+        &'a mut self,
+        source: &mut impl BufRead,
+    ) -> Result<Option<DratStep<'a>>, Error> {
+        self.lit_buf.clear();
+
+        let binary = match self.binary {
+            Some(binary) => binary,
+            None => {
+                let binary = self.detect_binary(source)?;
+                self.binary = Some(binary);
+                binary
+            }
+        };
+
+        let add = if binary {
+            self.parse_binary_step(source)?
+        } else {
+            self.parse_text_step(source)?
+        };
+
+        Ok(add.map(move |add| DratStep {
+            add,
+            clause: &self.lit_buf,
+        }))
+    }
+
+    fn next_byte(&mut self, source: &mut impl BufRead) -> io::Result<Option<u8>> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Ok(Some(byte));
+        }
+        read_byte(source)
+    }
+
+    /// Detect whether the proof uses the binary format.
+    ///
+    /// Binary steps start with `a` or `d`. As a `d` can also start a text step, in that case the
+    /// input is read until it contains a byte that cannot occur in a text deletion step, which
+    /// includes the zero byte terminating every binary step, or until it contains the terminating
+    /// `0` of a text step.
+    fn detect_binary(&mut self, source: &mut impl BufRead) -> io::Result<bool> {
+        match read_byte(source)? {
+            Some(byte) => self.pending.push_back(byte),
+            None => return Ok(false),
+        }
+
+        match self.pending[0] {
+            b'a' => Ok(true),
+            b'd' => loop {
+                let byte = match read_byte(source)? {
+                    Some(byte) => byte,
+                    None => return Ok(false),
+                };
+                self.pending.push_back(byte);
+
+                if !(byte.is_ascii_digit() || byte == b'-' || byte.is_ascii_whitespace()) {
+                    return Ok(true);
+                }
+
+                let len = self.pending.len();
+                if byte.is_ascii_whitespace()
+                    && self.pending[len - 2] == b'0'
+                    && self.pending[len - 3].is_ascii_whitespace()
+                {
+                    return Ok(false);
+                }
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Parse a text step into `lit_buf`, returning whether it adds a clause.
+    fn parse_text_step(&mut self, source: &mut impl BufRead) -> Result<Option<bool>, Error> {
+        let mut add = None;
+        let mut number: Option<usize> = None;
+        let mut negative = false;
+        let mut start_of_line = true;
+
+        loop {
+            let byte = self.next_byte(source)?;
+
+            match byte {
+                Some(b'0'..=b'9') => {
+                    let digit = (byte.unwrap() - b'0') as usize;
+                    let value = number
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(digit))
+                        .filter(|&value| value <= Var::max_count())
+                        .ok_or_else(|| anyhow!("literal index is too large"))?;
+                    number = Some(value);
+                    start_of_line = false;
+                }
+                Some(b'-') if number.is_none() && !negative => {
+                    negative = true;
+                    start_of_line = false;
+                }
+                Some(b'd') if add.is_none() && number.is_none() && !negative => {
+                    add = Some(false);
+                    start_of_line = false;
+                }
+                Some(b'c') if start_of_line && add.is_none() && self.lit_buf.is_empty() => {
+                    while !matches!(self.next_byte(source)?, None | Some(b'\n')) {}
+                }
+                None | Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') => {
+                    match number.take() {
+                        Some(0) => return Ok(Some(add.unwrap_or(true))),
+                        Some(index) => {
+                            self.lit_buf
+                                .push(Var::from_dimacs(index as isize).lit(!negative));
+                            add = add.or(Some(true));
+                        }
+                        None if negative => return Err(anyhow!("expected literal after '-'")),
+                        None => (),
+                    }
+                    negative = false;
+
+                    if byte.is_none() {
+                        if add.is_some() {
+                            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                        }
+                        return Ok(None);
+                    }
+
+                    start_of_line = byte == Some(b'\n') || byte == Some(b'\r');
+                }
+                Some(byte) => return Err(anyhow!("unexpected character {:?}", byte as char)),
+            }
+        }
+    }
+
+    /// Parse a binary step into `lit_buf`, returning whether it adds a clause.
+    fn parse_binary_step(&mut self, source: &mut impl BufRead) -> Result<Option<bool>, Error> {
+        let add = match self.next_byte(source)? {
+            None => return Ok(None),
+            Some(b'a') => true,
+            Some(b'd') => false,
+            Some(byte) => return Err(anyhow!("unexpected binary step marker {:#x}", byte)),
+        };
+
+        loop {
+            let mut code: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let byte = self
+                    .next_byte(source)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                if shift >= 64 {
+                    return Err(anyhow!("literal encoding is too long"));
+                }
+                code |= ((byte & 0x7f) as u64) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+
+            match code {
+                0 => return Ok(Some(add)),
+                1 => return Err(anyhow!("invalid literal encoding")),
+                _ if code - 2 >= 2 * Var::max_count() as u64 => {
+                    return Err(anyhow!("literal index is too large"))
+                }
+                _ => self.lit_buf.push(Lit::from_code((code - 2) as usize)),
+            }
+        }
+    }
+}
+
+/// Read a single byte from a buffered reader.
+fn read_byte(source: &mut impl BufRead) -> io::Result<Option<u8>> {
+    let byte = loop {
+        match source.fill_buf() {
+            Ok(buf) => break buf.first().cloned(),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    };
+    if byte.is_some() {
+        source.consume(1);
+    }
+    Ok(byte)
+}
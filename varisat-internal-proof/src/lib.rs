@@ -2,6 +2,7 @@
 use varisat_formula::{Lit, Var};
 
 pub mod binary_format;
+pub mod drat;
 
 mod vli_enc;
 
@@ -24,17 +24,17 @@ use std::{
 
 /// Write an encoded 64 bit number.
 pub fn write_u64(target: &mut impl Write, mut value: u64) -> Result<(), io::Error> {
-    let bits = (64 - value.leading_zeros()) as u32;
+    let bits = 64 - value.leading_zeros();
     let blocks = (bits * (64 / 7)) / 64;
     if value < (1 << (8 * 7)) {
         value = ((value << 1) | 1) << blocks;
-        let bytes = unsafe { std::mem::transmute::<u64, [u8; 8]>(value.to_le()) };
+        let bytes = u64::to_ne_bytes(value.to_le());
         target.write_all(&bytes[..(blocks + 1) as usize])
     } else {
         let lo_data = ((value << 1) | 1) << blocks;
-        let lo_bytes = unsafe { std::mem::transmute::<u64, [u8; 8]>(lo_data.to_le()) };
+        let lo_bytes = u64::to_ne_bytes(lo_data.to_le());
         let hi_data = value >> (64 - (blocks + 1));
-        let hi_bytes = unsafe { std::mem::transmute::<u64, [u8; 8]>(hi_data.to_le()) };
+        let hi_bytes = u64::to_ne_bytes(hi_data.to_le());
 
         target.write_all(&lo_bytes)?;
         target.write_all(&hi_bytes[..(blocks as usize) + 1 - 8])
@@ -43,9 +43,9 @@ pub fn write_u64(target: &mut impl Write, mut value: u64) -> Result<(), io::Erro
 
 /// Read an encoded 64 bit number, if at least 16 bytes lookahead are available.
 fn read_u64_fast(bytes: &[u8; 16]) -> (u64, usize) {
-    let lo_data = u64::from_le(unsafe {
-        std::mem::transmute::<[u8; 8], u64>(*<&[u8; 8]>::try_from(&bytes[..8]).unwrap())
-    });
+    let lo_data = u64::from_le(u64::from_ne_bytes(
+        *<&[u8; 8]>::try_from(&bytes[..8]).unwrap(),
+    ));
 
     let len = (lo_data | (1 << 9)).trailing_zeros() + 1;
 
@@ -56,15 +56,15 @@ fn read_u64_fast(bytes: &[u8; 16]) -> (u64, usize) {
 
         (result, len as usize)
     } else {
-        let hi_data = u64::from_le(unsafe {
-            std::mem::transmute::<[u8; 8], u64>(*<&[u8; 8]>::try_from(&bytes[8..]).unwrap())
-        });
+        let hi_data = u64::from_le(u64::from_ne_bytes(
+            *<&[u8; 8]>::try_from(&bytes[8..]).unwrap(),
+        ));
 
         let hi_data = hi_data & (!0u64 >> (64 - 8 * (len - 8)));
 
         let result = (lo_data >> len) | (hi_data << (64 - len));
 
-        (result as u64, len as usize)
+        (result, len as usize)
     }
 }
 
@@ -101,7 +101,7 @@ pub fn read_u64(source: &mut impl BufRead) -> Result<u64, io::Error> {
             result |= (byte[0] as u64) << (8 * i - len);
         }
 
-        Ok(result as u64)
+        Ok(result)
     }
 }
 
@@ -0,0 +1,378 @@
+//! IPASIR C API for the Varisat SAT solver.
+//!
+//! This exports the functions of the [IPASIR](https://github.com/biotomas/ipasir) interface for
+//! incremental SAT solving as `extern "C"` functions. The crate is built as a dynamic library, so
+//! C programs and other tools written against the IPASIR header can link to it.
+//!
+//! Solver pointers passed to these functions have to be created by [`ipasir_init`] and must not be
+//! used after passing them to [`ipasir_release`].
+use std::{
+    os::raw::{c_char, c_int, c_void},
+    process::abort,
+};
+
+use varisat::{ExtendFormula, Lit, Solver, Var};
+
+/// Terminate callback as passed to [`ipasir_set_terminate`].
+pub type TerminateCallback = Option<extern "C" fn(data: *mut c_void) -> c_int>;
+
+/// Solver state behind an IPASIR solver pointer.
+#[derive(Default)]
+struct IpasirSolver {
+    solver: Solver<'static>,
+    /// Literals of the clause that is currently being added.
+    clause: Vec<Lit>,
+    /// Assumptions for the next call to solve.
+    assumptions: Vec<Lit>,
+    /// Value of each variable in the last found model, indexed by variable.
+    model: Vec<Option<bool>>,
+    /// Whether a literal is part of the last failed core, indexed by literal code.
+    failed: Vec<bool>,
+}
+
+impl IpasirSolver {
+    /// Solve under the current assumptions and record the model or failed core.
+    fn solve(&mut self) -> c_int {
+        self.model.clear();
+        self.failed.clear();
+
+        self.solver.assume(&self.assumptions);
+        self.assumptions.clear();
+
+        let result = self.solver.solve();
+
+        match result {
+            Ok(true) => {
+                for lit in self
+                    .solver
+                    .model()
+                    .expect("no model after satisfiable solve")
+                {
+                    let index = lit.index();
+                    if self.model.len() <= index {
+                        self.model.resize(index + 1, None);
+                    }
+                    self.model[index] = Some(lit.is_positive());
+                }
+            }
+            Ok(false) => {
+                for &lit in self
+                    .solver
+                    .failed_core()
+                    .expect("no core after unsatisfiable solve")
+                {
+                    let code = lit.code();
+                    if self.failed.len() <= code {
+                        self.failed.resize(code + 1, false);
+                    }
+                    self.failed[code] = true;
+                }
+            }
+            Err(_) => (),
+        }
+
+        self.solver.assume(&[]);
+
+        match result {
+            Ok(true) => 10,
+            Ok(false) => 20,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// Access the solver behind an IPASIR solver pointer.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+unsafe fn solver_from_ptr<'a>(solver: *mut c_void) -> &'a mut IpasirSolver {
+    &mut *(solver as *mut IpasirSolver)
+}
+
+/// Literal for an IPASIR literal.
+///
+/// Returns `None` for 0 and for literals of variables past `Var::max_var()`.
+fn lit_from_ipasir(lit: i32) -> Option<Lit> {
+    let var_count = (lit as i64).unsigned_abs();
+    if lit == 0 || var_count > Var::max_count() as u64 {
+        None
+    } else {
+        Some(Lit::from_dimacs(lit as isize))
+    }
+}
+
+/// Literal for an IPASIR literal that is added to the formula or assumed.
+///
+/// Aborts for invalid literals, as ignoring them would change the problem and panics must not
+/// unwind into the calling C code.
+fn required_lit_from_ipasir(function: &str, lit: i32) -> Lit {
+    lit_from_ipasir(lit).unwrap_or_else(|| {
+        eprintln!("{}: invalid literal {}", function, lit);
+        abort()
+    })
+}
+
+/// Name and version of the solver.
+#[no_mangle]
+pub extern "C" fn ipasir_signature() -> *const c_char {
+    concat!("varisat-", env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Create a new solver instance.
+///
+/// The returned pointer has to be passed to [`ipasir_release`] to free the solver.
+#[no_mangle]
+pub extern "C" fn ipasir_init() -> *mut c_void {
+    Box::into_raw(Box::new(IpasirSolver::default())) as *mut c_void
+}
+
+/// Free all resources of a solver instance.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_release(solver: *mut c_void) {
+    drop(Box::from_raw(solver as *mut IpasirSolver));
+}
+
+/// Add a literal to the current clause or finish the clause when `lit_or_zero` is zero.
+///
+/// Aborts the process if the variable of the literal is past `Var::max_var()`.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_add(solver: *mut c_void, lit_or_zero: i32) {
+    let solver = solver_from_ptr(solver);
+    if lit_or_zero == 0 {
+        solver.solver.add_clause(&solver.clause);
+        solver.clause.clear();
+    } else {
+        solver
+            .clause
+            .push(required_lit_from_ipasir("ipasir_add", lit_or_zero));
+    }
+}
+
+/// Assume a literal for the next call to [`ipasir_solve`].
+///
+/// All assumptions are cleared after the next call to solve. Aborts the process if `lit` is zero or
+/// its variable is past `Var::max_var()`.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_assume(solver: *mut c_void, lit: i32) {
+    let solver = solver_from_ptr(solver);
+    solver
+        .assumptions
+        .push(required_lit_from_ipasir("ipasir_assume", lit));
+}
+
+/// Solve the formula under the current assumptions.
+///
+/// Returns 10 if the formula is satisfiable, 20 if it is unsatisfiable and 0 if solving was
+/// interrupted.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_solve(solver: *mut c_void) -> c_int {
+    let solver = solver_from_ptr(solver);
+    solver.solve()
+}
+
+/// Value of a literal in the model found by the last call to [`ipasir_solve`].
+///
+/// Returns `lit` if it is true, `-lit` if it is false and 0 if the variable is not part of the
+/// model or `lit` is not a valid literal.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_val(solver: *mut c_void, lit: i32) -> i32 {
+    let solver = solver_from_ptr(solver);
+    let lit = match lit_from_ipasir(lit) {
+        Some(lit) => lit,
+        None => return 0,
+    };
+    match solver.model.get(lit.index()).cloned().flatten() {
+        Some(value) => lit.var().lit(value).to_dimacs() as i32,
+        None => 0,
+    }
+}
+
+/// Whether an assumption was used to show unsatisfiability in the last call to [`ipasir_solve`].
+///
+/// Returns 1 if the assumed literal is part of the failed core and 0 otherwise, including for
+/// invalid literals.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_failed(solver: *mut c_void, lit: i32) -> c_int {
+    let solver = solver_from_ptr(solver);
+    lit_from_ipasir(lit)
+        .and_then(|lit| solver.failed.get(lit.code()).cloned())
+        .unwrap_or(false) as c_int
+}
+
+/// Set a callback that is polled during solving.
+///
+/// Solving is interrupted when the callback returns a non-zero value. The callback is invoked with
+/// `data` as argument. Passing a null callback removes a previously set callback.
+///
+/// # Safety
+///
+/// The pointer must have been returned by [`ipasir_init`] and not been released. The callback
+/// must be safe to call with `data` until it is replaced or the solver is released.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_terminate(
+    solver: *mut c_void,
+    data: *mut c_void,
+    terminate: TerminateCallback,
+) {
+    let solver = solver_from_ptr(solver);
+    match terminate {
+        Some(terminate) => solver
+            .solver
+            .set_terminate_callback(move || terminate(data) != 0),
+        None => solver.solver.set_terminate_callback(|| false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::CStr;
+
+    fn add_clause(solver: *mut c_void, lits: &[i32]) {
+        for &lit in lits {
+            unsafe { ipasir_add(solver, lit) };
+        }
+        unsafe { ipasir_add(solver, 0) };
+    }
+
+    #[test]
+    fn signature() {
+        let signature = unsafe { CStr::from_ptr(ipasir_signature()) };
+        assert!(signature.to_str().unwrap().starts_with("varisat-"));
+    }
+
+    #[test]
+    fn incremental_solving() {
+        let solver = ipasir_init();
+
+        add_clause(solver, &[1, 2]);
+        add_clause(solver, &[-1, 3]);
+        add_clause(solver, &[-2, 3]);
+
+        unsafe {
+            assert_eq!(ipasir_solve(solver), 10);
+            assert_eq!(ipasir_val(solver, 3), 3);
+            assert_eq!(ipasir_val(solver, -3), 3);
+            assert_eq!(ipasir_val(solver, 10), 0);
+
+            ipasir_assume(solver, -3);
+            ipasir_assume(solver, 4);
+            assert_eq!(ipasir_solve(solver), 20);
+            assert_eq!(ipasir_failed(solver, -3), 1);
+            assert_eq!(ipasir_failed(solver, 4), 0);
+
+            // Assumptions are cleared after solving.
+            assert_eq!(ipasir_solve(solver), 10);
+
+            ipasir_assume(solver, 1);
+            assert_eq!(ipasir_solve(solver), 10);
+            assert_eq!(ipasir_val(solver, 1), 1);
+            assert_eq!(ipasir_val(solver, -1), 1);
+        }
+
+        add_clause(solver, &[-3]);
+
+        unsafe {
+            assert_eq!(ipasir_solve(solver), 20);
+            ipasir_release(solver);
+        }
+    }
+
+    #[test]
+    fn invalid_lits() {
+        let max_dimacs = Var::max_count() as i32;
+
+        assert_eq!(lit_from_ipasir(0), None);
+        assert_eq!(lit_from_ipasir(i32::MIN), None);
+        assert_eq!(lit_from_ipasir(i32::MAX), None);
+        assert_eq!(lit_from_ipasir(max_dimacs + 1), None);
+        assert_eq!(lit_from_ipasir(-max_dimacs - 1), None);
+        assert_eq!(
+            lit_from_ipasir(-max_dimacs),
+            Some(Lit::from_dimacs(-max_dimacs as isize))
+        );
+
+        let solver = ipasir_init();
+
+        add_clause(solver, &[1]);
+
+        unsafe {
+            assert_eq!(ipasir_solve(solver), 10);
+            assert_eq!(ipasir_val(solver, i32::MIN), 0);
+            assert_eq!(ipasir_val(solver, i32::MAX), 0);
+            assert_eq!(ipasir_failed(solver, i32::MIN), 0);
+            assert_eq!(ipasir_failed(solver, 0), 0);
+            ipasir_release(solver);
+        }
+    }
+
+    extern "C" fn terminate_after(data: *mut c_void) -> c_int {
+        let polls = unsafe { &mut *(data as *mut usize) };
+        if *polls == 0 {
+            1
+        } else {
+            *polls -= 1;
+            0
+        }
+    }
+
+    #[test]
+    fn terminate() {
+        let solver = ipasir_init();
+
+        // Pigeon hole formula that requires some conflicts to refute.
+        for pigeon in 0..6 {
+            let lits: Vec<i32> = (0..5).map(|hole| pigeon * 5 + hole + 1).collect();
+            add_clause(solver, &lits);
+        }
+        for hole in 0..5 {
+            for a in 0..6 {
+                for b in a + 1..6 {
+                    add_clause(solver, &[-(a * 5 + hole + 1), -(b * 5 + hole + 1)]);
+                }
+            }
+        }
+
+        let mut polls = 0usize;
+
+        unsafe {
+            ipasir_set_terminate(
+                solver,
+                &mut polls as *mut usize as *mut c_void,
+                Some(terminate_after),
+            );
+            assert_eq!(ipasir_solve(solver), 0);
+
+            ipasir_set_terminate(solver, std::ptr::null_mut(), None);
+            assert_eq!(ipasir_solve(solver), 20);
+
+            ipasir_release(solver);
+        }
+    }
+}
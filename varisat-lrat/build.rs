@@ -55,6 +55,8 @@ fn have_check_clrat() -> Result<(), Error> {
 }
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(test_check_lrat)");
+
     match (have_check_lrat(), have_check_clrat()) {
         (Ok(_), Ok(_)) => println!("cargo:rustc-cfg=test_check_lrat"),
         (Err(err), _) => println!(
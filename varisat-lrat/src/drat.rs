@@ -0,0 +1,181 @@
+//! DRAT proof generation from checked proofs.
+use std::io::{BufWriter, Write};
+
+use anyhow::Error;
+
+use varisat_checker::{CheckedProofStep, CheckerData, ProofProcessor};
+use varisat_formula::Lit;
+
+/// Proof processor that generates a DRAT proof.
+///
+/// Every clause derived by an AT step is written as an addition and every deletion of a clause is
+/// written as a deletion. Steps that concern models or assumptions are ignored.
+pub struct WriteDrat<'a> {
+    binary: bool,
+    target: BufWriter<Box<dyn Write + 'a>>,
+}
+
+impl<'a> ProofProcessor for WriteDrat<'a> {
+    fn process_step(&mut self, step: &CheckedProofStep, _data: CheckerData) -> Result<(), Error> {
+        match step {
+            &CheckedProofStep::AtClause { clause, .. } => {
+                self.write_add_step()?;
+                self.write_lits(clause)?;
+                self.write_end()?;
+            }
+            &CheckedProofStep::DeleteAtClause {
+                keep_as_redundant,
+                clause,
+                ..
+            }
+            | &CheckedProofStep::DeleteRatClause {
+                keep_as_redundant,
+                clause,
+                ..
+            } => {
+                if !keep_as_redundant {
+                    self.write_delete_step()?;
+                    self.write_lits(clause)?;
+                    self.write_end()?;
+                }
+            }
+            &CheckedProofStep::DeleteClause { clause, .. } => {
+                self.write_delete_step()?;
+                self.write_lits(clause)?;
+                self.write_end()?;
+            }
+            &CheckedProofStep::AddClause { .. }
+            | &CheckedProofStep::DuplicatedClause { .. }
+            | &CheckedProofStep::TautologicalClause { .. }
+            | &CheckedProofStep::UserVar { .. }
+            | &CheckedProofStep::MakeIrredundant { .. }
+            | &CheckedProofStep::Model { .. }
+            | &CheckedProofStep::Assumptions { .. }
+            | &CheckedProofStep::FailedAssumptions { .. } => (),
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteDrat<'a> {
+    /// Create a drat writing processor.
+    ///
+    /// The proof is written to `target`. If `binary` is false a textual DRAT proof is emitted,
+    /// otherwise the binary DRAT format is used.
+    pub fn new(target: impl Write + 'a, binary: bool) -> WriteDrat<'a> {
+        WriteDrat {
+            binary,
+            target: BufWriter::new(Box::new(target)),
+        }
+    }
+
+    /// Write out all steps processed so far.
+    ///
+    /// This is automatically called when this proof processor is dropped. Calling this explicitly
+    /// is recommended to handle possible IO errors.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.target.flush()?;
+        Ok(())
+    }
+
+    /// Begin a clause addition step.
+    fn write_add_step(&mut self) -> Result<(), Error> {
+        if self.binary {
+            self.target.write_all(b"a")?;
+        }
+        Ok(())
+    }
+
+    /// Begin a clause deletion step.
+    fn write_delete_step(&mut self) -> Result<(), Error> {
+        if self.binary {
+            self.target.write_all(b"d")?;
+        } else {
+            self.target.write_all(b"d ")?;
+        }
+        Ok(())
+    }
+
+    /// Write a list of literals.
+    fn write_lits(&mut self, lits: &[Lit]) -> Result<(), Error> {
+        if self.binary {
+            for &lit in lits {
+                leb128::write::unsigned(&mut self.target, lit.code() as u64 + 2)?;
+            }
+        } else {
+            for &lit in lits {
+                itoa::write(&mut self.target, lit.to_dimacs())?;
+                self.target.write_all(b" ")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// End the current step.
+    fn write_end(&mut self) -> Result<(), Error> {
+        if self.binary {
+            self.target.write_all(&[0])?
+        } else {
+            self.target.write_all(b"0\n")?
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for WriteDrat<'a> {
+    fn drop(&mut self) {
+        let _ignore_errors = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use varisat::{dimacs::write_dimacs, ProofFormat, Solver};
+    use varisat_checker::Checker;
+    use varisat_formula::{test::sgen_unsat_formula, CnfFormula};
+
+    fn solve_and_convert_drat(formula: CnfFormula, binary: bool) -> Result<(), Error> {
+        let mut dimacs = vec![];
+        let mut proof = vec![];
+        let mut drat_proof = vec![];
+
+        write_dimacs(&mut dimacs, &formula)?;
+
+        let mut solver = Solver::new();
+        solver.write_proof(&mut proof, ProofFormat::Varisat);
+        solver.add_dimacs_cnf(&mut &dimacs[..])?;
+        assert_eq!(solver.solve().ok(), Some(false));
+        solver.close_proof()?;
+        drop(solver);
+
+        {
+            let mut write_drat = WriteDrat::new(&mut drat_proof, binary);
+            let mut checker = Checker::new();
+            checker.add_processor(&mut write_drat);
+            checker.add_dimacs_cnf(&mut &dimacs[..])?;
+            checker.check_proof(&mut &proof[..])?;
+            drop(checker);
+            write_drat.flush()?;
+        }
+
+        let mut checker = Checker::new();
+        checker.add_dimacs_cnf(&mut &dimacs[..])?;
+        checker.check_drat_proof(&mut &drat_proof[..])?;
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn sgen_unsat_drat(
+            formula in sgen_unsat_formula(1..7usize),
+            binary in proptest::bool::ANY,
+        ) {
+            solve_and_convert_drat(formula, binary).unwrap();
+        }
+    }
+}
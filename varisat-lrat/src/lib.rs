@@ -1,14 +1,17 @@
 //! LRAT proof generation for the Varisat SAT solver.
-use std::{
-    io::{BufWriter, Write},
-    mem::replace,
-};
+//!
+//! This also provides [`WriteDrat`] to convert checked proofs into DRAT proofs.
+use std::io::{BufWriter, Write};
 
 use anyhow::Error;
 
 use varisat_checker::{CheckedProofStep, CheckerData, ProofProcessor};
 use varisat_formula::Lit;
 
+mod drat;
+
+pub use drat::WriteDrat;
+
 /// Proof processor that generates an LRAT proof.
 pub struct WriteLrat<'a> {
     binary: bool,
@@ -25,7 +28,7 @@ impl<'a> ProofProcessor for WriteLrat<'a> {
             CheckedProofStep::DuplicatedClause { .. } => (),
             _ => {
                 if !self.buffered_deletes.is_empty() {
-                    let buffered_deletes = replace(&mut self.buffered_deletes, vec![]);
+                    let buffered_deletes = std::mem::take(&mut self.buffered_deletes);
                     self.open_delete()?;
                     self.write_ids(&buffered_deletes)?;
                 }
@@ -230,7 +233,7 @@ mod tests {
     use tempfile::TempDir;
 
     use varisat::{dimacs::write_dimacs, ProofFormat, Solver};
-    use varisat_checker::Checker;
+    use varisat_checker::{Checker, CheckerError};
     use varisat_formula::{cnf_formula, test::sgen_unsat_formula, CnfFormula};
 
     fn check_lrat(tool: &str, cnf_file: &PathBuf, proof_file: &PathBuf) -> Result<bool, Error> {
@@ -300,6 +303,70 @@ mod tests {
         )
     }
 
+    /// Generate an LRAT proof for an unsatisfiable formula.
+    fn solve_to_lrat(formula: &CnfFormula, binary: bool, direct: bool) -> Result<Vec<u8>, Error> {
+        let mut lrat_proof = vec![];
+        let mut proof = vec![];
+
+        let mut write_lrat = WriteLrat::new(&mut lrat_proof, binary);
+
+        let mut solver = Solver::new();
+
+        if direct {
+            solver.add_proof_processor(&mut write_lrat);
+        } else {
+            solver.write_proof(&mut proof, ProofFormat::Varisat);
+        }
+
+        solver.add_formula(formula);
+
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        solver.close_proof()?;
+
+        drop(solver);
+
+        if !direct {
+            let mut checker = Checker::new();
+            checker.add_processor(&mut write_lrat);
+            checker.add_formula(formula)?;
+            checker.check_proof(&mut &proof[..])?;
+        }
+
+        write_lrat.flush()?;
+        drop(write_lrat);
+
+        Ok(lrat_proof)
+    }
+
+    /// Check an LRAT proof using [`Checker::check_lrat_proof`].
+    fn check_lrat_proof(formula: &CnfFormula, lrat_proof: &[u8]) -> Result<(), CheckerError> {
+        let mut checker = Checker::new();
+        checker.add_formula(formula)?;
+        checker.check_lrat_proof(lrat_proof)
+    }
+
+    /// Remove the last hint of the step adding the empty clause from a text LRAT proof.
+    fn remove_last_empty_clause_hint(lrat_proof: &[u8]) -> Vec<u8> {
+        let text = std::str::from_utf8(lrat_proof).unwrap();
+        let mut corrupted = String::new();
+        let mut found = false;
+
+        for line in text.lines() {
+            let mut tokens: Vec<&str> = line.split_whitespace().collect();
+            if !found && tokens.len() >= 4 && tokens[1] == "0" {
+                tokens.remove(tokens.len() - 2);
+                found = true;
+            }
+            corrupted.push_str(&tokens.join(" "));
+            corrupted.push('\n');
+        }
+
+        assert!(found, "no empty clause with hints in {:?}", text);
+
+        corrupted.into_bytes()
+    }
+
     #[cfg_attr(not(test_check_lrat), ignore)]
     #[test]
     fn duplicated_clause_lrat() {
@@ -364,5 +431,30 @@ mod tests {
         ) {
             prop_assert!(solve_and_check_lrat(formula, binary, direct).unwrap());
         }
+
+        #[test]
+        fn sgen_unsat_check_lrat_proof(
+            formula in sgen_unsat_formula(1..7usize),
+            binary in proptest::bool::ANY,
+            direct in proptest::bool::ANY,
+        ) {
+            let lrat_proof = solve_to_lrat(&formula, binary, direct).unwrap();
+            check_lrat_proof(&formula, &lrat_proof).unwrap();
+        }
+
+        #[test]
+        fn sgen_unsat_check_corrupted_lrat_proof(
+            formula in sgen_unsat_formula(1..7usize),
+            direct in proptest::bool::ANY,
+        ) {
+            let lrat_proof = solve_to_lrat(&formula, false, direct).unwrap();
+            let corrupted = remove_last_empty_clause_hint(&lrat_proof);
+            let result = check_lrat_proof(&formula, &corrupted);
+            prop_assert!(
+                matches!(result, Err(CheckerError::CheckFailed { .. })),
+                "unexpected result {:?}",
+                result
+            );
+        }
     }
 }
@@ -0,0 +1,29 @@
+//! Parse a formula given as a string and solve it.
+//!
+//! This only uses parts of Varisat that are available on `wasm32-unknown-unknown`.
+use varisat::{dimacs::DimacsParser, Solver};
+
+const FORMULA: &str = "\
+p cnf 3 4
+1 2 0
+-1 3 0
+-2 3 0
+-3 -1 0
+";
+
+fn main() {
+    let formula = DimacsParser::parse(FORMULA.as_bytes()).expect("invalid DIMACS CNF");
+
+    let mut solver = Solver::new();
+    solver.add_formula(&formula);
+
+    match solver.solve() {
+        Ok(true) => {
+            let model = solver.model().unwrap();
+            let lits: Vec<String> = model.iter().map(|lit| lit.to_string()).collect();
+            println!("SAT: {}", lits.join(" "));
+        }
+        Ok(false) => println!("UNSAT"),
+        Err(err) => println!("solving failed: {}", err),
+    }
+}
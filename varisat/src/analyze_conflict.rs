@@ -9,8 +9,10 @@ use varisat_formula::{lit::LitIdx, Lit, Var};
 use varisat_internal_proof::{clause_hash, lit_hash, ClauseHash};
 
 use crate::{
+    callbacks,
     clause::ClauseRef,
     context::{parts::*, Context},
+    decision,
     prop::{Conflict, Reason},
 };
 
@@ -41,6 +43,24 @@ impl AnalyzeConflict {
         self.var_flags.resize(count, false);
     }
 
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.var_flags
+            .reserve(count.saturating_sub(self.var_flags.len()));
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.clause.clear();
+        self.current_level_count = 0;
+        self.var_flags.clear();
+        self.to_clean.clear();
+        self.involved.clear();
+        self.clause_hashes.clear();
+        self.unordered_clause_hashes.clear();
+        self.stack.clear();
+    }
+
     /// The learned clause.
     pub fn clause(&self) -> &[Lit] {
         &self.clause
@@ -61,16 +81,22 @@ impl AnalyzeConflict {
 
 /// Learns a new clause by analyzing a conflict.
 ///
-/// Returns the lowest decision level that makes the learned clause asserting.
+/// Returns the lowest decision level that makes the learned clause asserting. The learned clause is
+/// passed to the learned clause callback.
 pub fn analyze_conflict<'a>(
     mut ctx: partial!(
         Context<'a>,
         mut AnalyzeConflictP,
+        mut CallbacksP<'a>,
+        mut NonDecisionP,
+        mut VmtfP,
         mut VsidsP,
         ClauseAllocP,
         ImplGraphP,
         ProofP<'a>,
+        SolverConfigP,
         TrailP,
+        VariablesP,
     ),
     conflict: Conflict,
 ) -> usize {
@@ -97,6 +123,7 @@ pub fn analyze_conflict<'a>(
 
     if ctx.part(TrailP).current_level() == 0 {
         // Conflict with no decisions, generate empty clause
+        callbacks::learned_clause(ctx.borrow(), &[]);
         return 0;
     }
 
@@ -202,7 +229,9 @@ pub fn analyze_conflict<'a>(
         }
     }
 
-    ctx.part_mut(VsidsP).decay();
+    decision::decay_var_activities(ctx.borrow());
+
+    callbacks::learned_clause(ctx.borrow(), &analyze.clause);
 
     backtrack_to
 }
@@ -212,9 +241,11 @@ fn add_literal(
     mut ctx: partial!(
         Context,
         mut AnalyzeConflictP,
+        mut VmtfP,
         mut VsidsP,
         ImplGraphP,
-        TrailP
+        SolverConfigP,
+        TrailP,
     ),
     lit: Lit,
 ) {
@@ -222,7 +253,7 @@ fn add_literal(
     let lit_level = ctx.part(ImplGraphP).level(lit.var());
     // No need to add literals that are set by unit clauses or already present
     if lit_level > 0 && !analyze.var_flags[lit.index()] {
-        ctx.part_mut(VsidsP).bump(lit.var());
+        decision::bump_var(ctx.borrow(), lit.var());
 
         analyze.var_flags[lit.index()] = true;
         if lit_level == ctx.part(TrailP).current_level() {
@@ -286,10 +317,12 @@ fn minimize_clause<'a>(
     mut ctx: partial!(
         Context<'a>,
         mut AnalyzeConflictP,
+        mut VmtfP,
         mut VsidsP,
         ClauseAllocP,
         ImplGraphP,
         ProofP<'a>,
+        SolverConfigP,
         TrailP,
     ),
 ) {
@@ -34,6 +34,15 @@ impl Assumptions {
         self.assumption_levels = 0;
     }
 
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.assumptions.clear();
+        self.failed_core.clear();
+        self.user_failed_core.clear();
+        self.assumption_levels = 0;
+        self.failed_propagation_hashes.clear();
+    }
+
     /// Subset of assumptions that made the formula unsatisfiable.
     pub fn failed_core(&self) -> &[Lit] {
         &self.failed_core
@@ -68,24 +77,20 @@ pub fn set_assumptions<'a>(
         mut AssumptionsP,
         mut BinaryClausesP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpFlagsP,
         mut TrailP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
     user_assumptions: &[Lit],
 ) {
-    full_restart(ctx.borrow());
-
-    let state = ctx.part_mut(SolverStateP);
-
-    state.sat_state = match state.sat_state {
-        SatState::Unsat => SatState::Unsat,
-        SatState::Sat | SatState::UnsatUnderAssumptions | SatState::Unknown => SatState::Unknown,
-    };
+    assumptions_changed(ctx.borrow());
 
     let (assumptions, mut ctx_2) = ctx.split_part_mut(AssumptionsP);
 
@@ -110,8 +115,131 @@ pub fn set_assumptions<'a>(
             .assumed = true;
     }
 
+    add_assumptions_step(ctx.borrow());
+}
+
+/// Add an assumption after the currently active assumptions.
+///
+/// The input uses user variable names.
+pub fn push_assumption<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut BinaryClausesP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+    user_lit: Lit,
+) {
+    assumptions_changed(ctx.borrow());
+
+    let (assumptions, mut ctx_2) = ctx.split_part_mut(AssumptionsP);
+
+    let lit =
+        user_lit.map_var(|user_var| variables::solver_from_user(ctx_2.borrow(), user_var, true));
+
+    ctx_2
+        .part_mut(VariablesP)
+        .var_data_solver_mut(lit.var())
+        .assumed = true;
+
+    assumptions.assumptions.push(lit);
+
+    add_assumptions_step(ctx.borrow());
+}
+
+/// Remove the most recently added assumption.
+///
+/// Returns false if there are no assumptions.
+pub fn pop_assumption<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut BinaryClausesP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+) -> bool {
+    if ctx.part(AssumptionsP).assumptions.is_empty() {
+        return false;
+    }
+
+    assumptions_changed(ctx.borrow());
+
+    let (assumptions, mut ctx_2) = ctx.split_part_mut(AssumptionsP);
+
+    let lit = assumptions.assumptions.pop().unwrap();
+
+    // The same variable can be assumed multiple times.
+    if assumptions
+        .assumptions
+        .iter()
+        .all(|other| other.var() != lit.var())
+    {
+        ctx_2
+            .part_mut(VariablesP)
+            .var_data_solver_mut(lit.var())
+            .assumed = false;
+    }
+
+    add_assumptions_step(ctx.borrow());
+
+    true
+}
+
+/// Invalidate the search state and result after changing the assumptions.
+fn assumptions_changed(
+    mut ctx: partial!(
+        Context,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut NonDecisionP,
+        mut SolverStateP,
+        mut TrailP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP,
+    ),
+) {
+    full_restart(ctx.borrow());
+
+    let state = ctx.part_mut(SolverStateP);
+
+    state.sat_state = match state.sat_state {
+        SatState::Unsat => SatState::Unsat,
+        SatState::Sat | SatState::UnsatUnderAssumptions | SatState::Unknown => SatState::Unknown,
+    };
+}
+
+/// Record the current assumptions in the proof.
+fn add_assumptions_step<'a>(
+    mut ctx: partial!(Context<'a>, mut ProofP<'a>, mut SolverStateP, AssumptionsP, VariablesP),
+) {
+    let (assumptions, mut ctx) = ctx.split_part(AssumptionsP);
     proof::add_step(
-        ctx_2.borrow(),
+        ctx.borrow(),
         true,
         &ProofStep::Assumptions {
             assumptions: &assumptions.assumptions,
@@ -1,5 +1,7 @@
 //! Binary clauses.
 
+use std::mem::size_of;
+
 use partial_ref::{partial, PartialRef};
 
 use varisat_formula::Lit;
@@ -15,14 +17,36 @@ use crate::{
 pub struct BinaryClauses {
     by_lit: Vec<Vec<Lit>>,
     count: usize,
+    /// Empty lists kept for reuse by [`reset`](BinaryClauses::reset).
+    unused: Vec<Vec<Lit>>,
 }
 
 impl BinaryClauses {
     /// Update structures for a new variable count.
     pub fn set_var_count(&mut self, count: usize) {
+        let missing = (count * 2).saturating_sub(self.by_lit.len());
+        let reused = self.unused.len().saturating_sub(missing);
+        self.by_lit.extend(self.unused.drain(reused..));
         self.by_lit.resize(count * 2, vec![]);
     }
 
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.by_lit
+            .reserve((count * 2).saturating_sub(self.by_lit.len()));
+    }
+
+    /// Remove all binary clauses, keeping the allocated capacity.
+    ///
+    /// The emptied lists are reused when the variable count increases again.
+    pub fn reset(&mut self) {
+        self.unused.extend(self.by_lit.drain(..).map(|mut implied| {
+            implied.clear();
+            implied
+        }));
+        self.count = 0;
+    }
+
     /// Add a binary clause.
     pub fn add_binary_clause(&mut self, lits: [Lit; 2]) {
         for i in 0..2 {
@@ -36,10 +60,34 @@ impl BinaryClauses {
         &self.by_lit[lit.code()]
     }
 
+    /// Estimated number of bytes used by the binary clauses.
+    pub fn memory_usage(&self) -> usize {
+        let lists = (self.by_lit.capacity() + self.unused.capacity()) * size_of::<Vec<Lit>>();
+        let lits: usize = self
+            .by_lit
+            .iter()
+            .chain(self.unused.iter())
+            .map(|implied| implied.capacity())
+            .sum();
+        lists + lits * size_of::<Lit>()
+    }
+
     /// Number of binary clauses.
     pub fn count(&self) -> usize {
         self.count
     }
+
+    /// Iterator over all binary clauses.
+    pub fn clauses_iter(&self) -> impl Iterator<Item = [Lit; 2]> + '_ {
+        self.by_lit.iter().enumerate().flat_map(|(code, implied)| {
+            let lit = !Lit::from_code(code);
+            // Each clause is stored for both of its literals, so we only report one of them.
+            implied
+                .iter()
+                .filter(move |&&other_lit| lit < other_lit)
+                .map(move |&other_lit| [lit, other_lit])
+        })
+    }
 }
 
 /// Remove binary clauses that have an assigned literal.
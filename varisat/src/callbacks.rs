@@ -0,0 +1,73 @@
+//! User provided callbacks.
+use partial_ref::{partial, PartialRef};
+
+use varisat_formula::Lit;
+
+use crate::{
+    context::{parts::*, Context},
+    export::export_solver_lit,
+    stats::SolverStats,
+};
+
+/// Callback that is passed a clause.
+pub type ClauseCallback<'a> = Box<dyn FnMut(&[Lit]) + 'a>;
+
+/// Callback that is polled and returns true to request termination.
+pub type TerminateCallback<'a> = Box<dyn FnMut() -> bool + 'a>;
+
+/// Callback that is passed the current solver statistics.
+pub type ProgressCallback<'a> = Box<dyn FnMut(&SolverStats) + 'a>;
+
+/// User provided callbacks.
+#[derive(Default)]
+pub struct Callbacks<'a> {
+    /// Invoked for each learned clause.
+    pub learned_clause: Option<ClauseCallback<'a>>,
+    /// Polled before each conflict.
+    pub terminate: Option<TerminateCallback<'a>>,
+    /// Invoked periodically during solving.
+    pub progress: Option<ProgressCallback<'a>>,
+    /// Temporary storage for literals passed to callbacks.
+    user_lits: Vec<Lit>,
+}
+
+impl<'a> Callbacks<'a> {
+    /// Remove all callbacks, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.learned_clause = None;
+        self.terminate = None;
+        self.progress = None;
+        self.user_lits.clear();
+    }
+}
+
+/// Pass a newly learned clause to the learned clause callback if present.
+pub fn learned_clause<'a>(
+    mut ctx: partial!(Context<'a>, mut CallbacksP<'a>, VariablesP),
+    lits: &[Lit],
+) {
+    let (callbacks, ctx) = ctx.split_part_mut(CallbacksP);
+    if let Some(callback) = &mut callbacks.learned_clause {
+        let variables = ctx.part(VariablesP);
+        callbacks.user_lits.clear();
+        callbacks
+            .user_lits
+            .extend(lits.iter().map(|&lit| export_solver_lit(variables, lit)));
+        callback(&callbacks.user_lits);
+    }
+}
+
+/// Poll the terminate callback if present.
+pub fn terminate_requested<'a>(mut ctx: partial!(Context<'a>, mut CallbacksP<'a>)) -> bool {
+    ctx.part_mut(CallbacksP)
+        .terminate
+        .as_mut()
+        .is_some_and(|callback| callback())
+}
+
+/// Pass the current statistics to the progress callback if present.
+pub fn progress<'a>(mut ctx: partial!(Context<'a>, mut CallbacksP<'a>), stats: &SolverStats) {
+    if let Some(callback) = &mut ctx.part_mut(CallbacksP).progress {
+        callback(stats);
+    }
+}
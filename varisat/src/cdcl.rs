@@ -2,17 +2,19 @@
 
 use partial_ref::{partial, PartialRef};
 
-use varisat_internal_proof::ProofStep;
+use varisat_formula::Lit;
+use varisat_internal_proof::{clause_hash, ProofStep};
 
 use crate::{
     analyze_conflict::analyze_conflict,
     assumptions::{enqueue_assumption, EnqueueAssumption},
+    callbacks,
     clause::{assess_learned_clause, bump_clause, db, decay_clause_activities},
     context::{parts::*, Context},
     decision::make_decision,
     model::reconstruct_global_model,
     proof,
-    prop::{backtrack, enqueue_assignment, propagate, Conflict, Reason},
+    prop::{backtrack, enqueue_assignment, full_restart, propagate, Conflict, Reason},
     state::SatState,
     unit_simplify::{prove_units, unit_simplify},
 };
@@ -25,19 +27,24 @@ pub fn conflict_step<'a>(
         mut AssignmentP,
         mut AssumptionsP,
         mut BinaryClausesP,
+        mut CallbacksP<'a>,
         mut ClauseActivityP,
         mut ClauseAllocP,
         mut ClauseDbP,
         mut ImplGraphP,
+        mut LearnStatsP,
         mut ModelP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpDataP,
         mut TmpFlagsP,
         mut TrailP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
 ) {
     let conflict = find_conflict(ctx.borrow());
@@ -68,6 +75,8 @@ pub fn conflict_step<'a>(
 
     let clause = analyze.clause();
 
+    ctx.part_mut(LearnStatsP).count_learned_clause(clause.len());
+
     proof::add_step(
         ctx.borrow(),
         true,
@@ -91,6 +100,7 @@ pub fn conflict_step<'a>(
         }
         _ => {
             let header = assess_learned_clause(ctx.borrow(), clause);
+            ctx.part_mut(LearnStatsP).count_glue(header.glue());
             let cref = db::add_clause(ctx.borrow(), header, clause);
             Reason::Long(cref)
         }
@@ -99,6 +109,91 @@ pub fn conflict_step<'a>(
     enqueue_assignment(ctx.borrow(), clause[0], reason);
 }
 
+/// Propagate the top level assignments without making any decisions.
+///
+/// This undoes all decisions and assumptions first. Returns the literals that were implied by this
+/// propagation, using global variable names. On a conflict the empty clause is learned and the
+/// state becomes unsatisfiable.
+pub fn propagate_top_level<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut BinaryClausesP,
+        mut CallbacksP<'a>,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TrailP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+) -> Vec<Lit> {
+    if ctx.part(SolverStateP).sat_state == SatState::Unsat {
+        return vec![];
+    }
+
+    full_restart(ctx.borrow());
+
+    let trail_len = ctx.part(TrailP).trail().len();
+
+    let propagation_result = propagate(ctx.borrow());
+
+    let variables = ctx.part(VariablesP);
+    let implied = ctx.part(TrailP).trail()[trail_len..]
+        .iter()
+        .map(|&lit| {
+            lit.map_var(|solver_var| {
+                variables
+                    .global_from_solver()
+                    .get(solver_var)
+                    .expect("no existing global var for solver var")
+            })
+        })
+        .collect();
+
+    let new_unit = prove_units(ctx.borrow());
+
+    match propagation_result {
+        Ok(()) => {
+            if new_unit {
+                unit_simplify(ctx.borrow());
+            }
+        }
+        Err(conflict) => {
+            // Without decisions the learned clause is always empty and follows from propagating
+            // the conflicting clause. Skipping the full conflict analysis keeps the variable
+            // activities untouched.
+            let mut propagation_hashes = vec![];
+            if ctx.part(ProofP).clause_hashes_required() {
+                propagation_hashes.push(clause_hash(conflict.lits(&ctx.borrow())));
+            }
+
+            callbacks::learned_clause(ctx.borrow(), &[]);
+
+            proof::add_step(
+                ctx.borrow(),
+                true,
+                &ProofStep::AtClause {
+                    redundant: false,
+                    clause: &[],
+                    propagation_hashes: &propagation_hashes,
+                },
+            );
+
+            ctx.part_mut(SolverStateP).sat_state = SatState::Unsat;
+        }
+    }
+
+    implied
+}
+
 /// Return type of [`find_conflict`].
 ///
 /// Specifies whether a conflict was found during propagation or while enqueuing assumptions.
@@ -125,13 +220,16 @@ fn find_conflict<'a>(
         mut ClauseAllocP,
         mut ClauseDbP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpFlagsP,
         mut TrailP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
 ) -> Result<(), FoundConflict> {
     loop {
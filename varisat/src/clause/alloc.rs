@@ -1,5 +1,8 @@
 //! Clause allocator.
-use std::{mem::transmute, slice};
+use std::{
+    mem::{size_of, transmute},
+    slice,
+};
 
 use varisat_formula::{lit::LitIdx, Lit};
 
@@ -37,6 +40,21 @@ impl ClauseAlloc {
         }
     }
 
+    /// Reserve capacity for the given number of additional clauses and literals.
+    pub fn reserve(&mut self, clauses: usize, lits: usize) {
+        self.buffer.reserve(clauses * HEADER_LEN + lits);
+    }
+
+    /// Estimated number of bytes used by the allocator.
+    pub fn memory_usage(&self) -> usize {
+        self.buffer.capacity() * size_of::<LitIdx>()
+    }
+
+    /// Remove all clauses, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
     /// Allocate space for and add a new clause.
     ///
     /// Clauses have a minimal size of 3, as binary and unit clauses are handled separately. This is
@@ -79,7 +97,7 @@ impl ClauseAlloc {
     pub fn header(&self, cref: ClauseRef) -> &ClauseHeader {
         let offset = cref.offset as usize;
         assert!(
-            offset as usize + HEADER_LEN <= self.buffer.len(),
+            offset + HEADER_LEN <= self.buffer.len(),
             "ClauseRef out of bounds"
         );
         unsafe { self.header_unchecked(cref) }
@@ -89,7 +107,7 @@ impl ClauseAlloc {
     pub fn header_mut(&mut self, cref: ClauseRef) -> &mut ClauseHeader {
         let offset = cref.offset as usize;
         assert!(
-            offset as usize + HEADER_LEN <= self.buffer.len(),
+            offset + HEADER_LEN <= self.buffer.len(),
             "ClauseRef out of bounds"
         );
         unsafe { self.header_unchecked_mut(cref) }
@@ -188,7 +206,7 @@ impl ClauseRef {
     /// The largest offset supported by the ClauseAlloc
     const fn max_offset() -> ClauseOffset {
         // Make sure we can savely add a length to an offset without overflowing usize
-        ((usize::max_value() >> 1) & (ClauseOffset::max_value() as usize)) as ClauseOffset
+        ((usize::MAX >> 1) & (ClauseOffset::MAX as usize)) as ClauseOffset
     }
 }
 
@@ -5,6 +5,7 @@ use varisat_formula::Lit;
 
 use crate::{
     clause::{db, ClauseRef},
+    config::SolverConfig,
     context::{parts::*, Context},
     glue::compute_glue,
 };
@@ -13,7 +14,7 @@ use super::{bump_clause_activity, ClauseHeader, Tier};
 
 /// Assess the newly learned clause and generate a clause header.
 pub fn assess_learned_clause(
-    mut ctx: partial!(Context, mut TmpFlagsP, ImplGraphP),
+    mut ctx: partial!(Context, mut TmpFlagsP, ImplGraphP, SolverConfigP),
     lits: &[Lit],
 ) -> ClauseHeader {
     // This is called while the clause is still in conflict, thus the computed glue level is one
@@ -23,16 +24,16 @@ pub fn assess_learned_clause(
     let mut header = ClauseHeader::new();
 
     header.set_glue(glue);
-    header.set_tier(select_tier(glue));
+    header.set_tier(select_tier(ctx.part(SolverConfigP), glue));
 
     header
 }
 
 /// Compute the tier for a redundant clause with a given glue level.
-fn select_tier(glue: usize) -> Tier {
-    if glue <= 2 {
+fn select_tier(config: &SolverConfig, glue: usize) -> Tier {
+    if glue <= config.core_glue_limit {
         Tier::Core
-    } else if glue <= 6 {
+    } else if glue <= config.mid_glue_limit {
         Tier::Mid
     } else {
         Tier::Local
@@ -47,7 +48,8 @@ pub fn bump_clause(
         mut ClauseAllocP,
         mut ClauseDbP,
         mut TmpFlagsP,
-        ImplGraphP
+        ImplGraphP,
+        SolverConfigP,
     ),
     cref: ClauseRef,
 ) {
@@ -64,6 +66,7 @@ pub fn bump_clause(
     if glue < clause.header().glue() {
         clause.header_mut().set_glue(glue);
 
-        db::set_clause_tier(ctx.borrow(), cref, select_tier(glue));
+        let tier = select_tier(ctx.part(SolverConfigP), glue);
+        db::set_clause_tier(ctx.borrow(), cref, tier);
     }
 }
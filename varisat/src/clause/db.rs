@@ -1,5 +1,5 @@
 //! Database for long clauses.
-use std::mem::transmute;
+use std::mem::{size_of, transmute};
 
 use partial_ref::{partial, PartialRef};
 
@@ -57,10 +57,37 @@ pub struct ClauseDb {
 }
 
 impl ClauseDb {
+    /// Estimated number of bytes used by the clause database, excluding the clause allocator.
+    pub fn memory_usage(&self) -> usize {
+        let refs = self.clauses.capacity()
+            + self
+                .by_tier
+                .iter()
+                .map(|by_tier| by_tier.capacity())
+                .sum::<usize>();
+        refs * size_of::<ClauseRef>()
+    }
+
+    /// Reserve capacity for at least `additional` more irredundant clauses.
+    pub fn reserve_irred(&mut self, additional: usize) {
+        self.clauses.reserve(additional);
+        self.by_tier[Tier::Irred as usize].reserve(additional);
+    }
+
     /// The number of long clauses of a given tier.
     pub fn count_by_tier(&self, tier: Tier) -> usize {
         self.count_by_tier[tier as usize]
     }
+
+    /// Remove all clauses, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.clauses.clear();
+        for by_tier in self.by_tier.iter_mut() {
+            by_tier.clear();
+        }
+        self.count_by_tier = [0; Tier::count()];
+        self.garbage_size = 0;
+    }
 }
 
 /// Add a long clause to the database.
@@ -231,8 +258,8 @@ mod tests {
 
         set_var_count(ctx.borrow(), clauses.var_count());
 
-        let tiers = vec![Tier::Irred, Tier::Core, Tier::Mid, Tier::Local];
-        let new_tiers = vec![Tier::Irred, Tier::Local, Tier::Local, Tier::Core];
+        let tiers = [Tier::Irred, Tier::Core, Tier::Mid, Tier::Local];
+        let new_tiers = [Tier::Irred, Tier::Local, Tier::Local, Tier::Core];
 
         let mut crefs = vec![];
 
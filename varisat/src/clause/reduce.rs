@@ -1,6 +1,4 @@
 //! Clause database reduction.
-use std::mem::replace;
-
 use ordered_float::OrderedFloat;
 use vec_mut_scan::VecMutScan;
 
@@ -35,7 +33,10 @@ pub fn dedup_and_mark_by_tier(
     })
 }
 
-/// Reduce the number of local tier clauses by deleting half of them.
+/// Reduce the number of local tier clauses.
+///
+/// This deletes the given fraction of local clauses, but at least enough to keep no more than
+/// `max_count` clauses.
 pub fn reduce_locals<'a>(
     mut ctx: partial!(
         Context<'a>,
@@ -48,13 +49,12 @@ pub fn reduce_locals<'a>(
         ImplGraphP,
         VariablesP,
     ),
+    fraction: f64,
+    max_count: Option<usize>,
 ) {
     dedup_and_mark_by_tier(ctx.borrow(), Tier::Local);
 
-    let mut locals = replace(
-        &mut ctx.part_mut(ClauseDbP).by_tier[Tier::Local as usize],
-        vec![],
-    );
+    let mut locals = std::mem::take(&mut ctx.part_mut(ClauseDbP).by_tier[Tier::Local as usize]);
 
     locals.sort_unstable_by_key(|&cref| {
         (
@@ -63,7 +63,10 @@ pub fn reduce_locals<'a>(
         )
     });
 
-    let mut to_delete = locals.len() / 2;
+    let mut to_delete = (locals.len() as f64 * fraction) as usize;
+    if let Some(max_count) = max_count {
+        to_delete = to_delete.max(locals.len().saturating_sub(max_count));
+    }
 
     let mut scan = VecMutScan::new(&mut locals);
 
@@ -109,10 +112,7 @@ pub fn reduce_locals<'a>(
 pub fn reduce_mids(mut ctx: partial!(Context, mut ClauseAllocP, mut ClauseDbP)) {
     dedup_and_mark_by_tier(ctx.borrow(), Tier::Mid);
 
-    let mut mids = replace(
-        &mut ctx.part_mut(ClauseDbP).by_tier[Tier::Mid as usize],
-        vec![],
-    );
+    let mut mids = std::mem::take(&mut ctx.part_mut(ClauseDbP).by_tier[Tier::Mid as usize]);
 
     mids.retain(|&cref| {
         let header = ctx.part_mut(ClauseAllocP).header_mut(cref);
@@ -1,9 +1,44 @@
 //! Solver configuration.
+use serde::{Deserialize, Serialize};
+
 use varisat_internal_macros::{ConfigUpdate, DocDefault};
 
+/// Policy deciding when the search is restarted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestartStrategy {
+    /// Restart intervals follow the Luby sequence scaled by the restart interval.
+    Luby,
+    /// Restart intervals start at the restart interval and grow by the interval multiplier.
+    Geometric,
+    /// Restart after every restart interval conflicts.
+    Fixed,
+    /// Never restart.
+    None,
+}
+
+/// Heuristic selecting the variable of each decision.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecisionHeuristic {
+    /// Branch on the variable with the highest decaying activity.
+    Vsids,
+    /// Branch on the most recently bumped unassigned variable.
+    Vmtf,
+}
+
 /// Configurable parameters used during solving.
-#[derive(DocDefault, ConfigUpdate)]
+///
+/// The solver does not use any randomness. Solving the same sequence of inputs with the same
+/// configuration always performs the same steps and produces the same results, so there is no
+/// random seed to configure.
+#[derive(Clone, DocDefault, ConfigUpdate, Serialize, Deserialize)]
 pub struct SolverConfig {
+    /// Heuristic selecting the variable of each decision.
+    ///
+    /// One of `Vsids` or `Vmtf`.
+    ///
+    /// [default: DecisionHeuristic::Vsids]
+    pub decision_heuristic: DecisionHeuristic,
+
     /// Multiplicative decay for the VSIDS decision heuristic.
     ///
     /// [default: 0.95]  [range: 0.5..1.0]
@@ -19,13 +54,80 @@ pub struct SolverConfig {
     /// [default: 15000]  [range: 1..]
     pub reduce_locals_interval: u64,
 
+    /// Fraction of local clauses that are deleted by a local clause reduction.
+    ///
+    /// Lower values keep more learned clauses.
+    ///
+    /// [default: 0.5]  [range: 0.0..=1.0]
+    pub reduce_locals_fraction: f64,
+
+    /// Maximal number of local learned clauses to keep.
+    ///
+    /// When there are more local clauses, a local clause reduction is performed early and deletes
+    /// at least enough clauses to get below this limit. Core and mid tier clauses are not counted.
+    /// Lower values reduce memory usage but can slow down solving. `None` disables the limit.
+    ///
+    /// [default: None]
+    pub max_local_clauses: Option<usize>,
+
+    /// Maximal glue level of learned clauses that are kept permanently.
+    ///
+    /// Set to 0 to allow deletion of all learned clauses.
+    ///
+    /// [default: 2]  [range: 0..]
+    pub core_glue_limit: usize,
+
+    /// Maximal glue level of learned clauses that are protected from deletion while active.
+    ///
+    /// Learned clauses with a glue level above the core limit and up to this limit are only
+    /// subject to deletion after they were not involved in a conflict since the last mid clause
+    /// reduction.
+    ///
+    /// [default: 6]  [range: 0..]
+    pub mid_glue_limit: usize,
+
     /// Number of conflicts between mid clause reductions.
     ///
     /// [default: 10000]  [range: 1..]
     pub reduce_mids_interval: u64,
 
-    /// Scaling factor for luby sequence based restarts (number of conflicts).
+    /// Policy deciding when the search is restarted.
+    ///
+    /// One of `Luby`, `Geometric`, `Fixed` or `None`.
+    ///
+    /// [default: RestartStrategy::Luby]
+    pub restart_strategy: RestartStrategy,
+
+    /// Base interval for restarts (number of conflicts).
+    ///
+    /// This scales the Luby sequence, is the first interval for geometric restarts and every
+    /// interval for fixed restarts.
+    ///
+    /// Previously named `luby_restart_interval_scale`, which is still accepted.
     ///
     /// [default: 128]  [range: 1..]
-    pub luby_restart_interval_scale: u64,
+    #[serde(alias = "luby_restart_interval_scale")]
+    pub restart_interval: u64,
+
+    /// Growth factor of the interval for geometric restarts.
+    ///
+    /// [default: 1.5]  [range: 1.0..]
+    pub restart_interval_multiplier: f64,
+
+    /// Number of conflicts between progress reports.
+    ///
+    /// Progress is logged and reported to the progress callback at this interval.
+    ///
+    /// [default: 5000]  [range: 1..]
+    pub progress_interval: u64,
+
+    /// Number of bits used for clause hashes in native proofs.
+    ///
+    /// By default the number of bits is adjusted to the number of clauses, which requires
+    /// additional proof steps. Setting a fixed number disables these adjustments and makes the
+    /// proof independent of the clause count. Small values increase the number of hash collisions,
+    /// which makes checking the proof slower, but do not affect the validity of the proof.
+    ///
+    /// [default: None]  [range: 1..=64]
+    pub proof_hash_bits: Option<u32>,
 }
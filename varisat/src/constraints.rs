@@ -0,0 +1,68 @@
+//! CNF encodings of cardinality constraints.
+use varisat_formula::{ExtendFormula, Lit, Var};
+
+/// Maximal number of literals of an at-most-one constraint that is encoded pairwise.
+const MAX_PAIRWISE_LEN: usize = 5;
+
+/// Add clauses that allow at most one of the given literals to be true.
+///
+/// Short constraints use the pairwise encoding, longer constraints use the sequential counter
+/// encoding of [`add_at_most_k`]. Returns the auxiliary variables allocated using
+/// [`ExtendFormula::new_var`].
+pub fn add_at_most_one(formula: &mut impl ExtendFormula, lits: &[Lit]) -> Vec<Var> {
+    if lits.len() > MAX_PAIRWISE_LEN {
+        return add_at_most_k(formula, lits, 1);
+    }
+
+    for (index, &lit) in lits.iter().enumerate() {
+        for &other_lit in lits[index + 1..].iter() {
+            formula.add_clause(&[!lit, !other_lit]);
+        }
+    }
+
+    vec![]
+}
+
+/// Add clauses that allow at most `k` of the given literals to be true.
+///
+/// Literals that occur multiple times are counted multiple times. This uses the sequential counter
+/// encoding described by Carsten Sinz in ["Towards an Optimal CNF Encoding of Boolean Cardinality
+/// Constraints"](https://doi.org/10.1007/11564751_73). Returns the auxiliary variables allocated
+/// using [`ExtendFormula::new_var`].
+pub fn add_at_most_k(formula: &mut impl ExtendFormula, lits: &[Lit], k: usize) -> Vec<Var> {
+    if k >= lits.len() {
+        return vec![];
+    }
+
+    if k == 0 {
+        for &lit in lits {
+            formula.add_clause(&[!lit]);
+        }
+        return vec![];
+    }
+
+    // counters[i * k + j] is true if at least j + 1 of the first i + 1 literals are true.
+    let counters: Vec<Var> = (0..(lits.len() - 1) * k)
+        .map(|_| formula.new_var())
+        .collect();
+    let counter = |i: usize, j: usize| counters[i * k + j].positive();
+
+    formula.add_clause(&[!lits[0], counter(0, 0)]);
+    for j in 1..k {
+        formula.add_clause(&[!counter(0, j)]);
+    }
+
+    for (i, &lit) in lits.iter().enumerate().take(lits.len() - 1).skip(1) {
+        formula.add_clause(&[!lit, counter(i, 0)]);
+        formula.add_clause(&[!counter(i - 1, 0), counter(i, 0)]);
+        for j in 1..k {
+            formula.add_clause(&[!lit, !counter(i - 1, j - 1), counter(i, j)]);
+            formula.add_clause(&[!counter(i - 1, j), counter(i, j)]);
+        }
+        formula.add_clause(&[!lit, !counter(i - 1, k - 1)]);
+    }
+
+    formula.add_clause(&[!lits[lits.len() - 1], !counter(lits.len() - 2, k - 1)]);
+
+    counters
+}
@@ -3,20 +3,22 @@
 //! This module defines the `Context` data structure which holds all data used by the solver. It
 //! also contains global notification functions that likely need to be extended when new parts are
 //! added to the solver.
-use partial_ref::{part, partial, PartialRef, PartialRefTarget};
+use partial_ref::{part, partial, IntoPartialRefMut, PartialRef, PartialRefTarget};
 
 use crate::{
     analyze_conflict::AnalyzeConflict,
     assumptions::Assumptions,
     binary::BinaryClauses,
+    callbacks::Callbacks,
     clause::{ClauseActivity, ClauseAlloc, ClauseDb},
     config::{SolverConfig, SolverConfigUpdate},
-    decision::vsids::Vsids,
+    decision::{self, non_decision::NonDecisionQueue, vmtf::Vmtf, vsids::Vsids},
     model::Model,
     proof::Proof,
     prop::{Assignment, ImplGraph, Trail, Watchlists},
     schedule::Schedule,
     state::SolverState,
+    stats::LearnStats,
     tmp::{TmpData, TmpFlags},
     variables::Variables,
 };
@@ -28,12 +30,15 @@ pub mod parts {
     part!(pub AnalyzeConflictP: AnalyzeConflict);
     part!(pub AssignmentP: Assignment);
     part!(pub BinaryClausesP: BinaryClauses);
+    part!(pub CallbacksP<'a>: Callbacks<'a>);
     part!(pub ClauseActivityP: ClauseActivity);
     part!(pub ClauseAllocP: ClauseAlloc);
     part!(pub ClauseDbP: ClauseDb);
     part!(pub ImplGraphP: ImplGraph);
+    part!(pub LearnStatsP: LearnStats);
     part!(pub AssumptionsP: Assumptions);
     part!(pub ModelP: Model);
+    part!(pub NonDecisionP: NonDecisionQueue);
     part!(pub ProofP<'a>: Proof<'a>);
     part!(pub ScheduleP: Schedule);
     part!(pub SolverConfigP: SolverConfig);
@@ -42,6 +47,7 @@ pub mod parts {
     part!(pub TmpFlagsP: TmpFlags);
     part!(pub TrailP: Trail);
     part!(pub VariablesP: Variables);
+    part!(pub VmtfP: Vmtf);
     part!(pub VsidsP: Vsids);
     part!(pub WatchlistsP: Watchlists);
 }
@@ -62,6 +68,8 @@ pub struct Context<'a> {
     pub assignment: Assignment,
     #[part(BinaryClausesP)]
     pub binary_clauses: BinaryClauses,
+    #[part(CallbacksP<'a>)]
+    pub callbacks: Callbacks<'a>,
     #[part(ClauseActivityP)]
     pub clause_activity: ClauseActivity,
     #[part(ClauseAllocP)]
@@ -70,10 +78,14 @@ pub struct Context<'a> {
     pub clause_db: ClauseDb,
     #[part(ImplGraphP)]
     pub impl_graph: ImplGraph,
+    #[part(LearnStatsP)]
+    pub learn_stats: LearnStats,
     #[part(AssumptionsP)]
     pub assumptions: Assumptions,
     #[part(ModelP)]
     pub model: Model,
+    #[part(NonDecisionP)]
+    pub non_decision: NonDecisionQueue,
     #[part(ProofP<'a>)]
     pub proof: Proof<'a>,
     #[part(ScheduleP)]
@@ -90,6 +102,8 @@ pub struct Context<'a> {
     pub trail: Trail,
     #[part(VariablesP)]
     pub variables: Variables,
+    #[part(VmtfP)]
+    pub vmtf: Vmtf,
     #[part(VsidsP)]
     pub vsids: Vsids,
     #[part(WatchlistsP)]
@@ -104,7 +118,9 @@ pub fn set_var_count(
         mut AssignmentP,
         mut BinaryClausesP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut TmpFlagsP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
     ),
@@ -114,18 +130,150 @@ pub fn set_var_count(
     ctx.part_mut(AssignmentP).set_var_count(count);
     ctx.part_mut(BinaryClausesP).set_var_count(count);
     ctx.part_mut(ImplGraphP).set_var_count(count);
+    ctx.part_mut(NonDecisionP).set_var_count(count);
     ctx.part_mut(TmpFlagsP).set_var_count(count);
+    ctx.part_mut(VmtfP).set_var_count(count);
     ctx.part_mut(VsidsP).set_var_count(count);
     ctx.part_mut(WatchlistsP).set_var_count(count);
 }
 
+/// Reserve capacity in the per variable structures for the given variable count.
+pub fn reserve_var_count(
+    mut ctx: partial!(
+        Context,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut BinaryClausesP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut TmpFlagsP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+    ),
+    count: usize,
+) {
+    ctx.part_mut(AnalyzeConflictP).reserve_var_count(count);
+    ctx.part_mut(AssignmentP).reserve_var_count(count);
+    ctx.part_mut(BinaryClausesP).reserve_var_count(count);
+    ctx.part_mut(ImplGraphP).reserve_var_count(count);
+    ctx.part_mut(NonDecisionP).reserve_var_count(count);
+    ctx.part_mut(TmpFlagsP).reserve_var_count(count);
+    ctx.part_mut(VmtfP).reserve_var_count(count);
+    ctx.part_mut(VsidsP).reserve_var_count(count);
+    ctx.part_mut(WatchlistsP).reserve_var_count(count);
+}
+
 /// The solver configuration has changed.
-pub fn config_changed(
-    mut ctx: partial!(Context, mut VsidsP, mut ClauseActivityP, SolverConfigP),
-    _update: &SolverConfigUpdate,
+pub fn config_changed<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut ClauseActivityP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut VmtfP,
+        mut VsidsP,
+        AssignmentP,
+        SolverConfigP,
+        VariablesP,
+    ),
+    update: &SolverConfigUpdate,
 ) {
     let (config, mut ctx) = ctx.split_part(SolverConfigP);
     ctx.part_mut(VsidsP).set_decay(config.vsids_decay);
     ctx.part_mut(ClauseActivityP)
         .set_decay(config.clause_activity_decay);
+    ctx.part_mut(ProofP)
+        .set_fixed_hash_bits(config.proof_hash_bits);
+
+    if update.decision_heuristic.is_some() {
+        decision::heuristic_changed(ctx.borrow());
+    }
+}
+
+/// Estimated number of bytes used by the major data structures of the solver.
+///
+/// This sums the capacities of the buffers used for clauses, watchlists and variables.
+pub fn memory_usage(
+    ctx: partial!(
+        Context,
+        AssignmentP,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        ImplGraphP,
+        NonDecisionP,
+        TrailP,
+        VariablesP,
+        VmtfP,
+        VsidsP,
+        WatchlistsP,
+    ),
+) -> usize {
+    ctx.part(AssignmentP).memory_usage()
+        + ctx.part(BinaryClausesP).memory_usage()
+        + ctx.part(ClauseAllocP).memory_usage()
+        + ctx.part(ClauseDbP).memory_usage()
+        + ctx.part(ImplGraphP).memory_usage()
+        + ctx.part(NonDecisionP).memory_usage()
+        + ctx.part(TrailP).memory_usage()
+        + ctx.part(VariablesP).memory_usage()
+        + ctx.part(VmtfP).memory_usage()
+        + ctx.part(VsidsP).memory_usage()
+        + ctx.part(WatchlistsP).memory_usage()
+}
+
+/// Update the peak memory usage with the current memory usage.
+pub fn update_peak_memory_usage(
+    mut ctx: partial!(
+        Context,
+        mut SolverStateP,
+        AssignmentP,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        ImplGraphP,
+        NonDecisionP,
+        TrailP,
+        VariablesP,
+        VmtfP,
+        VsidsP,
+        WatchlistsP,
+    ),
+) {
+    let current = memory_usage(ctx.borrow());
+    let state = ctx.part_mut(SolverStateP);
+    state.peak_memory_usage = state.peak_memory_usage.max(current);
+}
+
+/// Reset the solver to its initial state, keeping the configuration.
+///
+/// Every part is cleared in place, so all buffers keep their allocated capacity for reuse. Parts
+/// without buffers are replaced by their default values.
+pub fn reset(ctx: &mut Context) {
+    ctx.analyze_conflict.reset();
+    ctx.assignment.reset();
+    ctx.binary_clauses.reset();
+    ctx.callbacks.reset();
+    ctx.clause_activity = Default::default();
+    ctx.clause_alloc.clear();
+    ctx.clause_db.clear();
+    ctx.impl_graph.reset();
+    ctx.learn_stats.reset();
+    ctx.assumptions.reset();
+    ctx.model.reset();
+    ctx.non_decision.reset();
+    ctx.proof = Default::default();
+    ctx.schedule = Default::default();
+    ctx.solver_state = Default::default();
+    ctx.tmp_data.reset();
+    ctx.tmp_flags.reset();
+    ctx.trail.reset();
+    ctx.variables.reset();
+    ctx.vmtf.reset();
+    ctx.vsids.reset();
+    ctx.watchlists.reset();
+
+    let mut ctx = ctx.into_partial_ref_mut();
+    config_changed(ctx.borrow(), &SolverConfigUpdate::new());
 }
@@ -5,30 +5,51 @@ use partial_ref::{partial, PartialRef};
 use varisat_formula::Var;
 
 use crate::{
+    config::DecisionHeuristic,
     context::{parts::*, Context},
     prop::{enqueue_assignment, Reason},
 };
 
+pub mod non_decision;
+pub mod vmtf;
 pub mod vsids;
 
 /// Make a decision and enqueue it.
 ///
+/// Variables that are not enabled for decisions are only decided when all other variables are
+/// assigned. They are kept in a separate queue, as the decision heuristics don't contain them.
+///
 /// Returns `false` if no decision was made because all variables are assigned.
 pub fn make_decision(
     mut ctx: partial!(
         Context,
         mut AssignmentP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut TrailP,
-        mut VsidsP
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP,
+        VariablesP,
     ),
 ) -> bool {
-    let (vsids, mut ctx) = ctx.split_part_mut(VsidsP);
+    let decision_var = {
+        let (assignment, mut ctx) = ctx.split_part(AssignmentP);
+        let is_unassigned = |&var: &Var| assignment.var_value(var).is_none();
+
+        let decision_var = match ctx.part(SolverConfigP).decision_heuristic {
+            DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).find(is_unassigned),
+            DecisionHeuristic::Vmtf => ctx.part_mut(VmtfP).find(is_unassigned),
+        };
 
-    if let Some(decision_var) = vsids.find(|&var| ctx.part(AssignmentP).var_value(var).is_none()) {
+        decision_var.or_else(|| ctx.part_mut(NonDecisionP).find(is_unassigned))
+    };
+
+    if let Some(decision_var) = decision_var {
         let decision = decision_var.lit(ctx.part(AssignmentP).last_var_value(decision_var));
 
         ctx.part_mut(TrailP).new_decision_level();
+        ctx.part_mut(TrailP).count_decision();
 
         enqueue_assignment(ctx.borrow(), decision, Reason::Unit);
 
@@ -39,20 +60,197 @@ pub fn make_decision(
 }
 
 /// Make a variable available for decisions.
-pub fn make_available(mut ctx: partial!(Context, mut VsidsP), var: Var) {
-    ctx.part_mut(VsidsP).make_available(var);
+pub fn make_available(
+    mut ctx: partial!(
+        Context,
+        mut NonDecisionP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP
+    ),
+    var: Var,
+) {
+    match ctx.part(SolverConfigP).decision_heuristic {
+        DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).make_available(var),
+        DecisionHeuristic::Vmtf => ctx.part_mut(VmtfP).make_available(var),
+    }
+    ctx.part_mut(NonDecisionP).make_available(var);
 }
 
 /// Initialize decision heuristics for a new variable.
-pub fn initialize_var(mut ctx: partial!(Context, mut VsidsP), var: Var, available: bool) {
-    ctx.part_mut(VsidsP).reset(var);
+pub fn initialize_var(
+    mut ctx: partial!(
+        Context,
+        mut NonDecisionP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP
+    ),
+    var: Var,
+    available: bool,
+    decision: bool,
+) {
+    match ctx.part(SolverConfigP).decision_heuristic {
+        DecisionHeuristic::Vsids => {
+            let vsids = ctx.part_mut(VsidsP);
+            vsids.reset_activity(var);
+            vsids.set_decision(var, decision);
+        }
+        DecisionHeuristic::Vmtf => {
+            if decision {
+                ctx.part_mut(VmtfP).enqueue(var);
+            }
+        }
+    }
+
+    if !decision {
+        ctx.part_mut(NonDecisionP).enqueue(var);
+    }
 
     if available {
         make_available(ctx.borrow(), var);
     }
 }
 
+/// Bump a variable involved in a conflict.
+pub fn bump_var(mut ctx: partial!(Context, mut VmtfP, mut VsidsP, SolverConfigP), var: Var) {
+    match ctx.part(SolverConfigP).decision_heuristic {
+        DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).bump(var),
+        DecisionHeuristic::Vmtf => ctx.part_mut(VmtfP).bump(var),
+    }
+}
+
+/// Decay variable activities after a conflict.
+pub fn decay_var_activities(mut ctx: partial!(Context, mut VmtfP, mut VsidsP, SolverConfigP,)) {
+    if ctx.part(SolverConfigP).decision_heuristic == DecisionHeuristic::Vsids {
+        ctx.part_mut(VsidsP).decay();
+    }
+}
+
+/// Rebuild the state of the decision heuristic selected by the configuration.
+///
+/// Only the selected heuristic is updated during solving, so this needs to be called whenever the
+/// selection could have changed.
+pub fn heuristic_changed(
+    mut ctx: partial!(
+        Context,
+        mut NonDecisionP,
+        mut VmtfP,
+        mut VsidsP,
+        AssignmentP,
+        SolverConfigP,
+        VariablesP,
+    ),
+) {
+    ctx.part_mut(VmtfP).clear();
+    ctx.part_mut(VsidsP).clear();
+
+    let heuristic = ctx.part(SolverConfigP).decision_heuristic;
+
+    for index in 0..ctx.part(VariablesP).solver_watermark() {
+        let var = Var::from_index(index);
+        let variables = ctx.part(VariablesP);
+        let global = match variables.global_from_solver().get(var) {
+            Some(global) => global,
+            None => continue,
+        };
+        let decision = variables.var_data_global(global).decision;
+
+        match heuristic {
+            DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).set_decision(var, decision),
+            DecisionHeuristic::Vmtf => {
+                if decision {
+                    ctx.part_mut(VmtfP).enqueue(var);
+                }
+            }
+        }
+
+        if ctx.part(AssignmentP).var_value(var).is_none() {
+            make_available(ctx.borrow(), var);
+        }
+    }
+}
+
+/// Set the preferred phase of a global variable.
+///
+/// This overwrites the saved phase of the corresponding solver variable, if present. The phase is
+/// also remembered to initialize the saved phase whenever a new solver variable is allocated for
+/// the global variable. Passing `None` restores the default phase.
+pub fn set_phase(
+    mut ctx: partial!(Context, mut AssignmentP, mut VariablesP),
+    global: Var,
+    phase: Option<bool>,
+) {
+    let variables = ctx.part_mut(VariablesP);
+    variables.var_data_global_mut(global).phase = phase;
+
+    if let Some(solver) = variables.solver_from_global().get(global) {
+        ctx.part_mut(AssignmentP)
+            .set_saved_phase(solver, phase.unwrap_or(false));
+    }
+}
+
+/// Set whether a global variable can be chosen for decisions.
+///
+/// This is remembered for the global variable and applied to the corresponding solver variable, if
+/// present.
+pub fn set_decision_var(
+    mut ctx: partial!(
+        Context,
+        mut NonDecisionP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        AssignmentP,
+        SolverConfigP,
+    ),
+    global: Var,
+    decision: bool,
+) {
+    let variables = ctx.part_mut(VariablesP);
+    variables.var_data_global_mut(global).decision = decision;
+
+    let solver = match variables.solver_from_global().get(global) {
+        Some(solver) => solver,
+        None => return,
+    };
+
+    match ctx.part(SolverConfigP).decision_heuristic {
+        DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).set_decision(solver, decision),
+        DecisionHeuristic::Vmtf => {
+            if decision {
+                ctx.part_mut(VmtfP).enqueue(solver);
+            } else {
+                ctx.part_mut(VmtfP).dequeue(solver);
+            }
+        }
+    }
+
+    if decision {
+        ctx.part_mut(NonDecisionP).dequeue(solver);
+    } else {
+        ctx.part_mut(NonDecisionP).enqueue(solver);
+    }
+
+    if decision && ctx.part(AssignmentP).var_value(solver).is_none() {
+        make_available(ctx.borrow(), solver);
+    }
+}
+
 /// Remove a variable from the decision heuristics.
-pub fn remove_var(mut ctx: partial!(Context, mut VsidsP), var: Var) {
-    ctx.part_mut(VsidsP).make_unavailable(var);
+pub fn remove_var(
+    mut ctx: partial!(
+        Context,
+        mut NonDecisionP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP
+    ),
+    var: Var,
+) {
+    match ctx.part(SolverConfigP).decision_heuristic {
+        DecisionHeuristic::Vsids => ctx.part_mut(VsidsP).make_unavailable(var),
+        DecisionHeuristic::Vmtf => ctx.part_mut(VmtfP).dequeue(var),
+    }
+    ctx.part_mut(NonDecisionP).dequeue(var);
 }
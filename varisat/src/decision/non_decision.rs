@@ -0,0 +1,102 @@
+//! Queue of the variables that are disabled for decisions.
+//!
+//! Variables disabled for decisions are not part of the decision heuristics. To still find a
+//! complete assignment, they are decided after all other variables are assigned, in the order of
+//! this queue.
+
+use std::mem::size_of;
+
+use varisat_formula::Var;
+
+/// Variables disabled for decisions in the order they are decided.
+///
+/// Like the VMTF heuristic, this keeps a search position so that all queued variables before it are
+/// assigned. When a queued variable is unassigned, the search position is moved back to it if it is
+/// further to the front.
+#[derive(Default)]
+pub struct NonDecisionQueue {
+    /// Queued variables, removed entries are `None`.
+    queue: Vec<Option<Var>>,
+    /// The position in the queue for each variable.
+    position: Vec<Option<usize>>,
+    /// All queued variables before this position are assigned.
+    search: usize,
+    /// Number of removed entries in the queue.
+    removed: usize,
+}
+
+impl NonDecisionQueue {
+    /// Estimated number of bytes used by the queue.
+    pub fn memory_usage(&self) -> usize {
+        self.queue.capacity() * size_of::<Option<Var>>()
+            + self.position.capacity() * size_of::<Option<usize>>()
+    }
+
+    /// Update structures for a new variable count.
+    pub fn set_var_count(&mut self, count: usize) {
+        self.position.resize(count, None);
+    }
+
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.position
+            .reserve(count.saturating_sub(self.position.len()));
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.queue.clear();
+        self.position.clear();
+        self.search = 0;
+        self.removed = 0;
+    }
+
+    /// Insert a variable at the end of the queue if not already present.
+    pub fn enqueue(&mut self, var: Var) {
+        if self.position[var.index()].is_none() {
+            let position = self.queue.len();
+            self.position[var.index()] = Some(position);
+            self.queue.push(Some(var));
+            self.search = self.search.min(position);
+        }
+    }
+
+    /// Remove a variable from the queue if present.
+    pub fn dequeue(&mut self, var: Var) {
+        if let Some(position) = self.position[var.index()].take() {
+            self.queue[position] = None;
+            self.removed += 1;
+            if self.removed * 2 > self.queue.len() {
+                self.compact();
+            }
+        }
+    }
+
+    /// Remove the entries of removed variables from the queue.
+    fn compact(&mut self) {
+        self.queue.retain(|entry| entry.is_some());
+        for (position, var) in self.queue.iter().flatten().enumerate() {
+            self.position[var.index()] = Some(position);
+        }
+        self.removed = 0;
+        self.search = 0;
+    }
+
+    /// Update the search position for an unassigned variable.
+    pub fn make_available(&mut self, var: Var) {
+        if let Some(position) = self.position[var.index()] {
+            self.search = self.search.min(position);
+        }
+    }
+
+    /// Find the first unassigned variable in the queue.
+    pub fn find(&mut self, is_unassigned: impl Fn(&Var) -> bool) -> Option<Var> {
+        while let Some(&entry) = self.queue.get(self.search) {
+            if let Some(var) = entry.filter(&is_unassigned) {
+                return Some(var);
+            }
+            self.search += 1;
+        }
+        None
+    }
+}
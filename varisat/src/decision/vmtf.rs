@@ -0,0 +1,239 @@
+//! The VMTF branching heuristic.
+//!
+//! The VMTF (Variable Move To Front) branching heuristic keeps all variables in a queue. For each
+//! conflict the bumped variables are moved to the front of the queue. When a decision is made, it
+//! branches on the unassigned variable closest to the front of the queue.
+//!
+//! Varisat bumps the same variables as for the VSIDS heuristic, i.e. all variables in the conflict
+//! clause and all variables resolved on during conflict analysis.
+
+use std::mem::size_of;
+
+use varisat_formula::Var;
+
+/// Links of a variable in the queue.
+#[derive(Copy, Clone, Default)]
+struct Link {
+    /// The previously bumped variable, further away from the front.
+    prev: Option<Var>,
+    /// The next bumped variable, closer to the front.
+    next: Option<Var>,
+}
+
+/// The VMTF branching heuristic.
+///
+/// The queue is a doubly linked list ordered by the time each variable was last bumped, with the
+/// most recently bumped variable at the end of the list. To avoid scanning assigned variables at
+/// the front of the queue for every decision, a search position is maintained so that all
+/// variables after it are assigned. When a variable is unassigned, the search position is moved to
+/// it if it is closer to the front.
+#[derive(Default)]
+pub struct Vmtf {
+    /// Links of each variable.
+    links: Vec<Link>,
+    /// Time of the last bump of each variable, increasing towards the front.
+    stamps: Vec<u64>,
+    /// Whether each variable is in the queue.
+    queued: Vec<bool>,
+    /// The least recently bumped variable.
+    first: Option<Var>,
+    /// The most recently bumped variable.
+    last: Option<Var>,
+    /// All variables after this one are assigned.
+    search: Option<Var>,
+    /// Stamp for the next bumped variable.
+    next_stamp: u64,
+}
+
+impl Vmtf {
+    /// Estimated number of bytes used by the VMTF heuristic.
+    pub fn memory_usage(&self) -> usize {
+        self.links.capacity() * size_of::<Link>()
+            + self.stamps.capacity() * size_of::<u64>()
+            + self.queued.capacity() * size_of::<bool>()
+    }
+
+    /// Update structures for a new variable count.
+    pub fn set_var_count(&mut self, count: usize) {
+        self.links.resize(count, Link::default());
+        self.stamps.resize(count, 0);
+        self.queued.resize(count, false);
+    }
+
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.links.reserve(count.saturating_sub(self.links.len()));
+        self.stamps.reserve(count.saturating_sub(self.stamps.len()));
+        self.queued.reserve(count.saturating_sub(self.queued.len()));
+    }
+
+    /// Remove all variables from the queue.
+    pub fn clear(&mut self) {
+        for queued in self.queued.iter_mut() {
+            *queued = false;
+        }
+        self.first = None;
+        self.last = None;
+        self.search = None;
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity.
+    ///
+    /// Unlike [`clear`](Vmtf::clear) this also removes all variables and bump times.
+    pub fn reset(&mut self) {
+        self.links.clear();
+        self.stamps.clear();
+        self.queued.clear();
+        self.first = None;
+        self.last = None;
+        self.search = None;
+        self.next_stamp = 0;
+    }
+
+    /// Insert a variable at the front of the queue if not already present.
+    ///
+    /// This does not change the search position, so the variable needs to be made available
+    /// afterwards if it is unassigned.
+    pub fn enqueue(&mut self, var: Var) {
+        if !self.queued[var.index()] {
+            self.queued[var.index()] = true;
+            self.push_front(var);
+        }
+    }
+
+    /// Remove a variable from the queue if present.
+    pub fn dequeue(&mut self, var: Var) {
+        if self.queued[var.index()] {
+            self.queued[var.index()] = false;
+            let link = self.links[var.index()];
+            if self.search == Some(var) {
+                self.search = link.prev.or(link.next);
+            }
+            self.unlink(var);
+        }
+    }
+
+    /// Bump a variable by moving it to the front of the queue.
+    ///
+    /// This does not change the search position, so the variable has to be assigned.
+    pub fn bump(&mut self, var: Var) {
+        if self.queued[var.index()] && self.last != Some(var) {
+            if self.search == Some(var) {
+                self.search = self.links[var.index()]
+                    .prev
+                    .or(self.links[var.index()].next);
+            }
+            self.unlink(var);
+            self.push_front(var);
+        }
+    }
+
+    /// Update the search position for an unassigned variable.
+    pub fn make_available(&mut self, var: Var) {
+        if !self.queued[var.index()] {
+            return;
+        }
+        let closer = match self.search {
+            Some(search) => self.stamps[var.index()] > self.stamps[search.index()],
+            None => true,
+        };
+        if closer {
+            self.search = Some(var);
+        }
+    }
+
+    /// Find the variable closest to the front of the queue that satisfies the predicate.
+    ///
+    /// The predicate is used to skip assigned variables. Skipped variables have to stay assigned
+    /// until they are made available again.
+    pub fn find(&mut self, mut predicate: impl FnMut(&Var) -> bool) -> Option<Var> {
+        let mut current = self.search;
+        while let Some(var) = current {
+            if predicate(&var) {
+                break;
+            }
+            current = self.links[var.index()].prev;
+        }
+        self.search = current;
+        current
+    }
+
+    /// Append a variable that is not linked to the end of the list.
+    fn push_front(&mut self, var: Var) {
+        self.stamps[var.index()] = self.next_stamp;
+        self.next_stamp += 1;
+
+        self.links[var.index()] = Link {
+            prev: self.last,
+            next: None,
+        };
+        match self.last {
+            Some(last) => self.links[last.index()].next = Some(var),
+            None => self.first = Some(var),
+        }
+        self.last = Some(var);
+    }
+
+    /// Remove a variable from the list without updating the search position.
+    fn unlink(&mut self, var: Var) {
+        let link = self.links[var.index()];
+        match link.prev {
+            Some(prev) => self.links[prev.index()].next = link.next,
+            None => self.first = link.next,
+        }
+        match link.next {
+            Some(next) => self.links[next.index()].prev = link.prev,
+            None => self.last = link.prev,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_order() {
+        let mut vmtf = Vmtf::default();
+        vmtf.set_var_count(8);
+
+        for i in 0..8 {
+            vmtf.enqueue(Var::from_index(i));
+        }
+
+        vmtf.bump(Var::from_index(2));
+        vmtf.bump(Var::from_index(5));
+        vmtf.dequeue(Var::from_index(7));
+
+        for i in 0..8 {
+            vmtf.make_available(Var::from_index(i));
+        }
+
+        let mut assigned = [false; 8];
+        let mut order = vec![];
+
+        while let Some(var) = vmtf.find(|var| !assigned[var.index()]) {
+            assigned[var.index()] = true;
+            order.push(var.index());
+        }
+
+        assert_eq!(order, vec![5, 2, 6, 4, 3, 1, 0]);
+
+        assigned[4] = false;
+        vmtf.make_available(Var::from_index(4));
+        assigned[0] = false;
+        vmtf.make_available(Var::from_index(0));
+
+        assert_eq!(
+            vmtf.find(|var| !assigned[var.index()]),
+            Some(Var::from_index(4))
+        );
+        assigned[4] = true;
+        assert_eq!(
+            vmtf.find(|var| !assigned[var.index()]),
+            Some(Var::from_index(0))
+        );
+        assigned[0] = true;
+        assert_eq!(vmtf.find(|var| !assigned[var.index()]), None);
+    }
+}
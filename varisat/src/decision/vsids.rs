@@ -12,6 +12,8 @@
 //! others) by bumping all variables in the conflict clause and all variables resolved on during
 //! conflict analysis.
 
+use std::mem::size_of;
+
 use ordered_float::OrderedFloat;
 
 use varisat_formula::Var;
@@ -32,6 +34,8 @@ pub struct Vsids {
     heap: Vec<Var>,
     /// The position in the binary heap for each variable.
     position: Vec<Option<usize>>,
+    /// Whether each variable can be inserted into the heap.
+    decision: Vec<bool>,
     /// The value to add on bumping.
     bump: f32,
     /// The inverse of the decay factor.
@@ -44,6 +48,7 @@ impl Default for Vsids {
             activity: vec![],
             heap: vec![],
             position: vec![],
+            decision: vec![],
             bump: 1.0,
             inv_decay: 1.0 / SolverConfig::default().vsids_decay,
         }
@@ -51,15 +56,34 @@ impl Default for Vsids {
 }
 
 impl Vsids {
+    /// Estimated number of bytes used by the VSIDS heuristic.
+    pub fn memory_usage(&self) -> usize {
+        self.activity.capacity() * size_of::<OrderedFloat<f32>>()
+            + self.heap.capacity() * size_of::<Var>()
+            + self.position.capacity() * size_of::<Option<usize>>()
+            + self.decision.capacity() * size_of::<bool>()
+    }
+
     /// Update structures for a new variable count.
     pub fn set_var_count(&mut self, count: usize) {
         self.activity.resize(count, OrderedFloat(0.0));
         self.position.resize(count, None);
+        self.decision.resize(count, true);
+    }
+
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.activity
+            .reserve(count.saturating_sub(self.activity.len()));
+        self.position
+            .reserve(count.saturating_sub(self.position.len()));
+        self.decision
+            .reserve(count.saturating_sub(self.decision.len()));
     }
 
     /// Rescale activities if any value exceeds this value.
     fn rescale_limit() -> f32 {
-        std::f32::MAX / 16.0
+        f32::MAX / 16.0
     }
 
     /// Change the decay factor.
@@ -69,6 +93,14 @@ impl Vsids {
         self.inv_decay = 1.0 / decay;
     }
 
+    /// Activity of a variable.
+    ///
+    /// This is divided by the current bump value to remove the scaling factor, so it equals the
+    /// activity of the naive implementation that bumps by one and decays all activities.
+    pub fn activity(&self, var: Var) -> f64 {
+        self.activity[var.index()].0 as f64 / self.bump as f64
+    }
+
     /// Bump a variable by increasing its activity.
     pub fn bump(&mut self, var: Var) {
         let rescale = {
@@ -104,11 +136,29 @@ impl Vsids {
     /// Reset the activity of an unavailable variable to zero.
     ///
     /// Panics if the variable is still available.
-    pub fn reset(&mut self, var: Var) {
+    pub fn reset_activity(&mut self, var: Var) {
         assert!(self.position[var.index()].is_none());
         self.activity[var.index()] = OrderedFloat(0.0);
     }
 
+    /// Remove all variables from the heap.
+    pub fn clear(&mut self) {
+        for var in self.heap.drain(..) {
+            self.position[var.index()] = None;
+        }
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity and the decay factor.
+    ///
+    /// Unlike [`clear`](Vsids::clear) this also removes all variables and activities.
+    pub fn reset(&mut self) {
+        self.activity.clear();
+        self.heap.clear();
+        self.position.clear();
+        self.decision.clear();
+        self.bump = 1.0;
+    }
+
     /// Remove a variable from the heap if present.
     pub fn make_unavailable(&mut self, var: Var) {
         if let Some(position) = self.position[var.index()] {
@@ -122,9 +172,19 @@ impl Vsids {
         }
     }
 
-    /// Insert a variable into the heap if not already present.
+    /// Set whether a variable can be chosen for decisions.
+    ///
+    /// Disabling decisions removes the variable from the heap and keeps it from being inserted.
+    pub fn set_decision(&mut self, var: Var, decision: bool) {
+        self.decision[var.index()] = decision;
+        if !decision {
+            self.make_unavailable(var);
+        }
+    }
+
+    /// Insert a variable into the heap if not already present and enabled for decisions.
     pub fn make_available(&mut self, var: Var) {
-        if self.position[var.index()].is_none() {
+        if self.position[var.index()].is_none() && self.decision[var.index()] {
             let position = self.heap.len();
             self.position[var.index()] = Some(position);
             self.heap.push(var);
@@ -0,0 +1,216 @@
+//! Export of the formula held by the solver.
+use partial_ref::{partial, PartialRef};
+
+use varisat_formula::{CnfFormula, ExtendFormula, Lit, Var};
+
+use crate::{
+    clause::{db, Tier},
+    context::{parts::*, Context},
+    state::SatState,
+    variables::Variables,
+};
+
+/// User name for a global variable.
+///
+/// Global variables without a user name are named after their global index, offset by the user
+/// watermark, so they don't collide with user variables.
+fn export_global_var(variables: &Variables, global: Var) -> Var {
+    variables
+        .user_from_global()
+        .get(global)
+        .unwrap_or_else(|| Var::from_index(variables.user_watermark() + global.index()))
+}
+
+/// User name for a solver literal.
+///
+/// Uses the same naming as [`export_global_var`] for variables without a user name.
+pub fn export_solver_lit(variables: &Variables, lit: Lit) -> Lit {
+    lit.map_var(|solver_var| {
+        let global = variables
+            .global_from_solver()
+            .get(solver_var)
+            .expect("no existing global var for solver var");
+        export_global_var(variables, global)
+    })
+}
+
+/// Irredundant clauses currently held by the solver, using user variable names.
+///
+/// This contains the long irredundant clauses, all binary clauses and a unit clause for every
+/// variable fixed at the top level. Learned long clauses are not included. The result is
+/// equisatisfiable to the formula added to the solver, and contains an empty clause if the formula
+/// is known to be unsatisfiable.
+pub fn irredundant_clauses(
+    mut ctx: partial!(
+        Context,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        SolverStateP,
+        TrailP,
+        VariablesP,
+    ),
+) -> CnfFormula {
+    let (variables, mut ctx) = ctx.split_part(VariablesP);
+
+    let mut formula = CnfFormula::new();
+
+    if ctx.part(SolverStateP).sat_state == SatState::Unsat {
+        formula.add_clause(&[]);
+    }
+
+    for global_var in variables.global_var_iter() {
+        if let Some(value) = variables.var_data_global(global_var).unit {
+            formula.add_clause(&[export_global_var(variables, global_var).lit(value)]);
+        }
+    }
+
+    for &lit in ctx.part(TrailP).top_level_trail() {
+        let global_var = variables
+            .global_from_solver()
+            .get(lit.var())
+            .expect("no existing global var for solver var");
+        if variables.var_data_global(global_var).unit.is_none() {
+            formula.add_clause(&[export_solver_lit(variables, lit)]);
+        }
+    }
+
+    for lits in ctx.part(BinaryClausesP).clauses_iter() {
+        formula.add_clause(&[
+            export_solver_lit(variables, lits[0]),
+            export_solver_lit(variables, lits[1]),
+        ]);
+    }
+
+    add_long_clauses(ctx.borrow(), variables, &mut formula, |tier| {
+        tier == Tier::Irred
+    });
+
+    formula.set_var_count(variables.user_watermark());
+
+    formula
+}
+
+/// Number of clauses returned by [`irredundant_clauses`].
+///
+/// This uses the maintained clause counts and only needs to scan the variables for units.
+pub fn irredundant_clause_count(
+    ctx: partial!(
+        Context,
+        BinaryClausesP,
+        ClauseDbP,
+        SolverStateP,
+        TrailP,
+        VariablesP,
+    ),
+) -> usize {
+    let variables = ctx.part(VariablesP);
+
+    let empty_clause = (ctx.part(SolverStateP).sat_state == SatState::Unsat) as usize;
+
+    let units = variables
+        .global_var_iter()
+        .filter(|&global_var| variables.var_data_global(global_var).unit.is_some())
+        .count();
+
+    // Units on the trail are not yet stored in the variable data, see `irredundant_clauses`.
+    let trail_units = ctx
+        .part(TrailP)
+        .top_level_trail()
+        .iter()
+        .filter(|lit| {
+            let global_var = variables
+                .global_from_solver()
+                .get(lit.var())
+                .expect("no existing global var for solver var");
+            variables.var_data_global(global_var).unit.is_none()
+        })
+        .count();
+
+    empty_clause
+        + units
+        + trail_units
+        + ctx.part(BinaryClausesP).count()
+        + ctx.part(ClauseDbP).count_by_tier(Tier::Irred)
+}
+
+/// Literals fixed at the top level, using user variable names.
+///
+/// This contains the literals assigned at decision level zero, including those that were already
+/// removed from the trail during simplification. Literals of variables without a user name and
+/// literals fixed by pure literal elimination, which are not implied by the formula, are omitted.
+pub fn top_level_units(ctx: partial!(Context, TrailP, VariablesP)) -> Vec<Lit> {
+    let variables = ctx.part(VariablesP);
+
+    let mut units = vec![];
+
+    for global_var in variables.global_var_iter() {
+        let data = variables.var_data_global(global_var);
+        if let (Some(value), false) = (data.unit, data.pure) {
+            if let Some(user_var) = variables.user_from_global().get(global_var) {
+                units.push(user_var.lit(value));
+            }
+        }
+    }
+
+    for &lit in ctx.part(TrailP).top_level_trail() {
+        let global_var = variables
+            .global_from_solver()
+            .get(lit.var())
+            .expect("no existing global var for solver var");
+        let data = variables.var_data_global(global_var);
+        if data.unit.is_none() && !data.pure {
+            if let Some(user_var) = variables.user_from_global().get(global_var) {
+                units.push(user_var.lit(lit.is_positive()));
+            }
+        }
+    }
+
+    units
+}
+
+/// Learned long clauses currently held by the solver, using user variable names.
+///
+/// Learned binary clauses are not distinguished from irredundant binary clauses and thus are
+/// included in [`irredundant_clauses`] instead.
+pub fn learned_clauses(
+    mut ctx: partial!(Context, ClauseAllocP, ClauseDbP, VariablesP),
+) -> CnfFormula {
+    let (variables, mut ctx) = ctx.split_part(VariablesP);
+
+    let mut formula = CnfFormula::new();
+
+    add_long_clauses(ctx.borrow(), variables, &mut formula, |tier| {
+        tier != Tier::Irred
+    });
+
+    formula.set_var_count(variables.user_watermark());
+
+    formula
+}
+
+/// Add all long clauses of the selected tiers to a formula, using user variable names.
+fn add_long_clauses(
+    mut ctx: partial!(Context, ClauseAllocP, ClauseDbP),
+    variables: &Variables,
+    formula: &mut CnfFormula,
+    select_tier: impl Fn(Tier) -> bool,
+) {
+    let (alloc, mut ctx) = ctx.split_part(ClauseAllocP);
+
+    let mut user_lits = vec![];
+
+    for cref in db::clauses_iter(&ctx.borrow()) {
+        let clause = alloc.clause(cref);
+        if select_tier(clause.header().tier()) {
+            user_lits.clear();
+            user_lits.extend(
+                clause
+                    .lits()
+                    .iter()
+                    .map(|&lit| export_solver_lit(variables, lit)),
+            );
+            formula.add_clause(&user_lits);
+        }
+    }
+}
@@ -4,32 +4,56 @@
 //!
 //! In addition to this API documentation, Varisat comes with a [user manual].
 //!
+//! # WebAssembly
+//!
+//! The solver can be compiled for `wasm32-unknown-unknown`. It requires `std`, which this target
+//! provides, but there is no file system and there are no threads, so some features are not
+//! available there:
+//!
+//!   * Proofs and DIMACS output can only be written to in-memory targets like `Vec<u8>`.
+//!   * [`Solver::set_terminate`] needs another thread to set the flag. Use
+//!     [`Solver::set_terminate_callback`] or a conflict budget instead.
+//!   * The command line solver `varisat-cli` is not supported.
+//!
+//! Formulas can be parsed from strings using [`dimacs::DimacsParser::parse`], as shown in the
+//! `solve_str` example. The `varisat-formula` crate also works without `std`.
+//!
 //! [cdcl]: https://en.wikipedia.org/wiki/Conflict-Driven_Clause_Learning
 //! [cnf]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
 //! [user manual]: https://jix.github.io/varisat/manual/0.2.1/
 
 pub mod config;
+pub mod portfolio;
 pub mod solver;
 
 mod analyze_conflict;
 mod assumptions;
 mod binary;
+mod callbacks;
 mod cdcl;
 mod clause;
+mod constraints;
 mod context;
 mod decision;
+mod export;
 mod glue;
 mod load;
 mod model;
 mod proof;
 mod prop;
 mod schedule;
+mod simplify;
+mod snapshot;
 mod state;
+mod stats;
 mod tmp;
 mod unit_simplify;
 mod variables;
+mod xor;
 
-pub use solver::{ProofFormat, Solver};
+pub use portfolio::PortfolioSolver;
+pub use solver::{ProofFormat, SatState, Solver};
+pub use stats::SolverStats;
 pub use varisat_formula::{cnf, lit, CnfFormula, ExtendFormula, Lit, Var};
 
 pub mod dimacs {
@@ -40,7 +64,7 @@ pub mod dimacs {
 pub mod checker {
     //! Proof checker for Varisat proofs.
     pub use varisat_checker::{
-        CheckedProofStep, Checker, CheckerData, CheckerError, ProofProcessor,
-        ProofTranscriptProcessor, ProofTranscriptStep,
+        CheckedProofStep, Checker, CheckerData, CheckerError, CollectModel, ProofProcessor,
+        ProofTranscriptProcessor, ProofTranscriptStep, StepCounter, StepCounts, UnsatCore,
     };
 }
@@ -32,17 +32,22 @@ pub fn load_clause<'a>(
         mut ClauseAllocP,
         mut ClauseDbP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpDataP,
         mut TmpFlagsP,
         mut TrailP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
     user_lits: &[Lit],
 ) {
+    variables::ensure_var_count_from_slice(user_lits);
+
     match ctx.part(SolverStateP).sat_state {
         SatState::Unsat => return,
         SatState::Sat => {
@@ -68,7 +73,7 @@ pub fn load_clause<'a>(
     lits.sort_unstable();
     lits.dedup();
 
-    proof::add_clause(ctx.borrow(), &lits);
+    proof::add_clause(ctx.borrow(), lits);
 
     // Detect tautological clauses
     let mut last = None;
@@ -129,7 +134,7 @@ pub fn load_clause<'a>(
         resurrect_unit(ctx.borrow(), !lit);
     }
 
-    lits.extend_from_slice(&false_lits);
+    lits.extend_from_slice(false_lits);
 
     if clause_is_true {
         if lits.len() > 1 {
@@ -21,6 +21,11 @@ pub struct Model {
 }
 
 impl Model {
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.assignment.clear();
+    }
+
     /// Assignment of the global model.
     ///
     /// Only valid if the solver state is SAT.
@@ -0,0 +1,197 @@
+//! Portfolio solving using multiple threads.
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use anyhow::Error;
+
+use varisat_formula::{CnfFormula, Lit};
+
+use crate::{
+    config::{SolverConfig, SolverConfigUpdate},
+    solver::{Solver, SolverError},
+};
+
+/// Result of solving a formula using a [`PortfolioSolver`].
+#[derive(Clone, Debug)]
+pub struct PortfolioSolution {
+    /// Index of the configuration that solved the formula first.
+    pub config_index: usize,
+    /// A satisfying assignment or `None` if the formula is unsatisfiable.
+    pub model: Option<Vec<Lit>>,
+}
+
+/// Solve a formula by racing solvers with different configurations.
+///
+/// Every configuration runs in its own thread with its own [`Solver`]. As soon as one of them finds
+/// a result, the others are interrupted. Clauses are not shared between the solvers, so each thread
+/// holds a full copy of the formula.
+#[derive(Default)]
+pub struct PortfolioSolver {
+    configs: Vec<SolverConfigUpdate>,
+    terminate: Option<Arc<AtomicBool>>,
+}
+
+impl PortfolioSolver {
+    /// Create a new portfolio without any configurations.
+    pub fn new() -> PortfolioSolver {
+        PortfolioSolver::default()
+    }
+
+    /// Add a configuration to the portfolio.
+    ///
+    /// Each configuration adds a thread used during solving.
+    pub fn add_config(&mut self, config_update: SolverConfigUpdate) -> Result<(), Error> {
+        config_update.apply(&mut SolverConfig::default())?;
+        self.configs.push(config_update);
+        Ok(())
+    }
+
+    /// Number of configurations in the portfolio.
+    pub fn config_count(&self) -> usize {
+        self.configs.len()
+    }
+
+    /// Poll the given flag and stop solving when it is set.
+    ///
+    /// See [`Solver::set_terminate`].
+    pub fn set_terminate(&mut self, flag: Arc<AtomicBool>) {
+        self.terminate = Some(flag);
+    }
+
+    /// Solve a formula using all configurations of the portfolio.
+    ///
+    /// This blocks until one solver finds a result and all threads are finished. If no
+    /// configuration was added, the default configuration is used. Returns
+    /// `Err(SolverError::Interrupted)` when solving was interrupted via the termination flag.
+    pub fn solve(&self, formula: &CnfFormula) -> Result<PortfolioSolution, SolverError> {
+        let default_configs = [SolverConfigUpdate::new()];
+        let configs = if self.configs.is_empty() {
+            &default_configs[..]
+        } else {
+            &self.configs[..]
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let solution = Mutex::new(None);
+
+        thread::scope(|scope| {
+            for (config_index, config_update) in configs.iter().enumerate() {
+                let stop = stop.clone();
+                let terminate = self.terminate.clone();
+                let solution = &solution;
+
+                scope.spawn(move || {
+                    let mut solver = Solver::new();
+                    solver
+                        .config(config_update)
+                        .expect("config was validated when it was added");
+                    solver.set_terminate(stop.clone());
+                    if let Some(terminate) = terminate {
+                        solver.set_terminate_callback(move || terminate.load(Ordering::Relaxed));
+                    }
+                    solver.add_formula(formula);
+
+                    if let Ok(satisfiable) = solver.solve() {
+                        let mut solution = solution.lock().unwrap();
+                        if solution.is_none() {
+                            *solution = Some(PortfolioSolution {
+                                config_index,
+                                model: if satisfiable { solver.model() } else { None },
+                            });
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        solution
+            .into_inner()
+            .unwrap()
+            .ok_or(SolverError::Interrupted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use varisat_formula::test::{sat_formula, sgen_unsat_formula};
+
+    use crate::config::RestartStrategy;
+
+    fn portfolio() -> PortfolioSolver {
+        let mut portfolio = PortfolioSolver::new();
+        portfolio.add_config(SolverConfigUpdate::new()).unwrap();
+        portfolio
+            .add_config(SolverConfigUpdate {
+                restart_strategy: Some(RestartStrategy::Geometric),
+                ..SolverConfigUpdate::new()
+            })
+            .unwrap();
+        portfolio
+            .add_config(SolverConfigUpdate {
+                vsids_decay: Some(0.8),
+                core_glue_limit: Some(1),
+                ..SolverConfigUpdate::new()
+            })
+            .unwrap();
+        portfolio
+    }
+
+    #[test]
+    fn invalid_config() {
+        let mut portfolio = PortfolioSolver::new();
+        assert!(portfolio
+            .add_config(SolverConfigUpdate {
+                vsids_decay: Some(2.0),
+                ..SolverConfigUpdate::new()
+            })
+            .is_err());
+        assert_eq!(portfolio.config_count(), 0);
+    }
+
+    #[test]
+    fn interrupted() {
+        let mut portfolio = portfolio();
+        portfolio.set_terminate(Arc::new(AtomicBool::new(true)));
+        assert!(matches!(
+            portfolio.solve(&CnfFormula::new()),
+            Err(SolverError::Interrupted)
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn sgen_unsat(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+
+            let solution = portfolio().solve(&formula).unwrap();
+            prop_assert!(solution.config_index < 3);
+            prop_assert!(solution.model.is_none());
+        }
+
+        #[test]
+        fn sat(formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0)) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let solution = portfolio().solve(&formula).unwrap();
+            let model = solution.model.unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+    }
+}
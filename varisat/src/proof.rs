@@ -22,6 +22,11 @@ pub enum ProofFormat {
     Varisat,
     Drat,
     BinaryDrat,
+    /// DRAT proof without clause deletions.
+    ///
+    /// Some tools only support proofs that never delete clauses. Without deletion information,
+    /// checkers have to keep all clauses, which can make checking much slower.
+    DratNoDeletions,
 }
 
 /// Number of added or removed clauses.
@@ -62,6 +67,12 @@ pub struct Proof<'a> {
     map_step: map_step::MapStep,
     /// How many bits are used for storing clause hashes.
     hash_bits: u32,
+    /// Fixed number of hash bits, disabling automatic adjustment.
+    fixed_hash_bits: Option<u32>,
+    /// Whether failed self checks are recorded instead of panicking.
+    report_self_check_failures: bool,
+    /// Recorded self check failures.
+    self_check_failures: Vec<CheckerError>,
     /// How many clauses are currently in the db.
     ///
     /// This is used to pick a good number of hash_bits
@@ -76,6 +87,9 @@ impl<'a> Default for Proof<'a> {
             checker: None,
             map_step: Default::default(),
             hash_bits: 64,
+            fixed_hash_bits: None,
+            report_self_check_failures: false,
+            self_check_failures: vec![],
             clause_count: 0,
         }
     }
@@ -88,6 +102,13 @@ impl<'a> Proof<'a> {
         self.target = BufWriter::new(Box::new(target))
     }
 
+    /// Use a fixed number of bits for clause hashes or adjust it automatically when `None`.
+    ///
+    /// A change is written before the next proof step.
+    pub fn set_fixed_hash_bits(&mut self, bits: Option<u32>) {
+        self.fixed_hash_bits = bits;
+    }
+
     /// Begin checking proof steps.
     pub fn begin_checking(&mut self) {
         if self.checker.is_none() {
@@ -95,6 +116,17 @@ impl<'a> Proof<'a> {
         }
     }
 
+    /// Begin checking proof steps, recording failed checks instead of panicking.
+    pub fn begin_checking_with_report(&mut self) {
+        self.begin_checking();
+        self.report_self_check_failures = true;
+    }
+
+    /// Failed self checks recorded since checking with a report began.
+    pub fn self_check_failures(&self) -> &[CheckerError] {
+        &self.self_check_failures
+    }
+
     /// Add a [`ProofProcessor`].
     ///
     /// See also [`Checker::add_processor`].
@@ -103,6 +135,21 @@ impl<'a> Proof<'a> {
         self.checker.as_mut().unwrap().add_processor(processor);
     }
 
+    /// Track the input clauses used to derive the final conflict.
+    ///
+    /// This implicitly enables checking.
+    pub fn enable_unsat_core(&mut self) {
+        self.begin_checking();
+        self.checker.as_mut().unwrap().enable_unsat_core();
+    }
+
+    /// Indices of the input clauses used to derive the final conflict.
+    ///
+    /// Returns `None` if unsat core tracking is not enabled or no conflict was derived.
+    pub fn unsat_core_clauses(&self) -> Option<Vec<usize>> {
+        self.checker.as_ref()?.unsat_core()?.unsat_core_indices()
+    }
+
     /// Whether proof generation is active.
     pub fn is_active(&self) -> bool {
         self.checker.is_some() || self.format.is_some()
@@ -198,6 +245,10 @@ pub fn add_step<'a, 's>(
             let step = proof.map_step.map(step, map_vars, |hash| hash);
             drat::write_binary_step(&mut proof.target, &step)
         }
+        Some(ProofFormat::DratNoDeletions) => {
+            let step = proof.map_step.map(step, map_vars, |hash| hash);
+            drat::write_step_without_deletions(&mut proof.target, &step)
+        }
         None => Ok(()),
     };
 
@@ -224,16 +275,21 @@ fn write_varisat_step<'a, 's>(
     proof.clause_count += clause_count_delta(step);
 
     let mut rehash = false;
-    // Should we change the hash size?
-    while proof.clause_count > (1 << (proof.hash_bits / 2)) {
-        proof.hash_bits += 2;
-        rehash = true;
-    }
-    if ctx.part(SolverStateP).solver_invoked {
-        while proof.hash_bits > 6 && proof.clause_count * 4 < (1 << (proof.hash_bits / 2)) {
-            proof.hash_bits -= 2;
+    if let Some(bits) = proof.fixed_hash_bits {
+        rehash = proof.hash_bits != bits;
+        proof.hash_bits = bits;
+    } else {
+        // Should we change the hash size?
+        while proof.clause_count > (1 << (proof.hash_bits / 2)) {
+            proof.hash_bits += 2;
             rehash = true;
         }
+        if ctx.part(SolverStateP).solver_invoked {
+            while proof.hash_bits > 6 && proof.clause_count * 4 < (1 << (proof.hash_bits / 2)) {
+                proof.hash_bits -= 2;
+                rehash = true;
+            }
+        }
     }
 
     if rehash {
@@ -245,7 +301,7 @@ fn write_varisat_step<'a, 's>(
         )?;
     }
 
-    let shift_bits = ClauseHash::max_value().count_ones() - proof.hash_bits;
+    let shift_bits = ClauseHash::MAX.count_ones() - proof.hash_bits;
 
     let map_hash = |hash| hash >> shift_bits;
     let step = proof.map_step.map(step, map_vars, map_hash);
@@ -300,12 +356,24 @@ fn handle_self_check_result<'a>(
         }
         Err(err) => {
             log::error!("{}", err);
-            if let CheckerError::CheckFailed { debug_step, .. } = err {
+            if let CheckerError::CheckFailed { debug_step, .. } = &err {
                 if !debug_step.is_empty() {
                     log::error!("failed step was {}", debug_step)
                 }
             }
-            panic!("self check failure");
+            let proof = ctx.part_mut(ProofP);
+            if !proof.report_self_check_failures {
+                panic!("self check failure");
+            }
+            // The remaining proof would be checked against an inconsistent state, so proof
+            // generation stops, keeping only the report.
+            let mut failures = std::mem::take(&mut proof.self_check_failures);
+            failures.push(err);
+            *proof = Proof {
+                report_self_check_failures: true,
+                self_check_failures: failures,
+                ..Proof::default()
+            };
         }
         Ok(()) => (),
     }
@@ -333,13 +401,14 @@ mod tests {
     use tempfile::TempDir;
 
     use varisat_dimacs::write_dimacs;
-    use varisat_formula::{test::sgen_unsat_formula, CnfFormula};
+    use varisat_formula::{cnf_formula, test::sgen_unsat_formula, CnfFormula, ExtendFormula};
 
-    use crate::solver::Solver;
+    use crate::{config::SolverConfigUpdate, solver::Solver};
 
     enum Checker {
         DratTrim,
         Rate,
+        Native,
     }
 
     fn test_drat(checker: Checker, formula: CnfFormula, binary: bool) -> Result<(), TestCaseError> {
@@ -368,6 +437,17 @@ mod tests {
             .close_proof()
             .map_err(|e| TestCaseError::fail(e.to_string()))?;
 
+        if let Checker::Native = checker {
+            let mut checker = varisat_checker::Checker::new();
+            checker
+                .add_formula(&formula)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            checker
+                .check_drat_proof(File::open(&drat_proof)?)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            return Ok(());
+        }
+
         let output = match checker {
             Checker::DratTrim => {
                 if binary {
@@ -387,6 +467,7 @@ mod tests {
                 .arg(&cnf_file)
                 .arg(&drat_proof)
                 .output()?,
+            Checker::Native => unreachable!(),
         };
 
         prop_assert!(std::str::from_utf8(&output.stdout)?.contains("s VERIFIED"));
@@ -394,7 +475,286 @@ mod tests {
         Ok(())
     }
 
+    /// Solve the first part of a formula, then add the remaining clauses and solve again.
+    ///
+    /// The generated proof is checked using the native checker. Native proofs contain the clauses
+    /// added after solving, while DRAT proofs are checked against the whole formula.
+    fn test_incremental_proof(
+        formula: &CnfFormula,
+        initial_clauses: usize,
+        format: ProofFormat,
+        self_checking: bool,
+    ) -> Result<(), TestCaseError> {
+        let clauses: Vec<_> = formula.iter().collect();
+        let initial = CnfFormula::from(clauses[..initial_clauses].iter().cloned());
+
+        let mut proof = vec![];
+
+        {
+            let mut solver = Solver::new();
+            solver.write_proof(&mut proof, format);
+            if self_checking {
+                solver.enable_self_checking();
+            }
+            solver.add_formula(&initial);
+            solver.solve().unwrap();
+            for &clause in clauses[initial_clauses..].iter() {
+                solver.add_clause(clause);
+            }
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+            solver
+                .close_proof()
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        }
+
+        let mut checker = varisat_checker::Checker::new();
+
+        let result = if format == ProofFormat::Varisat {
+            checker.add_formula(&initial).unwrap();
+            checker.check_proof(&proof[..])
+        } else {
+            checker.add_formula(formula).unwrap();
+            checker.check_drat_proof(&proof[..])
+        };
+
+        result.map_err(|e| TestCaseError::fail(e.to_string()))
+    }
+
+    #[test]
+    fn incremental_unit_conflict() {
+        let formula = cnf_formula![
+            1, 2;
+            -2, 3;
+            1;
+            -1;
+        ];
+
+        for &format in [
+            ProofFormat::Varisat,
+            ProofFormat::Drat,
+            ProofFormat::BinaryDrat,
+            ProofFormat::DratNoDeletions,
+        ]
+        .iter()
+        {
+            for &self_checking in [false, true].iter() {
+                test_incremental_proof(&formula, 3, format, self_checking).unwrap();
+            }
+        }
+    }
+
     proptest! {
+        #[test]
+        fn sgen_unsat_incremental(
+            formula in sgen_unsat_formula(1..7usize),
+            initial_clauses in proptest::num::usize::ANY,
+            format in prop_oneof![
+                Just(ProofFormat::Varisat),
+                Just(ProofFormat::Drat),
+                Just(ProofFormat::BinaryDrat),
+                Just(ProofFormat::DratNoDeletions),
+            ],
+            self_checking in proptest::bool::ANY,
+        ) {
+            let initial_clauses = initial_clauses % formula.len();
+            test_incremental_proof(&formula, initial_clauses, format, self_checking)?;
+        }
+
+        #[test]
+        fn sgen_unsat_core(formula in sgen_unsat_formula(1..7usize)) {
+            let mut proof = vec![];
+
+            {
+                let mut solver = Solver::new();
+                solver.write_proof(&mut proof, ProofFormat::Varisat);
+                solver.add_formula(&formula);
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+                solver
+                    .close_proof()
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            let mut unsat_core = varisat_checker::UnsatCore::new();
+
+            {
+                let mut checker = varisat_checker::Checker::new();
+                checker.add_processor(&mut unsat_core);
+                checker
+                    .add_formula(&formula)
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+                checker
+                    .check_proof(&proof[..])
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            let core = unsat_core.unsat_core().unwrap();
+            let clauses: Vec<_> = formula.iter().collect();
+
+            let mut solver = Solver::new();
+            for &id in core.iter() {
+                solver.add_clause(clauses[id as usize]);
+            }
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sgen_unsat_parallel_check(
+            formula in sgen_unsat_formula(1..7usize),
+            removed_clause in proptest::num::usize::ANY,
+        ) {
+            let mut proof = vec![];
+
+            {
+                let mut solver = Solver::new();
+                solver.write_proof(&mut proof, ProofFormat::Varisat);
+                solver.add_formula(&formula);
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+                solver
+                    .close_proof()
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            // Removing an input clause makes the proof invalid unless the clause is not needed.
+            let clauses: Vec<_> = formula.iter().collect();
+            let removed_clause = removed_clause % clauses.len();
+            let mut weakened = CnfFormula::new();
+            for (index, clause) in clauses.iter().enumerate() {
+                if index != removed_clause {
+                    weakened.add_clause(clause);
+                }
+            }
+
+            let check = |formula: &CnfFormula, threads: Option<usize>| {
+                let mut checker = varisat_checker::Checker::new();
+                checker.add_formula(formula).unwrap();
+                let result = match threads {
+                    Some(threads) => checker.check_proof_parallel(&proof[..], threads),
+                    None => checker.check_proof(&proof[..]),
+                };
+                result.map_err(|err| match err {
+                    varisat_checker::CheckerError::CheckFailed { step, .. } => Some(step),
+                    _ => None,
+                })
+            };
+
+            prop_assert_eq!(check(&formula, Some(2)), Ok(()));
+
+            let sequential = check(&weakened, None);
+            prop_assert_eq!(check(&weakened, Some(2)), sequential);
+            prop_assert_eq!(check(&weakened, Some(3)), sequential);
+        }
+
+        #[test]
+        fn sgen_unsat_step_kinds(formula in sgen_unsat_formula(1..7usize)) {
+            let mut proof = vec![];
+
+            {
+                let mut solver = Solver::new();
+                solver.write_proof(&mut proof, ProofFormat::Varisat);
+                solver.add_formula(&formula);
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+                solver
+                    .close_proof()
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            let mut at_clauses = 0;
+            let mut deleted_clauses = 0;
+            let mut empty_clause = false;
+
+            varisat_internal_proof::binary_format::for_each_step(&proof[..], |step| {
+                match step {
+                    ProofStep::AtClause { clause, .. } => {
+                        at_clauses += 1;
+                        empty_clause |= clause.is_empty();
+                    }
+                    ProofStep::DeleteClause { .. } => deleted_clauses += 1,
+                    _ => (),
+                }
+                Ok(())
+            })
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+            prop_assert!(at_clauses > 0);
+            prop_assert!(deleted_clauses <= at_clauses + formula.len());
+            prop_assert!(empty_clause);
+        }
+
+        #[test]
+        fn sgen_unsat_drat_no_deletions(formula in sgen_unsat_formula(1..7usize)) {
+            let mut proof = vec![];
+
+            {
+                let mut solver = Solver::new();
+                let mut config = SolverConfigUpdate::new();
+                config.reduce_locals_interval = Some(150);
+                solver.config(&config).unwrap();
+                solver.write_proof(&mut proof, ProofFormat::DratNoDeletions);
+                solver.add_formula(&formula);
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+                solver
+                    .close_proof()
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            let proof_str = std::str::from_utf8(&proof)?;
+            prop_assert!(!proof_str.lines().any(|line| line.starts_with('d')));
+
+            let mut checker = varisat_checker::Checker::new();
+            checker
+                .add_formula(&formula)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            checker
+                .check_drat_proof(&proof[..])
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        }
+
+        #[test]
+        fn sgen_unsat_fixed_hash_bits(
+            formula in sgen_unsat_formula(1..7usize),
+            bits in 1..=64u32,
+        ) {
+            let mut proof = vec![];
+
+            {
+                let mut solver = Solver::new();
+                let mut config = SolverConfigUpdate::new();
+                config.proof_hash_bits = Some(Some(bits));
+                solver.config(&config).unwrap();
+                solver.write_proof(&mut proof, ProofFormat::Varisat);
+                solver.add_formula(&formula);
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+                solver
+                    .close_proof()
+                    .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            }
+
+            // Only the initial change from the default of 64 bits is written.
+            let mut change_steps = vec![];
+            let mut parser = varisat_internal_proof::binary_format::Parser::default();
+            let mut source = &proof[..];
+            loop {
+                match parser.parse_step(&mut source).unwrap() {
+                    ProofStep::ChangeHashBits { bits } => change_steps.push(bits),
+                    ProofStep::End => break,
+                    _ => (),
+                }
+            }
+            if bits == 64 {
+                prop_assert_eq!(change_steps, Vec::<u32>::new());
+            } else {
+                prop_assert_eq!(change_steps, vec![bits]);
+            }
+
+            let mut checker = varisat_checker::Checker::new();
+            checker
+                .add_formula(&formula)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            checker
+                .check_proof(&proof[..])
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        }
+
         #[cfg_attr(not(test_drat_trim), ignore)]
         #[test]
         fn sgen_unsat_drat_trim(
@@ -411,6 +771,20 @@ mod tests {
             test_drat(Checker::DratTrim, formula, true)?;
         }
 
+        #[test]
+        fn sgen_unsat_drat_native(
+            formula in sgen_unsat_formula(1..7usize),
+        ) {
+            test_drat(Checker::Native, formula, false)?;
+        }
+
+        #[test]
+        fn sgen_unsat_binary_drat_native(
+            formula in sgen_unsat_formula(1..7usize),
+        ) {
+            test_drat(Checker::Native, formula, true)?;
+        }
+
         #[cfg_attr(not(test_rate), ignore)]
         #[test]
         fn sgen_unsat_rate(
@@ -10,7 +10,7 @@ fn drat_step(
 ) -> io::Result<()> {
     match step {
         ProofStep::AtClause { clause, .. } => {
-            emit_drat_step(true, &clause)?;
+            emit_drat_step(true, clause)?;
         }
         ProofStep::UnitClauses { units } => {
             for &(unit, _hash) in units.iter() {
@@ -27,10 +27,10 @@ fn drat_step(
         | ProofStep::ChangeHashBits { .. }
         | ProofStep::Model { .. }
         | ProofStep::End => (),
-        ProofStep::AddClause { .. } => {
-            // TODO allow error handling here?
-            panic!("incremental clause additions not supported by DRAT proofs");
-        }
+        // DRAT has no way to add input clauses, so a DRAT proof always refers to the formula
+        // including all clauses added after solving. Lemmas implied by a subset of the input stay
+        // implied by the full formula, so omitting these steps keeps the proof valid.
+        ProofStep::AddClause { .. } => (),
         ProofStep::Assumptions { .. } | ProofStep::FailedAssumptions { .. } => {
             // TODO allow error handling here?
             panic!("assumptions not supported by DRAT proofs");
@@ -46,7 +46,20 @@ pub fn write_step<'s>(target: &mut impl Write, step: &'s ProofStep<'s>) -> io::R
         if !add {
             target.write_all(b"d ")?;
         }
-        write_literals(target, &clause[..])?;
+        write_literals(target, clause)?;
+        Ok(())
+    })
+}
+
+/// Writes a proof step in DRAT format, omitting clause deletions
+pub fn write_step_without_deletions<'s>(
+    target: &mut impl Write,
+    step: &'s ProofStep<'s>,
+) -> io::Result<()> {
+    drat_step(step, |add, clause| {
+        if add {
+            write_literals(target, clause)?;
+        }
         Ok(())
     })
 }
@@ -59,7 +72,7 @@ pub fn write_binary_step<'s>(target: &mut impl Write, step: &'s ProofStep<'s>) -
         } else {
             target.write_all(b"d")?;
         }
-        write_binary_literals(target, &clause[..])?;
+        write_binary_literals(target, clause)?;
         Ok(())
     })
 }
@@ -83,3 +96,68 @@ fn write_binary_literals(target: &mut impl Write, literals: &[Lit]) -> io::Resul
     target.write_all(&[0])?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{prelude::*, *};
+
+    use varisat_formula::lit::strategy::lit;
+    use varisat_internal_proof::{drat::DratParser, DeleteClauseProof};
+
+    fn drat_steps() -> impl Strategy<Value = Vec<(bool, Vec<Lit>)>> {
+        collection::vec(
+            (bool::ANY, collection::vec(lit(0..100usize), 0..10)),
+            0..100,
+        )
+    }
+
+    fn roundtrip(
+        steps: &[(bool, Vec<Lit>)],
+        write: fn(&mut Vec<u8>, &ProofStep) -> io::Result<()>,
+    ) -> Vec<(bool, Vec<Lit>)> {
+        let mut proof = vec![];
+
+        for (add, clause) in steps.iter() {
+            let step = if *add {
+                ProofStep::AtClause {
+                    redundant: true,
+                    clause,
+                    propagation_hashes: &[],
+                }
+            } else {
+                ProofStep::DeleteClause {
+                    clause,
+                    proof: DeleteClauseProof::Redundant,
+                }
+            };
+            write(&mut proof, &step).unwrap();
+        }
+
+        let mut parser = DratParser::default();
+        let mut source = &proof[..];
+        let mut parsed = vec![];
+
+        while let Some(step) = parser.parse_step(&mut source).unwrap() {
+            parsed.push((step.add, step.clause.to_vec()));
+        }
+
+        parsed
+    }
+
+    proptest! {
+        #[test]
+        fn text_roundtrip(steps in drat_steps()) {
+            prop_assert_eq!(roundtrip(&steps, write_step), steps);
+        }
+
+        #[test]
+        fn binary_roundtrip(steps in drat_steps()) {
+            prop_assert_eq!(
+                roundtrip(&steps, write_binary_step),
+                steps
+            );
+        }
+    }
+}
@@ -1,4 +1,6 @@
 //! Partial assignment and backtracking.
+use std::mem::size_of;
+
 use partial_ref::{partial, PartialRef};
 
 use varisat_formula::{lit::LitIdx, Lit, Var};
@@ -15,20 +17,46 @@ use super::Reason;
 pub struct Assignment {
     assignment: Vec<Option<bool>>,
     last_value: Vec<bool>,
+    /// Saved phases of assigned variables that are restored after unassigning them.
+    pending_phases: Vec<Lit>,
 }
 
 /// This compares two `Option<bool>` values as bytes. Workaround for bad code generation.
 pub fn fast_option_eq(a: Option<bool>, b: Option<bool>) -> bool {
-    unsafe { std::mem::transmute::<_, u8>(a) == std::mem::transmute::<_, u8>(b) }
+    unsafe {
+        std::mem::transmute::<Option<bool>, u8>(a) == std::mem::transmute::<Option<bool>, u8>(b)
+    }
 }
 
 impl Assignment {
+    /// Estimated number of bytes used by the assignment.
+    pub fn memory_usage(&self) -> usize {
+        self.assignment.capacity() * size_of::<Option<bool>>()
+            + self.last_value.capacity() * size_of::<bool>()
+            + self.pending_phases.capacity() * size_of::<Lit>()
+    }
+
     /// Update structures for a new variable count.
     pub fn set_var_count(&mut self, count: usize) {
         self.assignment.resize(count, None);
         self.last_value.resize(count, false);
     }
 
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.assignment.clear();
+        self.last_value.clear();
+        self.pending_phases.clear();
+    }
+
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.assignment
+            .reserve(count.saturating_sub(self.assignment.len()));
+        self.last_value
+            .reserve(count.saturating_sub(self.last_value.len()));
+    }
+
     /// Current partial assignment as slice.
     pub fn assignment(&self) -> &[Option<bool>] {
         &self.assignment
@@ -47,6 +75,22 @@ impl Assignment {
         self.last_value[var.index()]
     }
 
+    /// Set the value used by [`last_var_value`](Assignment::last_var_value).
+    pub fn set_last_var_value(&mut self, var: Var, value: bool) {
+        self.last_value[var.index()] = value;
+    }
+
+    /// Set the saved phase of a variable, even if it is currently assigned.
+    ///
+    /// Unlike [`set_last_var_value`](Assignment::set_last_var_value) the value is not overwritten
+    /// when backtracking unassigns the variable.
+    pub fn set_saved_phase(&mut self, var: Var, value: bool) {
+        self.last_value[var.index()] = value;
+        if self.assignment[var.index()].is_some() {
+            self.pending_phases.push(var.lit(value));
+        }
+    }
+
     /// Value assigned to a literal.
     pub fn lit_value(&self, lit: Lit) -> Option<bool> {
         self.assignment[lit.index()].map(|b| b ^ lit.is_negative())
@@ -89,9 +133,18 @@ pub struct Trail {
     decisions: Vec<LitIdx>,
     /// Number of unit clauses removed from the trail.
     units_removed: usize,
+    /// Number of assignments propagated so far.
+    propagations: u64,
+    /// Number of decisions made by the decision heuristic so far.
+    decision_count: u64,
 }
 
 impl Trail {
+    /// Estimated number of bytes used by the trail.
+    pub fn memory_usage(&self) -> usize {
+        self.trail.capacity() * size_of::<Lit>() + self.decisions.capacity() * size_of::<LitIdx>()
+    }
+
     /// Return the next assigned literal to propagate.
     pub fn queue_head(&self) -> Option<Lit> {
         self.trail.get(self.queue_head_pos).cloned()
@@ -102,10 +155,28 @@ impl Trail {
         let head = self.queue_head();
         if head.is_some() {
             self.queue_head_pos += 1;
+            self.propagations += 1;
         }
         head
     }
 
+    /// Total number of assignments propagated so far.
+    pub fn propagations(&self) -> u64 {
+        self.propagations
+    }
+
+    /// Total number of decisions made by the decision heuristic so far.
+    ///
+    /// This does not include assumptions.
+    pub fn decision_count(&self) -> u64 {
+        self.decision_count
+    }
+
+    /// Count a decision made by the decision heuristic.
+    pub fn count_decision(&mut self) {
+        self.decision_count += 1;
+    }
+
     /// Re-enqueue all assigned literals.
     pub fn reset_queue(&mut self) {
         self.queue_head_pos = 0;
@@ -127,6 +198,18 @@ impl Trail {
         self.queue_head_pos = 0;
     }
 
+    /// Reset to the initial state, keeping the allocated capacity.
+    ///
+    /// Unlike [`clear`](Trail::clear) this also removes all decisions and resets the counters.
+    pub fn reset(&mut self) {
+        self.trail.clear();
+        self.queue_head_pos = 0;
+        self.decisions.clear();
+        self.units_removed = 0;
+        self.propagations = 0;
+        self.decision_count = 0;
+    }
+
     /// Start a new decision level.
     ///
     /// Does not enqueue the decision itself.
@@ -142,12 +225,22 @@ impl Trail {
     /// The number of assignments at level 0.
     pub fn top_level_assignment_count(&self) -> usize {
         self.decisions
-            .get(0)
+            .first()
             .map(|&len| len as usize)
             .unwrap_or(self.trail.len())
             + self.units_removed
     }
 
+    /// Assignments at level 0 that are still on the trail.
+    pub fn top_level_trail(&self) -> &[Lit] {
+        let len = self
+            .decisions
+            .first()
+            .map(|&len| len as usize)
+            .unwrap_or(self.trail.len());
+        &self.trail[..len]
+    }
+
     /// Whether all assignments are processed.
     pub fn fully_propagated(&self) -> bool {
         self.queue_head_pos == self.trail.len()
@@ -164,7 +257,7 @@ pub fn enqueue_assignment(
     reason: Reason,
 ) {
     let assignment = ctx.part_mut(AssignmentP);
-    debug_assert!(assignment.lit_value(lit) == None);
+    debug_assert!(assignment.lit_value(lit).is_none());
 
     assignment.assign_lit(lit);
 
@@ -180,7 +273,15 @@ pub fn enqueue_assignment(
 
 /// Undo all assignments in decision levels deeper than the given level.
 pub fn backtrack(
-    mut ctx: partial!(Context, mut AssignmentP, mut TrailP, mut VsidsP),
+    mut ctx: partial!(
+        Context,
+        mut AssignmentP,
+        mut NonDecisionP,
+        mut TrailP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP,
+    ),
     level: usize,
 ) {
     let (assignment, mut ctx) = ctx.split_part_mut(AssignmentP);
@@ -206,6 +307,23 @@ pub fn backtrack(
         *var_assignment = None;
     }
     trail.trail.truncate(new_trail_len);
+
+    if !assignment.pending_phases.is_empty() {
+        let Assignment {
+            assignment: values,
+            last_value,
+            pending_phases,
+        } = assignment;
+        pending_phases.retain(|&lit| {
+            if values[lit.index()].is_some() {
+                // Variables still assigned at level 0 are never decided again
+                level != 0
+            } else {
+                last_value[lit.index()] = lit.is_positive();
+                false
+            }
+        });
+    }
 }
 
 /// Undo all decisions and assumptions.
@@ -214,8 +332,11 @@ pub fn full_restart(
         Context,
         mut AssignmentP,
         mut AssumptionsP,
+        mut NonDecisionP,
         mut TrailP,
+        mut VmtfP,
         mut VsidsP,
+        SolverConfigP,
     ),
 ) {
     ctx.part_mut(AssumptionsP).full_restart();
@@ -227,9 +348,12 @@ pub fn restart(
     mut ctx: partial!(
         Context,
         mut AssignmentP,
+        mut NonDecisionP,
         mut TrailP,
+        mut VmtfP,
         mut VsidsP,
-        AssumptionsP
+        AssumptionsP,
+        SolverConfigP,
     ),
 ) {
     let level = ctx.part(AssumptionsP).assumption_levels();
@@ -1,5 +1,7 @@
 //! The implication graph.
 
+use std::mem::size_of;
+
 use partial_ref::{partial, PartialRef};
 
 use varisat_formula::{lit::LitIdx, Lit, Var};
@@ -64,7 +66,7 @@ impl Conflict {
 pub struct ImplNode {
     pub reason: Reason,
     pub level: LitIdx,
-    /// Position in trail when assigned, `LitIdx::max_value()` is used as sentinel for removed
+    /// Position in trail when assigned, `LitIdx::MAX` is used as sentinel for removed
     /// units.
     pub depth: LitIdx,
 }
@@ -81,6 +83,11 @@ pub struct ImplGraph {
 }
 
 impl ImplGraph {
+    /// Estimated number of bytes used by the implication graph.
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.capacity() * size_of::<ImplNode>()
+    }
+
     /// Update structures for a new variable count.
     pub fn set_var_count(&mut self, count: usize) {
         self.nodes.resize(
@@ -93,6 +100,16 @@ impl ImplGraph {
         );
     }
 
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.nodes.reserve(count.saturating_sub(self.nodes.len()));
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.nodes.clear();
+    }
+
     /// Get the reason for an assigned variable.
     ///
     /// Returns stale data if the variable isn't assigned.
@@ -125,10 +142,10 @@ impl ImplGraph {
     pub fn update_removed_unit(&mut self, var: Var) {
         let node = &mut self.nodes[var.index()];
         node.reason = Reason::Unit;
-        node.depth = LitIdx::max_value();
+        node.depth = LitIdx::MAX;
     }
 
     pub fn is_removed_unit(&self, var: Var) -> bool {
-        self.nodes[var.index()].depth == LitIdx::max_value()
+        self.nodes[var.index()].depth == LitIdx::MAX
     }
 }
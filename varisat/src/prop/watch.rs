@@ -32,6 +32,8 @@
 //! [handbook-ch4]: https://www.satassociation.org/articles/FAIA185-0131.pdf
 //! [minisat-2.1]: https://www.cril.univ-artois.fr/SAT09/solvers/booklet.pdf
 
+use std::mem::size_of;
+
 use partial_ref::{partial, PartialRef};
 
 use varisat_formula::Lit;
@@ -56,6 +58,8 @@ pub struct Watchlists {
     watches: Vec<Vec<Watch>>,
     /// Whether watchlists are present
     enabled: bool,
+    /// Empty watchlists kept for reuse by [`reset`](Watchlists::reset).
+    unused: Vec<Vec<Watch>>,
 }
 
 impl Default for Watchlists {
@@ -63,16 +67,50 @@ impl Default for Watchlists {
         Watchlists {
             watches: vec![],
             enabled: true,
+            unused: vec![],
         }
     }
 }
 
 impl Watchlists {
+    /// Estimated number of bytes used by the watchlists.
+    pub fn memory_usage(&self) -> usize {
+        let lists = (self.watches.capacity() + self.unused.capacity()) * size_of::<Vec<Watch>>();
+        let watches: usize = self
+            .watches
+            .iter()
+            .chain(self.unused.iter())
+            .map(|watches| watches.capacity())
+            .sum();
+        lists + watches * size_of::<Watch>()
+    }
+
     /// Update structures for a new variable count.
     pub fn set_var_count(&mut self, count: usize) {
+        let missing = (count * 2).saturating_sub(self.watches.len());
+        let reused = self.unused.len().saturating_sub(missing);
+        self.watches.extend(self.unused.drain(reused..));
         self.watches.resize(count * 2, vec![]);
     }
 
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.watches
+            .reserve((count * 2).saturating_sub(self.watches.len()));
+    }
+
+    /// Remove all watches and enable the watchlists, keeping the allocated capacity.
+    ///
+    /// The emptied watchlists are reused when the variable count increases again.
+    pub fn reset(&mut self) {
+        self.unused
+            .extend(self.watches.drain(..).map(|mut watches| {
+                watches.clear();
+                watches
+            }));
+        self.enabled = true;
+    }
+
     /// Start watching a clause.
     ///
     /// `lits` have to be the first two literals of the given clause.
@@ -1,20 +1,28 @@
 //! Scheduling of processing and solving steps.
 //!
 //! The current implementation is temporary and will be replaced with something more flexible.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
 use log::info;
 
 use partial_ref::{partial, PartialRef};
 
 use crate::{
+    callbacks,
     cdcl::conflict_step,
     clause::{
         collect_garbage,
         reduce::{reduce_locals, reduce_mids},
         Tier,
     },
-    context::{parts::*, Context},
+    config::{RestartStrategy, SolverConfig},
+    context::{parts::*, update_peak_memory_usage, Context},
     prop::restart,
     state::SatState,
+    stats::solver_stats,
 };
 
 mod luby;
@@ -28,9 +36,99 @@ pub struct Schedule {
     next_restart: u64,
     restarts: u64,
     luby: LubySequence,
+    /// Current interval for geometric restarts.
+    geometric_interval: Option<f64>,
+    /// Maximal number of conflicts per call to solve.
+    conflict_budget: Option<u64>,
+    /// Maximal number of propagations per call to solve.
+    propagation_budget: Option<u64>,
+    /// Number of conflicts when the current call to solve was started.
+    conflicts_at_start: u64,
+    /// Number of propagations when the current call to solve was started.
+    propagations_at_start: u64,
+    /// Flag that requests termination of the search when set.
+    terminate: Option<Arc<AtomicBool>>,
+}
+
+impl Schedule {
+    /// Limit the number of conflicts per call to solve.
+    pub fn set_conflict_budget(&mut self, budget: Option<u64>) {
+        self.conflict_budget = budget;
+    }
+
+    /// Limit the number of propagations per call to solve.
+    pub fn set_propagation_budget(&mut self, budget: Option<u64>) {
+        self.propagation_budget = budget;
+    }
+
+    /// Poll the given flag and stop searching when it is set.
+    pub fn set_terminate(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.terminate = flag;
+    }
+
+    /// Number of conflicts found so far.
+    pub fn conflicts(&self) -> u64 {
+        self.conflicts
+    }
+
+    /// Number of restarts performed so far.
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+
+    /// Number of conflicts until the next restart or `None` if restarts are disabled.
+    ///
+    /// Intervals that don't fit into a `u64` saturate.
+    fn next_restart_interval(&mut self, config: &SolverConfig) -> Option<u64> {
+        match config.restart_strategy {
+            RestartStrategy::Luby => {
+                Some(config.restart_interval.saturating_mul(self.luby.advance()))
+            }
+            RestartStrategy::Geometric => {
+                let interval = self
+                    .geometric_interval
+                    .unwrap_or(config.restart_interval as f64);
+                self.geometric_interval = Some(interval * config.restart_interval_multiplier);
+                Some(interval.min(u64::MAX as f64) as u64)
+            }
+            RestartStrategy::Fixed => Some(config.restart_interval),
+            RestartStrategy::None => None,
+        }
+    }
+
+    /// Whether termination of the search was requested.
+    fn terminate_requested(&self) -> bool {
+        self.terminate
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Whether the conflict or propagation budget of the current call to solve is used up.
+    fn budget_exhausted(&self, propagations: u64) -> bool {
+        let conflicts_exhausted = self
+            .conflict_budget
+            .is_some_and(|budget| self.conflicts - self.conflicts_at_start >= budget);
+        let propagations_exhausted = self
+            .propagation_budget
+            .is_some_and(|budget| propagations - self.propagations_at_start >= budget);
+        conflicts_exhausted || propagations_exhausted
+    }
+}
+
+/// Start counting conflicts and propagations against the budget for a new call to solve.
+pub fn reset_budget(mut ctx: partial!(Context, mut ScheduleP, TrailP)) {
+    let propagations = ctx.part(TrailP).propagations();
+    let schedule = ctx.part_mut(ScheduleP);
+    schedule.conflicts_at_start = schedule.conflicts;
+    schedule.propagations_at_start = propagations;
 }
 
 /// Perform one step of the schedule.
+///
+/// Returns false when the satisfiability of the formula is decided, an error occured, the
+/// conflict or propagation budget is exhausted or termination was requested. As the budget is only
+/// checked between conflicts, the propagation budget can be exceeded by the propagations required
+/// for a single conflict.
 pub fn schedule_step<'a>(
     mut ctx: partial!(
         Context<'a>,
@@ -38,11 +136,14 @@ pub fn schedule_step<'a>(
         mut AssignmentP,
         mut AssumptionsP,
         mut BinaryClausesP,
+        mut CallbacksP<'a>,
         mut ClauseActivityP,
         mut ClauseAllocP,
         mut ClauseDbP,
         mut ImplGraphP,
+        mut LearnStatsP,
         mut ModelP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut ScheduleP,
         mut SolverStateP,
@@ -50,6 +151,7 @@ pub fn schedule_step<'a>(
         mut TmpFlagsP,
         mut TrailP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
         SolverConfigP,
@@ -60,33 +162,49 @@ pub fn schedule_step<'a>(
 
     if ctx.part(SolverStateP).sat_state != SatState::Unknown
         || ctx.part(SolverStateP).solver_error.is_some()
+        || schedule.budget_exhausted(ctx.part(TrailP).propagations())
+        || schedule.terminate_requested()
+        || callbacks::terminate_requested(ctx.borrow())
     {
         false
     } else {
-        if schedule.conflicts > 0 && schedule.conflicts % 5000 == 0 {
-            let db = ctx.part(ClauseDbP);
-            let units = ctx.part(TrailP).top_level_assignment_count();
+        if schedule.conflicts > 0 && schedule.conflicts % config.progress_interval == 0 {
+            let stats = solver_stats(ctx.borrow(), schedule);
             info!(
                 "confl: {}k rest: {} vars: {} bin: {} irred: {} core: {} mid: {} local: {}",
-                schedule.conflicts / 1000,
-                schedule.restarts,
-                ctx.part(AssignmentP).assignment().len() - units,
-                ctx.part(BinaryClausesP).count(),
-                db.count_by_tier(Tier::Irred),
-                db.count_by_tier(Tier::Core),
-                db.count_by_tier(Tier::Mid),
-                db.count_by_tier(Tier::Local)
+                stats.conflicts / 1000,
+                stats.restarts,
+                stats.vars,
+                stats.binary_clauses,
+                stats.irred_clauses,
+                stats.core_clauses,
+                stats.mid_clauses,
+                stats.local_clauses,
             );
+            callbacks::progress(ctx.borrow(), &stats);
         }
 
-        if schedule.next_restart == schedule.conflicts {
+        if config.restart_strategy != RestartStrategy::None
+            && schedule.next_restart <= schedule.conflicts
+        {
             restart(ctx.borrow());
             schedule.restarts += 1;
-            schedule.next_restart += config.luby_restart_interval_scale * schedule.luby.advance();
+            if let Some(interval) = schedule.next_restart_interval(config) {
+                schedule.next_restart = schedule.conflicts.saturating_add(interval);
+            }
         }
 
-        if schedule.conflicts % config.reduce_locals_interval == 0 {
-            reduce_locals(ctx.borrow());
+        let too_many_locals = config
+            .max_local_clauses
+            .is_some_and(|max_count| ctx.part(ClauseDbP).count_by_tier(Tier::Local) > max_count);
+
+        if schedule.conflicts % config.reduce_locals_interval == 0 || too_many_locals {
+            update_peak_memory_usage(ctx.borrow());
+            reduce_locals(
+                ctx.borrow(),
+                config.reduce_locals_fraction,
+                config.max_local_clauses,
+            );
         }
         if schedule.conflicts % config.reduce_mids_interval == 0 {
             reduce_mids(ctx.borrow());
@@ -99,3 +217,78 @@ pub fn schedule_step<'a>(
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restart_intervals(config: &SolverConfig, count: usize) -> Vec<Option<u64>> {
+        let mut schedule = Schedule::default();
+        std::iter::repeat_with(|| schedule.next_restart_interval(config))
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn luby_restart_intervals() {
+        let config = SolverConfig {
+            restart_interval: 10,
+            ..SolverConfig::default()
+        };
+
+        assert_eq!(
+            restart_intervals(&config, 8),
+            [10, 10, 20, 10, 10, 20, 40, 10]
+                .iter()
+                .map(|&interval| Some(interval))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn geometric_and_fixed_restart_intervals() {
+        let mut config = SolverConfig {
+            restart_strategy: RestartStrategy::Geometric,
+            restart_interval: 100,
+            restart_interval_multiplier: 2.0,
+            ..SolverConfig::default()
+        };
+
+        assert_eq!(
+            restart_intervals(&config, 4),
+            vec![Some(100), Some(200), Some(400), Some(800)]
+        );
+
+        config.restart_strategy = RestartStrategy::Fixed;
+        assert_eq!(restart_intervals(&config, 3), vec![Some(100); 3]);
+
+        config.restart_strategy = RestartStrategy::None;
+        assert_eq!(restart_intervals(&config, 2), vec![None; 2]);
+    }
+
+    #[test]
+    fn huge_restart_intervals_saturate() {
+        let mut config = SolverConfig {
+            restart_strategy: RestartStrategy::Geometric,
+            restart_interval: 1 << 63,
+            restart_interval_multiplier: 1e300,
+            ..SolverConfig::default()
+        };
+
+        assert_eq!(
+            restart_intervals(&config, 4),
+            vec![
+                Some(1 << 63),
+                Some(u64::MAX),
+                Some(u64::MAX),
+                Some(u64::MAX)
+            ]
+        );
+
+        config.restart_strategy = RestartStrategy::Luby;
+        assert_eq!(
+            restart_intervals(&config, 3),
+            vec![Some(1 << 63), Some(1 << 63), Some(u64::MAX)]
+        );
+    }
+}
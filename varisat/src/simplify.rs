@@ -0,0 +1,477 @@
+//! Simplification of the clause database.
+//!
+//! Apart from pure literal elimination, the simplifications implemented here preserve all models
+//! of the formula, so variables keep their meaning and no model reconstruction is required. Pure
+//! literal elimination only fixes variables that are not sampling variables, which preserves the
+//! invariants described for [`SamplingMode`].
+use partial_ref::{partial, PartialRef};
+
+use varisat_formula::{Lit, Var};
+use varisat_internal_proof::{clause_hash, DeleteClauseProof, ProofStep};
+
+use crate::{
+    cdcl::propagate_top_level,
+    clause::{db, ClauseRef},
+    context::{parts::*, Context},
+    proof,
+    prop::{enqueue_assignment, Reason},
+    state::SatState,
+    variables::data::SamplingMode,
+};
+
+/// Simplify the formula without solving.
+///
+/// This alternates [`simplify_subsumption`] and [`eliminate_pure_literals`] until neither changes
+/// the formula.
+pub fn preprocess<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut BinaryClausesP,
+        mut CallbacksP<'a>,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+) {
+    loop {
+        simplify_subsumption(ctx.borrow());
+
+        if ctx.part(SolverStateP).sat_state == SatState::Unsat
+            || ctx.part(SolverStateP).solver_error.is_some()
+            || eliminate_pure_literals(ctx.borrow()) == 0
+        {
+            break;
+        }
+    }
+}
+
+/// Assign literals whose negation does not occur in any irredundant clause.
+///
+/// Such a pure literal can be made true without affecting satisfiability, but this removes the
+/// models in which it is false. Therefore only witness and hidden variables are considered, as the
+/// formula restricted to the sampling variables stays equivalent. Variables that are frozen or part
+/// of the current assumptions are skipped. The native proof format cannot justify these units, so
+/// nothing is done while a proof is generated.
+///
+/// The assigned variables are marked as [`pure`](crate::variables::data::VarData::pure), so they
+/// are not reported as implied units. As witness variables cannot be constrained, no later clause
+/// can contain the negation of such a literal.
+///
+/// This must be called at decision level zero. The assigned literals are enqueued but not
+/// propagated. Returns the number of assigned literals.
+pub fn eliminate_pure_literals<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AssignmentP,
+        mut ImplGraphP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut VariablesP,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        ProofP<'a>,
+    ),
+) -> usize {
+    if ctx.part(ProofP).is_active() {
+        return 0;
+    }
+
+    let (flags, mut ctx) = ctx.split_part_mut(TmpFlagsP);
+    let flags = &mut flags.flags;
+
+    for lits in ctx.part(BinaryClausesP).clauses_iter() {
+        for lit in lits.iter() {
+            flags[lit.code()] = true;
+        }
+    }
+
+    let crefs: Vec<ClauseRef> = db::clauses_iter(&ctx.borrow()).collect();
+    for cref in crefs {
+        let clause = ctx.part(ClauseAllocP).clause(cref);
+        if !clause.header().redundant() {
+            for lit in clause.lits() {
+                flags[lit.code()] = true;
+            }
+        }
+    }
+
+    let mut pure_lits = vec![];
+
+    for index in 0..ctx.part(AssignmentP).assignment().len() {
+        let var = Var::from_index(index);
+        let (positive, negative) = (flags[var.positive().code()], flags[var.negative().code()]);
+        flags[var.positive().code()] = false;
+        flags[var.negative().code()] = false;
+
+        if positive == negative || ctx.part(AssignmentP).var_value(var).is_some() {
+            continue;
+        }
+
+        let variables = ctx.part_mut(VariablesP);
+        if let Some(global) = variables.global_from_solver().get(var) {
+            let data = variables.var_data_global_mut(global);
+            if data.sampling_mode != SamplingMode::Sample && !data.frozen && !data.assumed {
+                data.pure = true;
+                pure_lits.push(var.lit(positive));
+            }
+        }
+    }
+
+    for &lit in pure_lits.iter() {
+        enqueue_assignment(ctx.borrow(), lit, Reason::Unit);
+    }
+
+    pure_lits.len()
+}
+
+/// Remove subsumed clauses and strengthen clauses using self-subsuming resolution.
+///
+/// This alternates with top level propagation, removing satisfied clauses and false literals,
+/// until no more clauses or literals are removed.
+pub fn simplify_subsumption<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut AssumptionsP,
+        mut BinaryClausesP,
+        mut CallbacksP<'a>,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+) {
+    loop {
+        propagate_top_level(ctx.borrow());
+
+        if ctx.part(SolverStateP).sat_state == SatState::Unsat
+            || ctx.part(SolverStateP).solver_error.is_some()
+            || subsume(ctx.borrow()) == 0
+        {
+            break;
+        }
+    }
+}
+
+/// Remove and strengthen long clauses using irredundant clauses.
+///
+/// All binary clauses are considered irredundant. A long clause containing all literals of an
+/// irredundant clause is removed. A long clause containing all but one literal of an irredundant
+/// clause and the negation of the remaining literal is strengthened by removing that negation.
+/// Strengthened clauses that become unit are enqueued but not propagated.
+///
+/// This must be called at decision level zero after propagating all assignments. Returns the
+/// number of removed or strengthened clauses.
+pub fn subsume<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AssignmentP,
+        mut BinaryClausesP,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut WatchlistsP,
+        VariablesP,
+    ),
+) -> usize {
+    let mut crefs: Vec<ClauseRef> = db::clauses_iter(&ctx.borrow()).collect();
+
+    let mut occurs: Vec<Vec<ClauseRef>> = vec![vec![]; ctx.part(TmpFlagsP).flags.len()];
+    for &cref in crefs.iter() {
+        for &lit in ctx.part(ClauseAllocP).clause(cref).lits() {
+            occurs[lit.code()].push(cref);
+        }
+    }
+
+    let mut changed = 0;
+
+    let binary_clauses: Vec<[Lit; 2]> = ctx.part(BinaryClausesP).clauses_iter().collect();
+
+    for lits in binary_clauses.iter() {
+        changed += simplify_with(ctx.borrow(), &occurs, lits, None);
+    }
+
+    crefs.retain(|&cref| !ctx.part(ClauseAllocP).header(cref).redundant());
+    crefs.sort_by_key(|&cref| ctx.part(ClauseAllocP).header(cref).len());
+
+    let mut lits = vec![];
+
+    for &cref in crefs.iter() {
+        let clause = ctx.part(ClauseAllocP).clause(cref);
+        if clause.header().deleted() {
+            continue;
+        }
+        lits.clear();
+        lits.extend_from_slice(clause.lits());
+        changed += simplify_with(ctx.borrow(), &occurs, &lits, Some(cref));
+    }
+
+    changed
+}
+
+/// Remove and strengthen long clauses using an irredundant clause.
+///
+/// The irredundant clause itself is passed as `cref` if it is a long clause. Returns the number of
+/// removed or strengthened clauses.
+fn simplify_with<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AssignmentP,
+        mut BinaryClausesP,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut TrailP,
+        mut WatchlistsP,
+        VariablesP,
+    ),
+    occurs: &[Vec<ClauseRef>],
+    lits: &[Lit],
+    cref: Option<ClauseRef>,
+) -> usize {
+    // Clauses containing literals of units enqueued during this pass are skipped, as they will be
+    // simplified by the next unit propagation.
+    if lits
+        .iter()
+        .any(|&lit| !ctx.part(AssignmentP).lit_is_unk(lit))
+    {
+        return 0;
+    }
+
+    let mut changed = 0;
+
+    for &lit in lits {
+        ctx.part_mut(TmpFlagsP).flags[lit.code()] = true;
+    }
+
+    let min_lit = lits
+        .iter()
+        .cloned()
+        .min_by_key(|lit| occurs[lit.code()].len());
+
+    if let Some(min_lit) = min_lit {
+        for &other in occurs[min_lit.code()].iter() {
+            if Some(other) != cref && contains_marked(ctx.borrow(), other, lits.len()) {
+                changed += remove_subsumed(ctx.borrow(), other, lits);
+            }
+        }
+    }
+
+    for &lit in lits {
+        let flags = &mut ctx.part_mut(TmpFlagsP).flags;
+        flags[lit.code()] = false;
+        flags[(!lit).code()] = true;
+
+        for &other in occurs[(!lit).code()].iter() {
+            if Some(other) != cref && contains_marked(ctx.borrow(), other, lits.len()) {
+                strengthen(ctx.borrow(), other, lits, !lit);
+                changed += 1;
+            }
+        }
+
+        let flags = &mut ctx.part_mut(TmpFlagsP).flags;
+        flags[lit.code()] = true;
+        flags[(!lit).code()] = false;
+    }
+
+    for &lit in lits {
+        ctx.part_mut(TmpFlagsP).flags[lit.code()] = false;
+    }
+
+    changed
+}
+
+/// Whether a clause is present and contains `count` marked literals and no assigned literals.
+fn contains_marked(
+    ctx: partial!(Context, AssignmentP, ClauseAllocP, TmpFlagsP),
+    cref: ClauseRef,
+    count: usize,
+) -> bool {
+    let clause = ctx.part(ClauseAllocP).clause(cref);
+    if clause.header().deleted() || clause.lits().len() < count {
+        return false;
+    }
+
+    let assignment = ctx.part(AssignmentP);
+    if clause.lits().iter().any(|&lit| !assignment.lit_is_unk(lit)) {
+        return false;
+    }
+
+    let flags = &ctx.part(TmpFlagsP).flags;
+    clause.lits().iter().filter(|lit| flags[lit.code()]).count() == count
+}
+
+/// Remove a long clause subsumed by an irredundant clause.
+///
+/// Returns the number of removed clauses.
+fn remove_subsumed<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut WatchlistsP,
+        VariablesP,
+    ),
+    cref: ClauseRef,
+    subsuming: &[Lit],
+) -> usize {
+    let clause = ctx.part(ClauseAllocP).clause(cref);
+    let redundant = clause.header().redundant();
+
+    if ctx.part(ProofP).is_active() {
+        // Proofs can only justify the deletion of an irredundant clause that is strictly
+        // subsumed.
+        if !redundant && clause.lits().len() == subsuming.len() {
+            return 0;
+        }
+
+        let subsumed = clause.lits().to_vec();
+
+        if !redundant {
+            // The proof format can only justify the deletion of an irredundant clause by a
+            // subsuming clause added in the previous step, so the subsuming clause is added
+            // again. This leaves a duplicate of it in the checker.
+            let hash = [clause_hash(subsuming)];
+            proof::add_step(
+                ctx.borrow(),
+                true,
+                &ProofStep::AtClause {
+                    redundant: false,
+                    clause: subsuming,
+                    propagation_hashes: &hash[..],
+                },
+            );
+        }
+
+        proof::add_step(
+            ctx.borrow(),
+            true,
+            &ProofStep::DeleteClause {
+                clause: &subsumed,
+                proof: if redundant {
+                    DeleteClauseProof::Redundant
+                } else {
+                    DeleteClauseProof::Simplified
+                },
+            },
+        );
+    }
+
+    db::delete_clause(ctx.borrow(), cref);
+
+    1
+}
+
+/// Remove a literal from a long clause using self-subsuming resolution with an irredundant clause.
+///
+/// The irredundant clause contains the negation of `removed_lit` and all other of its literals are
+/// contained in the strengthened clause.
+fn strengthen<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AssignmentP,
+        mut BinaryClausesP,
+        mut ClauseAllocP,
+        mut ClauseDbP,
+        mut ImplGraphP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TrailP,
+        mut WatchlistsP,
+        VariablesP,
+    ),
+    cref: ClauseRef,
+    resolvent_lits: &[Lit],
+    removed_lit: Lit,
+) {
+    let clause = ctx.part(ClauseAllocP).clause(cref);
+    let redundant = clause.header().redundant();
+
+    let new_lits: Vec<Lit> = clause
+        .lits()
+        .iter()
+        .cloned()
+        .filter(|&lit| lit != removed_lit)
+        .collect();
+
+    if ctx.part(ProofP).is_active() {
+        let hashes = [clause_hash(resolvent_lits), clause_hash(clause.lits())];
+        let old_lits = clause.lits().to_vec();
+
+        proof::add_step(
+            ctx.borrow(),
+            true,
+            &ProofStep::AtClause {
+                redundant: redundant && new_lits.len() > 2,
+                clause: &new_lits,
+                propagation_hashes: &hashes[..],
+            },
+        );
+        proof::add_step(
+            ctx.borrow(),
+            true,
+            &ProofStep::DeleteClause {
+                clause: &old_lits,
+                proof: if redundant {
+                    DeleteClauseProof::Redundant
+                } else {
+                    DeleteClauseProof::Simplified
+                },
+            },
+        );
+    }
+
+    match new_lits[..] {
+        // A long clause has at least 3 literals and the irredundant clause at least 2, so at least
+        // one literal remains.
+        [] => unreachable!(),
+        [lit] => {
+            db::delete_clause(ctx.borrow(), cref);
+            enqueue_assignment(ctx.borrow(), lit, Reason::Unit);
+        }
+        [lit_0, lit_1] => {
+            db::delete_clause(ctx.borrow(), cref);
+            ctx.part_mut(BinaryClausesP)
+                .add_binary_clause([lit_0, lit_1]);
+        }
+        ref lits => {
+            ctx.part_mut(WatchlistsP).disable();
+            let clause = ctx.part_mut(ClauseAllocP).clause_mut(cref);
+            clause.lits_mut()[..lits.len()].copy_from_slice(lits);
+            clause.header_mut().set_len(lits.len());
+        }
+    }
+}
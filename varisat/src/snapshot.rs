@@ -0,0 +1,146 @@
+//! Saving and restoring the solver state.
+use std::io;
+
+use anyhow::Error;
+use partial_ref::{partial, IntoPartialRefMut, PartialRef};
+use serde::{Deserialize, Serialize};
+
+use varisat_formula::{CnfFormula, Var};
+
+use crate::{
+    config::{SolverConfig, SolverConfigUpdate},
+    context::{config_changed, parts::*, Context},
+    export::irredundant_clauses,
+    load::load_clause,
+    variables::{self, data::SamplingMode},
+};
+
+/// Persisted solver state.
+///
+/// Variables that have no user name are stored using user variables past `var_count`.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    config: SolverConfig,
+    var_count: usize,
+    /// User variables below `var_count` that are not in use.
+    unused_vars: Vec<Var>,
+    witness_vars: Vec<Var>,
+    formula: CnfFormula,
+}
+
+/// Write the configuration, the user variables and the irredundant clauses.
+pub fn save_state(
+    mut ctx: partial!(
+        Context,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        SolverConfigP,
+        SolverStateP,
+        TrailP,
+        VariablesP,
+    ),
+    target: impl io::Write,
+) -> Result<(), Error> {
+    bincode::serialize_into(target, &take_snapshot(ctx.borrow()))?;
+
+    Ok(())
+}
+
+/// Restore a state written by [`save_state`] into an empty solver.
+pub fn load_state(ctx: &mut Context, input: impl io::Read) -> Result<(), Error> {
+    let snapshot: Snapshot = bincode::deserialize_from(input)?;
+
+    restore_snapshot(ctx, snapshot);
+
+    Ok(())
+}
+
+/// Copy the state that would be saved by [`save_state`] into an empty solver.
+pub fn fork_state(
+    mut ctx: partial!(
+        Context,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        SolverConfigP,
+        SolverStateP,
+        TrailP,
+        VariablesP,
+    ),
+    target: &mut Context,
+) {
+    restore_snapshot(target, take_snapshot(ctx.borrow()));
+}
+
+/// Collect the state persisted by [`save_state`].
+fn take_snapshot(
+    mut ctx: partial!(
+        Context,
+        BinaryClausesP,
+        ClauseAllocP,
+        ClauseDbP,
+        SolverConfigP,
+        SolverStateP,
+        TrailP,
+        VariablesP,
+    ),
+) -> Snapshot {
+    let variables = ctx.part(VariablesP);
+
+    let witness_vars = variables
+        .user_var_iter()
+        .filter(|&user_var| {
+            let global_var = variables
+                .global_from_user()
+                .get(user_var)
+                .expect("no existing global var for user var");
+            variables.var_data_global(global_var).sampling_mode == SamplingMode::Witness
+        })
+        .collect();
+
+    let unused_vars = (0..variables.user_watermark())
+        .map(Var::from_index)
+        .filter(|&user_var| variables.global_from_user().get(user_var).is_none())
+        .collect();
+
+    Snapshot {
+        config: ctx.part(SolverConfigP).clone(),
+        var_count: variables.user_watermark(),
+        unused_vars,
+        witness_vars,
+        formula: irredundant_clauses(ctx.borrow()),
+    }
+}
+
+/// Restore a snapshot into an empty solver.
+fn restore_snapshot(ctx: &mut Context, snapshot: Snapshot) {
+    ctx.solver_config = snapshot.config;
+
+    let mut ctx = ctx.into_partial_ref_mut();
+
+    config_changed(ctx.borrow(), &SolverConfigUpdate::new());
+
+    if snapshot.var_count > 0 {
+        variables::global_from_user(ctx.borrow(), Var::from_index(snapshot.var_count - 1), false);
+    }
+
+    for clause in snapshot.formula.iter() {
+        load_clause(ctx.borrow(), clause);
+    }
+
+    for &user_var in snapshot.witness_vars.iter() {
+        let global = variables::global_from_user(ctx.borrow(), user_var, false);
+        variables::set_sampling_mode(ctx.borrow(), global, SamplingMode::Witness);
+    }
+
+    let hidden_vars = (snapshot.var_count..snapshot.formula.var_count()).map(Var::from_index);
+
+    for user_var in snapshot.unused_vars.iter().cloned().chain(hidden_vars) {
+        if let Some(global) = ctx.part(VariablesP).global_from_user().get(user_var) {
+            variables::set_sampling_mode(ctx.borrow(), global, SamplingMode::Hide);
+        }
+    }
+
+    variables::truncate_user_vars(ctx.borrow(), snapshot.var_count);
+}
@@ -1,24 +1,35 @@
 //! Boolean satisfiability solver.
-use std::io;
+use std::{
+    io,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use partial_ref::{IntoPartialRef, IntoPartialRefMut, PartialRef};
 
 use anyhow::Error;
 use thiserror::Error;
 
-use varisat_checker::ProofProcessor;
-use varisat_dimacs::DimacsParser;
+use varisat_checker::{CheckerError, ProofProcessor};
+use varisat_dimacs::{DimacsHeader, DimacsParser};
 use varisat_formula::{CnfFormula, ExtendFormula, Lit, Var};
 
+pub use crate::state::SatState;
+
 use crate::{
-    assumptions::set_assumptions,
+    assumptions::{pop_assumption, push_assumption, set_assumptions},
+    cdcl::propagate_top_level,
     config::SolverConfigUpdate,
-    context::{config_changed, parts::*, Context},
+    constraints,
+    context::{self, config_changed, parts::*, update_peak_memory_usage, Context},
+    decision,
+    export::{irredundant_clause_count, irredundant_clauses, learned_clauses, top_level_units},
     load::load_clause,
     proof,
-    schedule::schedule_step,
-    state::SatState,
-    variables,
+    prop::full_restart,
+    schedule::{reset_budget, schedule_step},
+    simplify, snapshot,
+    stats::{solver_stats, SolverStats},
+    variables, xor,
 };
 
 pub use crate::proof::ProofFormat;
@@ -48,6 +59,21 @@ impl SolverError {
     }
 }
 
+/// Copy of the solver's variable mappings, returned by
+/// [`dump_var_mapping`](Solver::dump_var_mapping).
+///
+/// User variables are the variables passed to and returned by the solver. Each user variable in
+/// use is mapped to a global variable, which stays the same while the variable is hidden or
+/// eliminated. Global variables that are present in the solver are mapped to solver variables,
+/// which are used during search. The mappings are indexed by variable index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VarMappingSnapshot {
+    /// Global variable of each user variable.
+    pub global_from_user: Vec<Option<Var>>,
+    /// Solver variable of each global variable.
+    pub solver_from_global: Vec<Option<Var>>,
+}
+
 /// A boolean satisfiability solver.
 #[derive(Default)]
 pub struct Solver<'a> {
@@ -68,6 +94,64 @@ impl<'a> Solver<'a> {
         Ok(())
     }
 
+    /// Remove all clauses and variables to reuse the solver for a new formula.
+    ///
+    /// This clears everything added to or derived by the solver, including clauses, variables,
+    /// assumptions, budgets, callbacks and the termination flag. The configuration is kept. Any
+    /// active proof processing is stopped as if [`close_proof`](Solver::close_proof) was called,
+    /// ignoring errors, and is not enabled again. All buffers, e.g. for clauses, watchlists and
+    /// per-variable data, keep their capacity and are reused for the new formula.
+    pub fn reset(&mut self) {
+        let _ = self.close_proof();
+        context::reset(&mut self.ctx);
+    }
+
+    /// Set a callback that is invoked for each learned clause.
+    ///
+    /// The callback is invoked with the clause's literals right after the clause is learned during
+    /// conflict analysis, using user variable names. Variables without a user name, e.g. hidden
+    /// variables, are numbered after all user variables as for
+    /// [`clauses_iter`](Solver::clauses_iter). The empty clause is passed when the formula is found
+    /// to be unsatisfiable. The callback cannot access or modify the solver. Replaces any previously
+    /// set callback.
+    pub fn on_learned_clause(&mut self, callback: impl FnMut(&[Lit]) + 'a) {
+        self.ctx.callbacks.learned_clause = Some(Box::new(callback));
+    }
+
+    /// Save the solver state.
+    ///
+    /// This writes the configuration, the user variables with their sampling modes and the
+    /// irredundant clauses in their current simplified form, including units fixed at the top
+    /// level. Learned long clauses, variable activities and phases, assumptions, budgets and proof
+    /// settings are not saved. Use [`load_state`](Solver::load_state) to create a solver from the
+    /// saved state. Unless new clauses are added, the restored solver is satisfiable under the same
+    /// assumptions and has models that agree on all sampling variables.
+    pub fn save_state(&self, target: impl io::Write) -> Result<(), Error> {
+        let mut ctx = self.ctx.into_partial_ref();
+        snapshot::save_state(ctx.borrow(), target)
+    }
+
+    /// Create a solver from a state written by [`save_state`](Solver::save_state).
+    pub fn load_state(input: impl io::Read) -> Result<Solver<'a>, Error> {
+        let mut solver = Solver::new();
+        snapshot::load_state(&mut solver.ctx, input)?;
+        Ok(solver)
+    }
+
+    /// Create an independent copy of the loaded problem.
+    ///
+    /// The copy contains the same state that [`save_state`](Solver::save_state) writes, without
+    /// serializing it. Learned long clauses, the search state and assumptions are not copied, so
+    /// the copy searches independently of this solver. Callbacks and proof writers are not copied
+    /// either. As proof generation has to be enabled before adding clauses, the copy cannot
+    /// generate a proof.
+    pub fn fork(&self) -> Solver<'a> {
+        let mut solver = Solver::new();
+        let mut ctx = self.ctx.into_partial_ref();
+        snapshot::fork_state(ctx.borrow(), &mut solver.ctx);
+        solver
+    }
+
     /// Add a formula to the solver.
     pub fn add_formula(&mut self, formula: &CnfFormula) {
         let mut ctx = self.ctx.into_partial_ref_mut();
@@ -76,10 +160,77 @@ impl<'a> Solver<'a> {
         }
     }
 
+    /// Preallocate memory for a formula of the given size.
+    ///
+    /// This is only a hint to reduce reallocations while loading a formula, adding more variables
+    /// or clauses than specified is always possible. `vars` is the total number of variables and
+    /// `clauses` the number of clauses that will be added. The space for long clauses is estimated
+    /// assuming an average length of three literals.
+    pub fn reserve(&mut self, vars: usize, clauses: usize) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        context::reserve_var_count(ctx.borrow(), vars);
+        ctx.part_mut(ClauseAllocP).reserve(clauses, clauses * 3);
+        ctx.part_mut(ClauseDbP).reserve_irred(clauses);
+    }
+
+    /// Add multiple clauses to the solver.
+    ///
+    /// This is equivalent to calling [`add_clause`](ExtendFormula::add_clause) for each clause,
+    /// but allocates all new variables at once before loading the clauses. This avoids growing
+    /// the per variable data structures many times when adding a large number of small clauses.
+    pub fn add_clauses<'b>(&mut self, clauses: impl IntoIterator<Item = &'b [Lit]>) {
+        let clauses: Vec<&[Lit]> = clauses.into_iter().collect();
+
+        for clause in clauses.iter() {
+            variables::ensure_var_count_from_slice(clause);
+        }
+
+        let mut used = vec![];
+        for &lit in clauses.iter().flat_map(|clause| clause.iter()) {
+            if used.len() <= lit.index() {
+                used.resize(lit.index() + 1, false);
+            }
+            used[lit.index()] = true;
+        }
+
+        let user_vars: Vec<Var> = used
+            .iter()
+            .enumerate()
+            .filter(|&(_, &used)| used)
+            .map(|(index, _)| Var::from_index(index))
+            .collect();
+
+        let mut ctx = self.ctx.into_partial_ref_mut();
+
+        if ctx.part(SolverStateP).sat_state == SatState::Unsat {
+            return;
+        }
+
+        full_restart(ctx.borrow());
+        variables::solver_from_user_vars(ctx.borrow(), &user_vars, true);
+
+        for clause in clauses {
+            load_clause(ctx.borrow(), clause);
+        }
+    }
+
     /// Reads and adds a formula in DIMACS CNF format.
     ///
     /// Using this avoids creating a temporary [`CnfFormula`].
     pub fn add_dimacs_cnf(&mut self, input: impl io::Read) -> Result<(), Error> {
+        self.add_dimacs_cnf_with_header(input)?;
+        Ok(())
+    }
+
+    /// Reads and adds a formula in DIMACS CNF format, returning its header information.
+    ///
+    /// This works like [`add_dimacs_cnf`](Solver::add_dimacs_cnf) and returns the header of the
+    /// input if present, followed by the highest variable used and the number of clauses in the
+    /// input.
+    pub fn add_dimacs_cnf_with_header(
+        &mut self,
+        input: impl io::Read,
+    ) -> Result<(Option<DimacsHeader>, DimacsHeader), Error> {
         let parser = DimacsParser::parse_incremental(input, |parser| {
             self.add_formula(&parser.take_formula());
             Ok(())
@@ -91,7 +242,285 @@ impl<'a> Solver<'a> {
             parser.clause_count()
         );
 
-        Ok(())
+        Ok((parser.header(), parser.inferred_header()))
+    }
+
+    /// Writes the formula currently held by the solver in DIMACS CNF format.
+    ///
+    /// This includes the irredundant clauses in their current simplified form, all binary clauses
+    /// and a unit clause for each variable fixed at the top level. Learned long clauses are not
+    /// included. The output is equisatisfiable to the formula added to the solver, but does not
+    /// include the current assumptions. Variables that have no user name, e.g. hidden variables,
+    /// are numbered after all user variables.
+    pub fn write_dimacs(&self, mut target: impl io::Write) -> io::Result<()> {
+        let mut ctx = self.ctx.into_partial_ref();
+        let formula = irredundant_clauses(ctx.borrow());
+        varisat_dimacs::write_dimacs(&mut target, &formula)
+    }
+
+    /// Estimated number of bytes used by the solver.
+    ///
+    /// This sums the allocated capacity of the major buffers, i.e. the clause storage, including
+    /// learned clauses, the watchlists and the per variable data. It is only an estimate, as
+    /// smaller allocations and allocator overhead are not included.
+    pub fn memory_usage(&self) -> usize {
+        let mut ctx = self.ctx.into_partial_ref();
+        context::memory_usage(ctx.borrow())
+    }
+
+    /// Largest estimated memory usage observed during solving.
+    ///
+    /// The estimate of [`memory_usage`](Solver::memory_usage) is sampled periodically during each
+    /// call to [`solve`](Solver::solve) and when solve returns, so short peaks in between can be
+    /// missed.
+    pub fn peak_memory_usage(&self) -> usize {
+        self.ctx.solver_state.peak_memory_usage
+    }
+
+    /// Current statistics of the solver.
+    pub fn stats(&self) -> SolverStats {
+        let mut ctx = self.ctx.into_partial_ref();
+        let (schedule, mut ctx) = ctx.split_part(ScheduleP);
+        solver_stats(ctx.borrow(), schedule)
+    }
+
+    /// Periodically report progress during solving.
+    ///
+    /// The callback is invoked with the current statistics every
+    /// [`progress_interval`](crate::config::SolverConfig::progress_interval) conflicts. This
+    /// replaces a previously set callback.
+    pub fn set_progress_callback(&mut self, callback: impl FnMut(&SolverStats) + 'a) {
+        self.ctx.callbacks.progress = Some(Box::new(callback));
+    }
+
+    /// Add multiple new variables and return them.
+    ///
+    /// This is like [`new_vars`](ExtendFormula::new_vars), but the number of variables is given at
+    /// runtime and the variables are returned as a `Vec`. The new variables are distinct from all
+    /// variables used so far.
+    pub fn new_var_vec(&mut self, count: usize) -> Vec<Var> {
+        self.new_var_iter(count).collect()
+    }
+
+    /// Add multiple new variables and return them as positive literals.
+    ///
+    /// See [`new_var_vec`](Solver::new_var_vec).
+    pub fn new_lit_vec(&mut self, count: usize) -> Vec<Lit> {
+        self.new_lit_iter(count).collect()
+    }
+
+    /// Number of user variables in use.
+    ///
+    /// This is one more than the highest index of a user variable that was added to the solver,
+    /// or zero if no variables were added.
+    pub fn num_vars(&self) -> usize {
+        self.ctx.variables.user_watermark()
+    }
+
+    /// Number of irredundant clauses currently held by the solver.
+    ///
+    /// This is the number of clauses yielded by [`clauses_iter`](Solver::clauses_iter). It
+    /// includes unit clauses for variables fixed at the top level, but no learned long clauses.
+    pub fn num_clauses(&self) -> usize {
+        let mut ctx = self.ctx.into_partial_ref();
+        irredundant_clause_count(ctx.borrow())
+    }
+
+    /// Iterator over the irredundant clauses currently held by the solver.
+    ///
+    /// This yields the same clauses that [`write_dimacs`](Solver::write_dimacs) writes: the
+    /// irredundant clauses in their current simplified form, all binary clauses, including learned
+    /// ones, and a unit clause for each variable fixed at the top level. Learned long clauses are
+    /// available through [`learned_clauses_iter`](Solver::learned_clauses_iter). Variables that
+    /// have no user name are numbered after all user variables.
+    pub fn clauses_iter(&self) -> impl Iterator<Item = Vec<Lit>> {
+        let mut ctx = self.ctx.into_partial_ref();
+        let formula = irredundant_clauses(ctx.borrow());
+        let clauses: Vec<_> = formula.iter().map(|clause| clause.to_vec()).collect();
+        clauses.into_iter()
+    }
+
+    /// Iterator over the learned long clauses currently held by the solver.
+    ///
+    /// Learned clauses are implied by the irredundant clauses, so they can be added to the output
+    /// of [`clauses_iter`](Solver::clauses_iter) without changing satisfiability.
+    pub fn learned_clauses_iter(&self) -> impl Iterator<Item = Vec<Lit>> {
+        let mut ctx = self.ctx.into_partial_ref();
+        let formula = learned_clauses(ctx.borrow());
+        let clauses: Vec<_> = formula.iter().map(|clause| clause.to_vec()).collect();
+        clauses.into_iter()
+    }
+
+    /// Add an XOR constraint to the solver.
+    ///
+    /// The constraint is satisfied if the number of true literals in `lits` is odd when `rhs` is
+    /// true or even when `rhs` is false. It is added as a set of clauses. Long constraints are split
+    /// using auxiliary variables, which are hidden afterwards and thus don't appear in models.
+    pub fn add_xor_clause(&mut self, lits: &[Lit], rhs: bool) {
+        self.map_constraint_vars(lits);
+        for aux_var in xor::add_xor_clause(self, lits, rhs) {
+            self.hide_var(aux_var);
+        }
+    }
+
+    /// Add a constraint that allows at most one of the given literals to be true.
+    ///
+    /// Long constraints use auxiliary variables, which are hidden afterwards and thus don't appear
+    /// in models.
+    pub fn add_at_most_one(&mut self, lits: &[Lit]) {
+        self.map_constraint_vars(lits);
+        for aux_var in constraints::add_at_most_one(self, lits) {
+            self.hide_var(aux_var);
+        }
+    }
+
+    /// Add a constraint that allows at most `k` of the given literals to be true.
+    ///
+    /// Literals that occur multiple times are counted multiple times. The constraint is encoded
+    /// using auxiliary variables, which are hidden afterwards and thus don't appear in models.
+    pub fn add_at_most_k(&mut self, lits: &[Lit], k: usize) {
+        self.map_constraint_vars(lits);
+        for aux_var in constraints::add_at_most_k(self, lits, k) {
+            self.hide_var(aux_var);
+        }
+    }
+
+    /// Add a clause that can be disabled later.
+    ///
+    /// This allocates a fresh activation variable `a`, adds the clause `lits ∨ ¬a` and returns the
+    /// literal `a`. The clause is only enforced while `a` is assumed, assuming `¬a` or not assuming
+    /// `a` disables it. To permanently remove the clause use [`retract`](Solver::retract).
+    ///
+    /// The activation variable stays a user variable, as it has to be passed to
+    /// [`assume`](Solver::assume), and it is frozen so that simplifications don't eliminate it
+    /// while the clause can still be enabled. Unlike the auxiliary variables of constraints it
+    /// therefore can't be hidden and appears in [`model`](Solver::model), where it is true only if
+    /// it was assumed or implied. To count or enumerate the models of the formula itself, pass only
+    /// the formula's variables to [`count_models_projected`](Solver::count_models_projected) or
+    /// [`model_projected`](Solver::model_projected).
+    pub fn add_retractable_clause(&mut self, lits: &[Lit]) -> Lit {
+        self.map_constraint_vars(lits);
+        let activation = self.new_var();
+        self.freeze_var(activation);
+
+        let mut clause = lits.to_vec();
+        clause.push(activation.negative());
+        self.add_clause(&clause);
+
+        activation.positive()
+    }
+
+    /// Permanently disable a clause added by
+    /// [`add_retractable_clause`](Solver::add_retractable_clause).
+    ///
+    /// This adds the unit clause `¬a` for the activation literal `a`, so the activation literal
+    /// must not be assumed afterwards.
+    pub fn retract(&mut self, activation: Lit) {
+        self.add_clause(&[!activation]);
+        self.melt_var(activation.var());
+    }
+
+    /// Allocate the variables of a constraint.
+    ///
+    /// This makes sure that auxiliary variables allocated during the encoding of the constraint
+    /// don't collide with variables of the constraint.
+    fn map_constraint_vars(&mut self, lits: &[Lit]) {
+        self.ctx.solver_state.formula_is_empty = false;
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        for &lit in lits {
+            variables::global_from_user(ctx.borrow(), lit.var(), false);
+        }
+    }
+
+    /// Set the preferred polarity of a variable.
+    ///
+    /// The next time the solver decides the variable of `lit`, it assigns the polarity that makes
+    /// `lit` true. As the solver uses phase saving, later decisions may use a different polarity.
+    /// Assumptions and propagations take precedence over the preferred phase.
+    ///
+    /// This has no effect for variables that do not appear in any clause or assumption yet.
+    pub fn set_phase(&mut self, lit: Lit) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        if let Some(global) = ctx.part(VariablesP).global_from_user().get(lit.var()) {
+            decision::set_phase(ctx.borrow(), global, Some(lit.is_positive()));
+        }
+    }
+
+    /// Restore the default preferred polarity of a variable.
+    ///
+    /// This undoes [`set_phase`](Solver::set_phase). It also resets the polarity saved from
+    /// previous assignments of the variable.
+    pub fn clear_phase(&mut self, var: Var) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        if let Some(global) = ctx.part(VariablesP).global_from_user().get(var) {
+            decision::set_phase(ctx.borrow(), global, None);
+        }
+    }
+
+    /// Suggest an assignment to try first.
+    ///
+    /// This sets the saved phase of the variables in `lits`, so the solver assigns the polarity
+    /// that makes each literal true the next time it decides that variable. Unlike assumptions, the
+    /// hint does not constrain the formula. Unlike [`set_phase`](Solver::set_phase) it is not kept
+    /// as the preferred polarity, so phase saving overwrites it when the search backtracks. If the
+    /// hint is a complete model of the formula, the next call to [`solve`](Solver::solve) finds it
+    /// without any conflicts.
+    ///
+    /// Literals of variables that do not appear in any clause or assumption are ignored. The result
+    /// of the last call to [`solve`](Solver::solve) and its model stay available until the next
+    /// call, which restarts the search from the hint even if the formula was already solved.
+    pub fn set_assignment_hint(&mut self, lits: &[Lit]) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+
+        for &lit in lits {
+            let variables = ctx.part(VariablesP);
+            let solver_var = variables
+                .global_from_user()
+                .get(lit.var())
+                .and_then(|global| variables.solver_from_global().get(global));
+            if let Some(solver_var) = solver_var {
+                ctx.part_mut(AssignmentP)
+                    .set_saved_phase(solver_var, lit.is_positive());
+            }
+        }
+
+        ctx.part_mut(SolverStateP).hint_pending = true;
+    }
+
+    /// Protect a variable from being eliminated by simplifications.
+    ///
+    /// A frozen variable keeps its meaning across calls to [`solve`](Solver::solve), so clauses
+    /// containing it can be added in later incremental rounds. A variable stays frozen until
+    /// [`melt_var`](Solver::melt_var) is called.
+    ///
+    /// Currently only the pure literal elimination of [`preprocess`](Solver::preprocess) removes
+    /// variables, which fixes witness or hidden variables instead of keeping their meaning.
+    pub fn freeze_var(&mut self, var: Var) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        let global = variables::global_from_user(ctx.borrow(), var, false);
+        ctx.part_mut(VariablesP).var_data_global_mut(global).frozen = true;
+    }
+
+    /// Allow simplifications to eliminate a variable frozen by [`freeze_var`](Solver::freeze_var).
+    pub fn melt_var(&mut self, var: Var) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        if let Some(global) = ctx.part(VariablesP).global_from_user().get(var) {
+            ctx.part_mut(VariablesP).var_data_global_mut(global).frozen = false;
+        }
+    }
+
+    /// Set whether the decision heuristic may choose a variable.
+    ///
+    /// A variable disabled for decisions is never decided while a variable enabled for decisions
+    /// is unassigned. Once all enabled variables are assigned, any remaining unassigned disabled
+    /// variables are decided in a fixed order using their saved phase, so a found model still
+    /// assigns every variable. This is useful for auxiliary variables of an encoding, e.g. Tseitin
+    /// variables, which are implied by the other variables. All variables are enabled for decisions
+    /// by default.
+    pub fn set_decision_var(&mut self, var: Var, enabled: bool) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        let global = variables::global_from_user(ctx.borrow(), var, false);
+        decision::set_decision_var(ctx.borrow(), global, enabled);
     }
 
     /// Sets the "witness" sampling mode for a variable.
@@ -135,7 +564,21 @@ impl<'a> Solver<'a> {
     }
 
     /// Check the satisfiability of the current formula.
+    ///
+    /// Returns `Err(SolverError::Interrupted)` when solving stopped before finding an answer. See
+    /// [`solve_limited`](Solver::solve_limited) for a variant that returns such an indeterminate
+    /// result as a value.
     pub fn solve(&mut self) -> Result<bool, SolverError> {
+        self.solve_limited()?.ok_or(SolverError::Interrupted)
+    }
+
+    /// Check the satisfiability of the current formula, allowing an indeterminate result.
+    ///
+    /// Returns `Ok(Some(true))` if the formula is satisfiable, `Ok(Some(false))` if it is
+    /// unsatisfiable, possibly under the current assumptions, and `Ok(None)` if solving stopped
+    /// because a budget was exhausted or termination was requested. Errors are only returned for
+    /// failures of the proof output or of proof processors, which are not recoverable.
+    pub fn solve_limited(&mut self) -> Result<Option<bool>, SolverError> {
         self.ctx.solver_state.solver_invoked = true;
 
         let mut ctx = self.ctx.into_partial_ref_mut();
@@ -144,17 +587,253 @@ impl<'a> Solver<'a> {
             "solve() called after encountering an unrecoverable error"
         );
 
+        reset_budget(ctx.borrow());
+
+        if ctx.part(SolverStateP).hint_pending {
+            let state = ctx.part_mut(SolverStateP);
+            state.hint_pending = false;
+            if state.sat_state == SatState::Sat {
+                state.sat_state = SatState::Unknown;
+            }
+            full_restart(ctx.borrow());
+        }
+
         while schedule_step(ctx.borrow()) {}
 
+        update_peak_memory_usage(ctx.borrow());
+
         proof::solve_finished(ctx.borrow());
 
         self.check_for_solver_error()?;
 
         match self.ctx.solver_state.sat_state {
-            SatState::Unknown => Err(SolverError::Interrupted),
-            SatState::Sat => Ok(true),
-            SatState::Unsat | SatState::UnsatUnderAssumptions => Ok(false),
+            SatState::Unknown => Ok(None),
+            SatState::Sat => Ok(Some(true)),
+            SatState::Unsat | SatState::UnsatUnderAssumptions => Ok(Some(false)),
+        }
+    }
+
+    /// Result of the last call to [`solve`](Solver::solve) without solving again.
+    ///
+    /// Returns `None` if the solver was not invoked since the formula or the assumptions changed,
+    /// or if the last call was interrupted. See [`sat_state`](Solver::sat_state) to distinguish
+    /// unsatisfiability under assumptions.
+    pub fn last_result(&self) -> Option<bool> {
+        match self.ctx.solver_state.sat_state {
+            SatState::Unknown => None,
+            SatState::Sat => Some(true),
+            SatState::Unsat | SatState::UnsatUnderAssumptions => Some(false),
+        }
+    }
+
+    /// Current satisfiability state.
+    ///
+    /// The results of [`model`](Solver::model) and [`failed_core`](Solver::failed_core) are
+    /// available when the state is [`SatState::Sat`] or one of the unsatisfiable states
+    /// respectively.
+    ///
+    /// A state of [`SatState::Unsat`] is permanent, as adding clauses or changing assumptions
+    /// cannot make the formula satisfiable again, while [`SatState::UnsatUnderAssumptions`] only
+    /// holds for the current assumptions.
+    pub fn sat_state(&self) -> SatState {
+        self.ctx.solver_state.sat_state
+    }
+
+    /// Current satisfiability state.
+    ///
+    /// This is an alias of [`sat_state`](Solver::sat_state).
+    pub fn state(&self) -> SatState {
+        self.sat_state()
+    }
+
+    /// Propagate the current formula without making any decisions.
+    ///
+    /// This performs unit propagation at the top level, ignoring the current assumptions, and
+    /// returns the literals that became implied by it. Literals of variables without a user name
+    /// are omitted. Literals that were already propagated before, e.g. during a previous call to
+    /// this method or to [`solve`](Solver::solve), are not returned again. No decisions are made
+    /// and the decision heuristic is not affected.
+    ///
+    /// Returns `Ok(None)` if propagation shows that the formula is unsatisfiable.
+    pub fn propagate(&mut self) -> Result<Option<Vec<Lit>>, SolverError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        assert!(
+            !ctx.part_mut(SolverStateP).state_is_invalid,
+            "propagate() called after encountering an unrecoverable error"
+        );
+
+        let implied = propagate_top_level(ctx.borrow());
+
+        self.check_for_solver_error()?;
+
+        if self.ctx.solver_state.sat_state == SatState::Unsat {
+            return Ok(None);
         }
+
+        let variables = &self.ctx.variables;
+        Ok(Some(
+            implied
+                .into_iter()
+                .flat_map(|lit| {
+                    let user = variables.user_from_global().get(lit.var())?;
+                    Some(user.lit(lit.is_positive()))
+                })
+                .collect(),
+        ))
+    }
+
+    /// Simplify the formula without solving.
+    ///
+    /// This performs the simplifications of
+    /// [`simplify_subsumption`](Solver::simplify_subsumption) and pure literal elimination,
+    /// repeating them until nothing changes. The simplified formula can be accessed using
+    /// [`clauses_iter`](Solver::clauses_iter) or [`write_dimacs`](Solver::write_dimacs).
+    ///
+    /// Pure literal elimination fixes each literal whose negation does not occur in any clause. Only
+    /// witness and hidden variables are eliminated, so the formula stays equivalent when restricted
+    /// to the sampling variables and each model of the simplified formula is a model of the
+    /// original formula. Witness variables cannot be constrained by later clauses or assumptions
+    /// and an eliminated variable cannot be turned into a sampling variable again. The fixed values
+    /// are not implied by the formula, so they are not included in
+    /// [`implied_units`](Solver::implied_units). Variables protected using
+    /// [`freeze_var`](Solver::freeze_var) and variables of the current assumptions are not
+    /// eliminated. Pure literal elimination is skipped while a proof is generated, as the proof
+    /// format cannot justify it.
+    pub fn preprocess(&mut self) -> Result<(), SolverError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        assert!(
+            !ctx.part_mut(SolverStateP).state_is_invalid,
+            "preprocess() called after encountering an unrecoverable error"
+        );
+
+        simplify::preprocess(ctx.borrow());
+
+        self.check_for_solver_error()
+    }
+
+    /// Simplify the formula using only subsumption.
+    ///
+    /// This runs unit propagation at the top level, ignoring the current assumptions, removes
+    /// clauses subsumed by other irredundant clauses and removes literals using self-subsuming
+    /// resolution, repeating until nothing changes. When proof generation is enabled, all removals
+    /// are justified in the proof. Unlike [`preprocess`](Solver::preprocess) this will never
+    /// include other simplifications.
+    pub fn simplify_subsumption(&mut self) -> Result<(), SolverError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        assert!(
+            !ctx.part_mut(SolverStateP).state_is_invalid,
+            "simplify_subsumption() called after encountering an unrecoverable error"
+        );
+
+        simplify::simplify_subsumption(ctx.borrow());
+
+        self.check_for_solver_error()
+    }
+
+    /// Literals fixed at the top level.
+    ///
+    /// These are the literals assigned at decision level zero, i.e. implied by the formula
+    /// independent of any assumptions. This includes units derived during previous calls to
+    /// [`solve`](Solver::solve) or [`propagate`](Solver::propagate). Literals of variables without
+    /// a user name are omitted, as are the values chosen by the pure literal elimination of
+    /// [`preprocess`](Solver::preprocess), which are not implied.
+    pub fn implied_units(&self) -> Vec<Lit> {
+        let mut ctx = self.ctx.into_partial_ref();
+        top_level_units(ctx.borrow())
+    }
+
+    /// Current decision level of the search.
+    ///
+    /// This is the number of decisions on the trail. It is only meaningful while the search is
+    /// paused, e.g. after [`solve`](Solver::solve) was interrupted, and intended for diagnostics.
+    pub fn decision_level(&self) -> usize {
+        self.ctx.trail.current_level()
+    }
+
+    /// Number of assigned literals on the trail.
+    ///
+    /// The trail contains assignments of solver internal variables, which do not correspond to
+    /// user variables and can include variables without a user name. Assignments fixed at the top
+    /// level can be removed from the trail during simplification, so this can be smaller than the
+    /// number of assigned variables. Like [`decision_level`](Solver::decision_level), this is
+    /// intended for diagnostics.
+    pub fn trail_len(&self) -> usize {
+        self.ctx.trail.trail().len()
+    }
+
+    /// VSIDS activity of a variable.
+    ///
+    /// Variables involved in many recent conflicts have a high activity. Activities of all
+    /// variables decay after each conflict, so they are only meaningful after at least one call to
+    /// [`solve`](Solver::solve) and mostly reflect the most recent conflicts. Activities are only
+    /// updated while the VSIDS [decision
+    /// heuristic](crate::config::SolverConfig::decision_heuristic) is used. Returns zero for
+    /// variables that are not present in the solver.
+    pub fn var_activity(&self, var: Var) -> f64 {
+        let variables = &self.ctx.variables;
+        variables
+            .global_from_user()
+            .get(var)
+            .and_then(|global| variables.solver_from_global().get(global))
+            .map(|solver| self.ctx.vsids.activity(solver))
+            .unwrap_or(0.0)
+    }
+
+    /// User variables present in the solver together with their VSIDS activity.
+    ///
+    /// The variables are ordered by decreasing activity. See
+    /// [`var_activity`](Solver::var_activity).
+    pub fn activity_ranking(&self) -> Vec<(Var, f64)> {
+        let variables = &self.ctx.variables;
+        let mut ranking: Vec<_> = variables
+            .user_var_iter()
+            .flat_map(|user| {
+                let global = variables.global_from_user().get(user)?;
+                let solver = variables.solver_from_global().get(global)?;
+                Some((user, self.ctx.vsids.activity(solver)))
+            })
+            .collect();
+        ranking.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranking
+    }
+
+    /// Limit the number of conflicts per call to [`solve`](Solver::solve).
+    ///
+    /// When a call to solve reaches this many conflicts without deciding satisfiability, it returns
+    /// `Err(SolverError::Interrupted)`. The solver stays usable and a subsequent call to solve
+    /// continues the search from where it stopped, with a fresh budget. Passing `None` removes the
+    /// limit.
+    pub fn set_conflict_budget(&mut self, budget: Option<u64>) {
+        self.ctx.schedule.set_conflict_budget(budget);
+    }
+
+    /// Limit the number of propagations per call to [`solve`](Solver::solve).
+    ///
+    /// This behaves like [`set_conflict_budget`](Solver::set_conflict_budget), but counts
+    /// propagated assignments. The budget is only checked between conflicts, so it can be exceeded
+    /// by the propagations required to reach the next conflict.
+    pub fn set_propagation_budget(&mut self, budget: Option<u64>) {
+        self.ctx.schedule.set_propagation_budget(budget);
+    }
+
+    /// Interrupt solving when the given flag is set.
+    ///
+    /// The flag is polled before each conflict, so the latency of an interruption is roughly the
+    /// time the solver needs to find a single conflict, which is usually well below a millisecond.
+    /// This makes it possible to cancel a call to [`solve`](Solver::solve) from another thread.
+    /// When the flag is observed as set, solve returns `Err(SolverError::Interrupted)` and the
+    /// solver stays usable. The flag is not reset by the solver, so it has to be cleared before
+    /// solving again.
+    pub fn set_terminate(&mut self, flag: Arc<AtomicBool>) {
+        self.ctx.schedule.set_terminate(Some(flag));
+    }
+
+    /// Interrupt solving when the given callback returns true.
+    ///
+    /// This behaves like [`set_terminate`](Solver::set_terminate), but the callback is invoked
+    /// before each conflict instead of polling a flag. This replaces a previously set callback.
+    pub fn set_terminate_callback(&mut self, callback: impl FnMut() -> bool + 'a) {
+        self.ctx.callbacks.terminate = Some(Box::new(callback));
     }
 
     /// Check for asynchronously generated errors.
@@ -183,7 +862,32 @@ impl<'a> Solver<'a> {
         set_assumptions(ctx.borrow(), assumptions);
     }
 
+    /// Add an assumption for future calls to solve.
+    ///
+    /// The assumptions form a stack: this adds `lit` to the current assumptions without replacing
+    /// them and [`pop_assumption`](Solver::pop_assumption) removes the most recently added one.
+    /// Calls to [`assume`](Solver::assume) replace the whole stack.
+    ///
+    /// Changing the assumptions discards the result of the previous call to solve, so
+    /// [`failed_core`](Solver::failed_core) returns `None` until solve is called again, unless the
+    /// formula is unsatisfiable without assumptions.
+    pub fn push_assumption(&mut self, lit: Lit) {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        push_assumption(ctx.borrow(), lit);
+    }
+
+    /// Remove the most recently added assumption.
+    ///
+    /// Returns false if there are no assumptions. See
+    /// [`push_assumption`](Solver::push_assumption).
+    pub fn pop_assumption(&mut self) -> bool {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        pop_assumption(ctx.borrow())
+    }
+
     /// Set of literals that satisfy the formula.
+    ///
+    /// Returns `None` unless the [state](Solver::sat_state) is [`SatState::Sat`].
     pub fn model(&self) -> Option<Vec<Lit>> {
         let ctx = self.ctx.into_partial_ref();
         if ctx.part(SolverStateP).sat_state == SatState::Sat {
@@ -206,38 +910,272 @@ impl<'a> Solver<'a> {
         }
     }
 
-    /// Subset of the assumptions that made the formula unsatisfiable.
+    /// Set of literals that satisfy the formula, including solver internal variables.
     ///
-    /// This is not guaranteed to be minimal and may just return all assumptions every time.
-    pub fn failed_core(&self) -> Option<&[Lit]> {
-        match self.ctx.solver_state.sat_state {
-            SatState::UnsatUnderAssumptions => Some(self.ctx.assumptions.user_failed_core()),
-            SatState::Unsat => Some(&[]),
-            SatState::Unknown | SatState::Sat => None,
+    /// This first turns all hidden and internal variables into witness variables using
+    /// [`observe_internal_vars`](Solver::observe_internal_vars), so it needs mutable access and the
+    /// newly visible variables also appear in later calls to [`model`](Solver::model). This is
+    /// useful for debugging encodings with auxiliary variables. The user variables assigned to
+    /// previously hidden variables depend on the order of operations and are not stable across
+    /// runs.
+    ///
+    /// Returns `None` unless the [state](Solver::sat_state) is [`SatState::Sat`].
+    pub fn model_with_internal(&mut self) -> Option<Vec<Lit>> {
+        if self.ctx.solver_state.sat_state != SatState::Sat {
+            return None;
         }
+        self.observe_internal_vars();
+        self.model()
     }
 
-    /// Generate a proof of unsatisfiability during solving.
+    /// Copy of the mappings between user, global and solver variables.
     ///
-    /// This needs to be called before any clauses are added.
-    pub fn write_proof(&mut self, target: impl io::Write + 'a, format: ProofFormat) {
-        assert!(
-            self.ctx.solver_state.formula_is_empty,
-            "called after clauses were added"
-        );
-        self.ctx.proof.write_proof(target, format);
+    /// This is intended for debugging, the mappings are an implementation detail and can change
+    /// whenever variables are added, hidden, observed or removed by simplifications.
+    pub fn dump_var_mapping(&self) -> VarMappingSnapshot {
+        let variables = &self.ctx.variables;
+        VarMappingSnapshot {
+            global_from_user: variables.global_from_user().to_vec(),
+            solver_from_global: variables.solver_from_global().to_vec(),
+        }
     }
 
-    /// Stop generating a proof of unsatisfiability.
+    /// Subset of the current model, restricted to the given variables.
     ///
-    /// This also flushes internal buffers and closes the target file.
-    pub fn close_proof(&mut self) -> Result<(), SolverError> {
-        let mut ctx = self.ctx.into_partial_ref_mut();
-        proof::close_proof(ctx.borrow());
-        self.check_for_solver_error()
-    }
-
-    /// Generate and check a proof on the fly.
+    /// Variables that are not assigned in the model, for example because they were never used, are
+    /// omitted.
+    ///
+    /// Returns `None` if the solver state is not SAT.
+    pub fn model_projected(&self, vars: &[Var]) -> Option<Vec<Lit>> {
+        let ctx = self.ctx.into_partial_ref();
+        if ctx.part(SolverStateP).sat_state == SatState::Sat {
+            Some(
+                vars.iter()
+                    .flat_map(|&user_var| {
+                        let global_var = ctx.part(VariablesP).global_from_user().get(user_var)?;
+                        ctx.part(ModelP).assignment()[global_var.index()]
+                            .map(|value| user_var.lit(value))
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Clause that excludes the current model.
+    ///
+    /// This is the negation of the current model, restricted to user variables that appear in the
+    /// formula. Variables that don't appear in any clause and variables that are fixed by unit
+    /// clauses are left out, so the excluded region is as large as possible.
+    /// Adding the returned clause and solving again finds a model that differs from the current one
+    /// in at least one of the remaining variables. This can be used to enumerate models.
+    ///
+    /// Note that adding the returned clause permanently changes the formula. To enumerate models
+    /// restricted to a subset of the variables, negate the literals returned by
+    /// [`model_projected`](Solver::model_projected) instead.
+    ///
+    /// Returns `None` if the solver state is not SAT.
+    pub fn model_blocking_clause(&self) -> Option<Vec<Lit>> {
+        let ctx = self.ctx.into_partial_ref();
+        if ctx.part(SolverStateP).sat_state == SatState::Sat {
+            Some(
+                ctx.part(VariablesP)
+                    .user_var_iter()
+                    .flat_map(|user_var| {
+                        let global_var = ctx
+                            .part(VariablesP)
+                            .global_from_user()
+                            .get(user_var)
+                            .expect("no existing global var for user var");
+                        if ctx.part(VariablesP).var_data_global(global_var).isolated {
+                            None
+                        } else {
+                            ctx.part(ModelP).assignment()[global_var.index()]
+                                .map(|value| user_var.lit(!value))
+                        }
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Subset of the assumptions that made the formula unsatisfiable.
+    ///
+    /// This is not guaranteed to be minimal and may just return all assumptions every time. Returns
+    /// an empty core if the [state](Solver::sat_state) is [`SatState::Unsat`] and `None` unless the
+    /// state is unsatisfiable.
+    pub fn failed_core(&self) -> Option<&[Lit]> {
+        match self.ctx.solver_state.sat_state {
+            SatState::UnsatUnderAssumptions => Some(self.ctx.assumptions.user_failed_core()),
+            SatState::Unsat => Some(&[]),
+            SatState::Unknown | SatState::Sat => None,
+        }
+    }
+
+    /// Minimal subset of the assumptions that makes the formula unsatisfiable.
+    ///
+    /// This needs to be called after a call to [`solve`](Solver::solve) returned false. Starting
+    /// from the [`failed_core`](Solver::failed_core), each candidate literal is removed in turn and
+    /// the remaining assumptions are solved again. A literal is kept only if the formula becomes
+    /// satisfiable without it, so removing any literal of the result makes the formula
+    /// satisfiable.
+    ///
+    /// This replaces the current assumptions by the returned core and requires one call to solve
+    /// per literal of the failed core. Returns `None` if the formula is not known to be
+    /// unsatisfiable under the current assumptions or if solving fails.
+    pub fn minimize_core(&mut self) -> Option<Vec<Lit>> {
+        let mut candidates = self.failed_core()?.to_owned();
+        let mut core: Vec<Lit> = vec![];
+        let mut assumptions = vec![];
+
+        while let Some(candidate) = candidates.pop() {
+            assumptions.clear();
+            assumptions.extend_from_slice(&core);
+            assumptions.extend_from_slice(&candidates);
+            self.assume(&assumptions);
+
+            if self.solve().ok()? {
+                core.push(candidate);
+            } else {
+                let failed_core = self.failed_core()?;
+                candidates.clear();
+                candidates.extend(failed_core.iter().filter(|lit| !core.contains(lit)));
+            }
+        }
+
+        core.sort_unstable();
+
+        self.assume(&core);
+        if self.solve().ok()? {
+            return None;
+        }
+
+        Some(core)
+    }
+
+    /// Literals that are true in every model of the formula.
+    ///
+    /// This first solves the formula without assumptions and then checks each literal of the found
+    /// model by assuming its negation and solving again. Literals for which this is unsatisfiable
+    /// are part of the backbone. Each satisfiable call removes all candidates not contained in the
+    /// new model. Literals fixed at the top level are not checked again.
+    ///
+    /// This replaces the current assumptions and clears them when done. It requires one call to
+    /// [`solve`](Solver::solve) per remaining candidate, so it can be expensive. Returns an empty
+    /// vector if the formula is unsatisfiable. Variables without a user name are not included.
+    pub fn backbone(&mut self) -> Result<Vec<Lit>, SolverError> {
+        self.assume(&[]);
+
+        if !self.solve()? {
+            return Ok(vec![]);
+        }
+
+        let candidates = self.model().expect("no model after satisfiable solve");
+        let mut backbone = self.implied_units();
+
+        let mut is_candidate = vec![false; self.num_vars() * 2];
+        for &lit in candidates.iter() {
+            is_candidate[lit.code()] = true;
+        }
+        for &lit in backbone.iter() {
+            is_candidate[lit.code()] = false;
+        }
+
+        for &lit in candidates.iter() {
+            if !is_candidate[lit.code()] {
+                continue;
+            }
+            self.assume(&[!lit]);
+            if self.solve()? {
+                for model_lit in self.model().expect("no model after satisfiable solve") {
+                    is_candidate[(!model_lit).code()] = false;
+                }
+            } else {
+                backbone.push(lit);
+            }
+        }
+
+        self.assume(&[]);
+
+        backbone.sort_unstable();
+
+        Ok(backbone)
+    }
+
+    /// Number of assignments to the given variables that can be extended to a model.
+    ///
+    /// This enumerates models by repeatedly solving and adding a clause that excludes the
+    /// assignment of `vars` in the found model, until the formula becomes unsatisfiable or `limit`
+    /// models were found. It is exact but requires one call to [`solve`](Solver::solve) per model,
+    /// so it is only suitable for a small number of models. The current assumptions are respected.
+    ///
+    /// The blocking clauses are added permanently, so after this call the formula has no models
+    /// left among those counted. Count on a copy of the formula or [`reset`](Solver::reset) the
+    /// solver to reuse it. The given variables must be sampling variables.
+    pub fn count_models_projected(
+        &mut self,
+        vars: &[Var],
+        limit: Option<u64>,
+    ) -> Result<u64, SolverError> {
+        let lits: Vec<Lit> = vars.iter().map(|var| var.positive()).collect();
+        self.map_constraint_vars(&lits);
+
+        let mut count = 0;
+
+        while limit.is_none_or(|limit| count < limit) && self.solve()? {
+            count += 1;
+
+            let blocking_clause: Vec<Lit> = self
+                .model_projected(vars)
+                .expect("no model after satisfiable solve")
+                .into_iter()
+                .map(|lit| !lit)
+                .collect();
+
+            self.add_clause(&blocking_clause);
+        }
+
+        Ok(count)
+    }
+
+    /// Generate a proof of unsatisfiability during solving.
+    ///
+    /// This needs to be called before any clauses are added. DRAT proofs cannot record clauses
+    /// added after solving, so a DRAT proof refers to the formula including all added clauses.
+    pub fn write_proof(&mut self, target: impl io::Write + 'a, format: ProofFormat) {
+        assert!(
+            self.ctx.solver_state.formula_is_empty,
+            "called after clauses were added"
+        );
+        self.ctx.proof.write_proof(target, format);
+    }
+
+    /// Stop generating a proof of unsatisfiability.
+    ///
+    /// This also flushes internal buffers and closes the target file.
+    pub fn close_proof(&mut self) -> Result<(), SolverError> {
+        let mut ctx = self.ctx.into_partial_ref_mut();
+        proof::close_proof(ctx.borrow());
+        self.check_for_solver_error()
+    }
+
+    /// Error encountered while writing the proof, if any.
+    ///
+    /// Proof steps are buffered and written whenever the buffer is full, so writing can fail while
+    /// adding clauses or solving. Such an error stops proof generation and is returned by the next
+    /// call to [`solve`](Solver::solve) or [`close_proof`](Solver::close_proof). This allows
+    /// checking for the error before that without clearing it.
+    pub fn proof_writer_error(&self) -> Option<&io::Error> {
+        match &self.ctx.solver_state.solver_error {
+            Some(SolverError::ProofIoError { cause }) => Some(cause),
+            _ => None,
+        }
+    }
+
+    /// Generate and check a proof on the fly.
     ///
     /// This needs to be called before any clauses are added.
     pub fn enable_self_checking(&mut self) {
@@ -248,6 +1186,30 @@ impl<'a> Solver<'a> {
         self.ctx.proof.begin_checking();
     }
 
+    /// Generate and check a proof on the fly, recording failed checks.
+    ///
+    /// This behaves like [`enable_self_checking`](Solver::enable_self_checking), but instead of
+    /// panicking when a check fails, the error is recorded and proof generation stops. Solving
+    /// continues without a proof. The recorded errors are returned by
+    /// [`self_check_errors`](Solver::self_check_errors).
+    ///
+    /// This needs to be called before any clauses are added.
+    pub fn enable_self_checking_report(&mut self) {
+        assert!(
+            self.ctx.solver_state.formula_is_empty,
+            "called after clauses were added"
+        );
+        self.ctx.proof.begin_checking_with_report();
+    }
+
+    /// Failed checks recorded when self checking with a report is enabled.
+    ///
+    /// For failed checks of proof steps, [`CheckerError::CheckFailed`] contains a description of
+    /// the failed step.
+    pub fn self_check_errors(&self) -> &[CheckerError] {
+        self.ctx.proof.self_check_failures()
+    }
+
     /// Generate a proof and process it using a [`ProofProcessor`].
     ///
     /// This implicitly enables self checking.
@@ -260,6 +1222,39 @@ impl<'a> Solver<'a> {
         );
         self.ctx.proof.add_processor(processor);
     }
+
+    /// Track which input clauses are used to show unsatisfiability.
+    ///
+    /// This implicitly enables self checking, which is used to record the dependencies of all
+    /// derived clauses. The result is available through
+    /// [`unsat_core_clauses`](Solver::unsat_core_clauses).
+    ///
+    /// This needs to be called before any clauses are added.
+    pub fn enable_unsat_core_clauses(&mut self) {
+        assert!(
+            self.ctx.solver_state.formula_is_empty,
+            "called after clauses were added"
+        );
+        self.ctx.proof.enable_unsat_core();
+    }
+
+    /// Input clauses that are unsatisfiable together with the failed core.
+    ///
+    /// Requires self checking with unsat core tracking enabled using
+    /// [`enable_unsat_core_clauses`](Solver::enable_unsat_core_clauses). Returns the sorted
+    /// indices of the clauses in the order they were added to the solver. Together with the
+    /// literals of the [`failed_core`](Solver::failed_core) these clauses are unsatisfiable.
+    ///
+    /// Returns `None` if tracking is not enabled or the formula is not known to be unsatisfiable
+    /// under the current assumptions.
+    pub fn unsat_core_clauses(&self) -> Option<Vec<usize>> {
+        match self.ctx.solver_state.sat_state {
+            SatState::Unsat | SatState::UnsatUnderAssumptions => {
+                self.ctx.proof.unsat_core_clauses()
+            }
+            SatState::Unknown | SatState::Sat => None,
+        }
+    }
 }
 
 impl<'a> Drop for Solver<'a> {
@@ -270,6 +1265,9 @@ impl<'a> Drop for Solver<'a> {
 
 impl<'a> ExtendFormula for Solver<'a> {
     /// Add a clause to the solver.
+    ///
+    /// Panics if a variable of the clause or a variable allocated internally exceeds the supported
+    /// variable count given by [`Var::max_count`].
     fn add_clause(&mut self, clause: &[Lit]) {
         let mut ctx = self.ctx.into_partial_ref_mut();
         load_clause(ctx.borrow(), clause);
@@ -287,15 +1285,25 @@ impl<'a> ExtendFormula for Solver<'a> {
 mod tests {
     use super::*;
 
-    use proptest::prelude::*;
+    use proptest::{bool, collection, prelude::*};
 
-    use varisat_checker::{CheckedProofStep, CheckerData};
+    use varisat_checker::{CheckedProofStep, CheckerData, CollectModel};
     use varisat_formula::{
-        cnf_formula, lits,
-        test::{sat_formula, sgen_unsat_formula},
+        cnf::strategy::cnf_formula,
+        cnf_formula, lit,
+        lit::strategy::lit,
+        lits,
+        test::{conditional_pigeon_hole, sat_formula, sgen_unsat_formula},
+        var, vars,
     };
 
     use varisat_dimacs::write_dimacs;
+    use varisat_internal_proof::ProofStep;
+
+    use crate::{
+        clause::Tier,
+        config::{DecisionHeuristic, RestartStrategy},
+    };
 
     fn enable_test_schedule(solver: &mut Solver) {
         let mut config = SolverConfigUpdate::new();
@@ -322,14 +1330,78 @@ mod tests {
 
         let result = solver.solve();
 
-        assert!(match result {
-            Err(SolverError::ProofIoError { .. }) => true,
-            _ => false,
-        });
+        assert!(matches!(result, Err(SolverError::ProofIoError { .. })));
 
         let _ = solver.solve();
     }
 
+    #[test]
+    fn proof_writer_error_before_solve() {
+        let mut output_buffer = [0u8; 1024];
+        let mut solver = Solver::new();
+        let proof_output = std::io::Cursor::new(&mut output_buffer[..]);
+
+        solver.write_proof(proof_output, ProofFormat::Varisat);
+
+        solver.add_clause(&lits![1, 2]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.proof_writer_error().is_none());
+
+        for index in 1..10000 {
+            solver.add_clause(&[Lit::from_dimacs(index), Lit::from_dimacs(-index - 1)]);
+        }
+
+        assert_eq!(
+            solver.proof_writer_error().map(|err| err.kind()),
+            Some(std::io::ErrorKind::WriteZero)
+        );
+        // Peeking does not clear the error.
+        assert!(solver.proof_writer_error().is_some());
+
+        let result = solver.solve();
+
+        assert!(matches!(result, Err(SolverError::ProofIoError { .. })));
+        assert!(solver.proof_writer_error().is_none());
+    }
+
+    #[test]
+    fn self_checking_report_records_failure() {
+        let mut solver = Solver::new();
+        solver.enable_self_checking_report();
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -1, 2;
+            3, 4;
+        ]);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.self_check_errors().is_empty());
+
+        // The clause -3, -4 is not implied by the formula.
+        {
+            let mut ctx = solver.ctx.into_partial_ref_mut();
+            proof::add_step(
+                ctx.borrow(),
+                false,
+                &ProofStep::AtClause {
+                    redundant: true,
+                    clause: &lits![-3, -4],
+                    propagation_hashes: &[],
+                },
+            );
+        }
+
+        assert_eq!(solver.self_check_errors().len(), 1);
+        assert!(matches!(
+            solver.self_check_errors()[0],
+            CheckerError::CheckFailed { .. }
+        ));
+
+        solver.add_clause(&lits![-2]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.self_check_errors().len(), 1);
+    }
+
     struct FailingProcessor;
 
     impl ProofProcessor for FailingProcessor {
@@ -410,92 +1482,1968 @@ mod tests {
         assert_eq!(solver.solve().ok(), Some(true));
     }
 
-    proptest! {
-        #[test]
-        fn sgen_unsat(
-            formula in sgen_unsat_formula(1..7usize),
-            test_schedule in proptest::bool::ANY,
-        ) {
-            let mut solver = Solver::new();
+    #[test]
+    fn terminate_flag() {
+        let mut solver = Solver::new();
 
-            solver.add_formula(&formula);
+        let flag = Arc::new(AtomicBool::new(true));
+        solver.set_terminate(flag.clone());
 
-            if test_schedule {
-                enable_test_schedule(&mut solver);
-            }
+        solver.add_formula(&cnf_formula![
+            -1, -2, -3; -1, -2, -4; -1, -2, -5; -1, -3, -4; -1, -3, -5; -1, -4, -5; -2, -3, -4;
+            -2, -3, -5; -2, -4, -5; -3, -4, -5; 1, 2, 5; 1, 2, 3; 1, 2, 4; 1, 5, 3; 1, 5, 4;
+            1, 3, 4; 2, 5, 3; 2, 5, 4; 2, 3, 4; 5, 3, 4;
+        ]);
 
-            prop_assert_eq!(solver.solve().ok(), Some(false));
-        }
+        assert!(matches!(solver.solve(), Err(SolverError::Interrupted)));
 
-        #[test]
-        fn sgen_unsat_checked(
-            formula in sgen_unsat_formula(1..7usize),
-            test_schedule in proptest::bool::ANY,
-        ) {
-            let mut solver = Solver::new();
+        flag.store(false, std::sync::atomic::Ordering::Relaxed);
 
-            solver.enable_self_checking();
+        assert_eq!(solver.solve().ok(), Some(false));
+    }
 
-            solver.add_formula(&formula);
+    #[test]
+    fn terminate_callback() {
+        let mut solver = Solver::new();
 
-            if test_schedule {
-                enable_test_schedule(&mut solver);
-            }
+        let mut polls = 0;
+        solver.set_terminate_callback(move || {
+            polls += 1;
+            polls <= 3
+        });
 
-            prop_assert_eq!(solver.solve().ok(), Some(false));
+        solver.add_formula(&cnf_formula![
+            -1, -2, -3; -1, -2, -4; -1, -2, -5; -1, -3, -4; -1, -3, -5; -1, -4, -5; -2, -3, -4;
+            -2, -3, -5; -2, -4, -5; -3, -4, -5; 1, 2, 5; 1, 2, 3; 1, 2, 4; 1, 5, 3; 1, 5, 4;
+            1, 3, 4; 2, 5, 3; 2, 5, 4; 2, 3, 4; 5, 3, 4;
+        ]);
+
+        for _ in 0..3 {
+            assert!(matches!(solver.solve(), Err(SolverError::Interrupted)));
         }
 
-        #[test]
-        fn sat(
-            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
-            test_schedule in proptest::bool::ANY,
-        ) {
-            let mut solver = Solver::new();
+        assert_eq!(solver.solve().ok(), Some(false));
+    }
 
-            solver.add_formula(&formula);
+    #[test]
+    fn enumerate_models_with_blocking_clauses() {
+        let mut solver = Solver::new();
 
-            if test_schedule {
-                enable_test_schedule(&mut solver);
-            }
+        solver.add_formula(&cnf_formula![
+            1, 2, 3;
+            -1, -2;
+            4;
+        ]);
 
-            prop_assert_eq!(solver.solve().ok(), Some(true));
+        // A variable that doesn't appear in the formula
+        solver.new_var();
 
-            let model = solver.model().unwrap();
+        let mut models = vec![];
 
-            for clause in formula.iter() {
-                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
-            }
+        while solver.solve().unwrap() {
+            let model = solver.model().unwrap();
+            assert!(!models
+                .iter()
+                .any(|other: &Vec<Lit>| other[..] == model[..3]));
+            models.push(model[..3].to_vec());
+
+            let blocking_clause = solver.model_blocking_clause().unwrap();
+            assert!(!blocking_clause.contains(&lit!(4)) && !blocking_clause.contains(&lit!(-4)));
+            solver.add_clause(&blocking_clause);
         }
 
-        #[test]
-        fn sat_via_dimacs(formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0)) {
-            let mut solver = Solver::new();
+        assert_eq!(models.len(), 5);
+        assert_eq!(solver.model_blocking_clause(), None);
+    }
 
-            let mut dimacs = vec![];
+    #[test]
+    fn enumerate_projected_models() {
+        let mut solver = Solver::new();
 
-            write_dimacs(&mut dimacs, &formula).unwrap();
+        solver.add_formula(&cnf_formula![
+            1, 2, 3;
+            -1, -2;
+        ]);
 
-            solver.add_dimacs_cnf(&mut &dimacs[..]).unwrap();
+        assert_eq!(solver.model_projected(&vars![1, 2]), None);
 
-            prop_assert_eq!(solver.solve().ok(), Some(true));
+        let mut models = vec![];
 
-            let model = solver.model().unwrap();
+        while solver.solve().unwrap() {
+            let model = solver.model_projected(&vars![1, 2, 7]).unwrap();
+            assert_eq!(model.len(), 2);
+            assert!(!models.contains(&model));
 
-            for clause in formula.iter() {
-                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
-            }
+            let blocking_clause: Vec<_> = model.iter().map(|&lit| !lit).collect();
+            solver.add_clause(&blocking_clause);
+            models.push(model);
         }
 
-        #[test]
-        fn sgen_unsat_incremental_clauses(formula in sgen_unsat_formula(1..7usize)) {
-            let mut solver = Solver::new();
+        assert_eq!(models.len(), 3);
+    }
 
-            let mut last_state = Some(true);
+    #[test]
+    fn propagate_top_level() {
+        let mut solver = Solver::new();
 
-            for clause in formula.iter() {
-                solver.add_clause(clause);
+        solver.enable_self_checking();
 
-                let state = solver.solve().ok();
+        solver.add_formula(&cnf_formula![
+            1;
+            -1, 2;
+            -2, 3, 4;
+            -3, 5;
+        ]);
+
+        let mut implied = solver.propagate().unwrap().unwrap();
+        implied.sort();
+        assert_eq!(implied, lits![2]);
+
+        assert_eq!(solver.propagate().unwrap(), Some(vec![]));
+
+        solver.add_clause(&lits![-4]);
+
+        let mut implied = solver.propagate().unwrap().unwrap();
+        implied.sort();
+        assert_eq!(implied, lits![3, 5]);
+
+        solver.add_clause(&lits![-5]);
+
+        assert_eq!(solver.propagate().unwrap(), None);
+        assert_eq!(solver.solve().ok(), Some(false));
+    }
+
+    #[test]
+    fn implied_units_after_solve() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            1, -2;
+            -1, 3, 4;
+            -3, 5;
+            -4, 5;
+        ]);
+
+        assert!(solver.implied_units().is_empty());
+
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        let units = solver.implied_units();
+        assert!(units.contains(&lit!(1)));
+        assert!(!units.contains(&lit!(3)));
+        assert!(!units.contains(&lit!(4)));
+
+        solver.add_clause(&lits![-3]);
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        let mut units = solver.implied_units();
+        units.sort();
+        assert_eq!(units, lits![1, -3, 4, 5]);
+    }
+
+    #[test]
+    fn decision_level_after_solve() {
+        let mut solver = Solver::new();
+
+        assert_eq!(solver.decision_level(), 0);
+        assert_eq!(solver.trail_len(), 0);
+
+        solver.add_formula(&cnf_formula![
+            1;
+            -1, 2;
+            -2, 3;
+        ]);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.decision_level(), 0);
+        assert!(solver.trail_len() <= 3);
+
+        solver.add_clause(&lits![4, 5]);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.decision_level(), 1);
+        assert!(solver.trail_len() <= 5);
+    }
+
+    #[test]
+    fn last_result_follows_state() {
+        let mut solver = Solver::new();
+
+        assert_eq!(solver.sat_state(), SatState::Unknown);
+        assert_eq!(solver.last_result(), None);
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -1, 2;
+        ]);
+
+        assert_eq!(solver.sat_state(), SatState::Unknown);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.sat_state(), SatState::Sat);
+        assert_eq!(solver.last_result(), Some(true));
+        assert!(solver.model().is_some());
+
+        solver.assume(&lits![-2]);
+        assert_eq!(solver.last_result(), None);
+
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::UnsatUnderAssumptions);
+        assert_eq!(solver.last_result(), Some(false));
+        assert!(solver.model().is_none());
+        assert!(solver.failed_core().is_some());
+
+        solver.add_clause(&lits![-2]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+        assert_eq!(solver.state(), SatState::Unsat);
+        assert_eq!(solver.last_result(), Some(false));
+        assert_eq!(solver.failed_core(), Some(&[][..]));
+    }
+
+    #[test]
+    fn unsat_under_assumptions_is_temporary() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -1, 3;
+        ]);
+
+        solver.assume(&lits![-2, -3]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::UnsatUnderAssumptions);
+
+        solver.assume(&lits![-2]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.sat_state(), SatState::Sat);
+
+        solver.assume(&[]);
+        solver.add_clause(&lits![-3]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.sat_state(), SatState::Sat);
+    }
+
+    #[test]
+    fn global_unsat_is_permanent() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -1, 2;
+            1, -2;
+        ]);
+
+        solver.assume(&lits![-1]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::UnsatUnderAssumptions);
+
+        solver.assume(&[]);
+        solver.add_clause(&lits![-1, -2]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+
+        solver.assume(&lits![1]);
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+
+        solver.assume(&[]);
+        solver.add_clause(&lits![3]);
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::Unsat);
+        assert_eq!(solver.failed_core(), Some(&[][..]));
+    }
+
+    #[test]
+    fn toggle_retractable_clause() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -1, 2;
+        ]);
+
+        let activation = solver.add_retractable_clause(&lits![-2]);
+
+        solver.assume(&[activation]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.sat_state(), SatState::UnsatUnderAssumptions);
+        assert_eq!(solver.failed_core(), Some(&[activation][..]));
+
+        solver.assume(&[]);
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        solver.assume(&[!activation]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.model().unwrap().contains(&lit!(2)));
+
+        solver.assume(&[activation]);
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        solver.assume(&[]);
+        solver.retract(activation);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.model().unwrap().contains(&!activation));
+
+        let other = solver.add_retractable_clause(&lits![1]);
+        assert_ne!(other.var(), activation.var());
+
+        solver.assume(&[other]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.model().unwrap().contains(&lit!(1)));
+        assert!(solver.model().unwrap().contains(&other));
+    }
+
+    #[test]
+    fn count_models_with_retractable_clause() {
+        let mut solver = Solver::new();
+
+        solver.add_clause(&lits![1, 2]);
+        let activation = solver.add_retractable_clause(&lits![-1]);
+
+        let vars: Vec<Var> = (0..2).map(Var::from_index).collect();
+
+        solver.assume(&[activation]);
+        assert_eq!(solver.count_models_projected(&vars, None).ok(), Some(1));
+
+        let variables = &solver.ctx.variables;
+        let global = variables.global_from_user().get(activation.var()).unwrap();
+        assert!(variables.var_data_global(global).frozen);
+    }
+
+    #[test]
+    fn count_models_of_small_formula() {
+        let formula = cnf_formula![
+            1, 2, 3;
+            -1, -2;
+        ];
+
+        let vars: Vec<Var> = (0..3).map(Var::from_index).collect();
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        assert_eq!(solver.count_models_projected(&vars, None).ok(), Some(5));
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        assert_eq!(
+            solver.count_models_projected(&vars[..2], None).ok(),
+            Some(3)
+        );
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        assert_eq!(solver.count_models_projected(&vars, Some(2)).ok(), Some(2));
+        assert_eq!(solver.count_models_projected(&vars, None).ok(), Some(3));
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        solver.assume(&lits![1]);
+        assert_eq!(solver.count_models_projected(&vars, None).ok(), Some(2));
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        let unused: Vec<Var> = (0..4).map(Var::from_index).collect();
+        assert_eq!(solver.count_models_projected(&unused, None).ok(), Some(10));
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        assert_eq!(solver.count_models_projected(&[], None).ok(), Some(1));
+    }
+
+    #[test]
+    fn model_with_hidden_tseitin_var() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            -3, 1;
+            -3, 2;
+            3, -1, -2;
+            1, 2;
+        ]);
+        solver.hide_var(Var::from_index(2));
+
+        assert_eq!(solver.model_with_internal(), None);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let user_model = solver.model().unwrap();
+        assert_eq!(user_model.len(), 2);
+
+        let model = solver.model_with_internal().unwrap();
+        assert_eq!(model.len(), 3);
+        assert!(user_model.iter().all(|lit| model.contains(lit)));
+
+        let internal = model.iter().find(|lit| lit.index() > 1).unwrap();
+        assert_eq!(
+            internal.is_positive(),
+            user_model.iter().all(|lit| lit.is_positive())
+        );
+
+        assert_eq!(solver.model(), Some(model));
+    }
+
+    #[test]
+    fn var_mapping_after_hiding_and_observing() {
+        let mut solver = Solver::new();
+
+        assert_eq!(solver.dump_var_mapping(), VarMappingSnapshot::default());
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            -2, 3;
+            -1, -3, 4;
+        ]);
+
+        let mapping = solver.dump_var_mapping();
+        let identity: Vec<_> = (0..4).map(|index| Some(Var::from_index(index))).collect();
+        assert_eq!(mapping.global_from_user, identity);
+        assert_eq!(
+            mapping.solver_from_global.iter().flatten().count(),
+            4,
+            "{:?}",
+            mapping
+        );
+
+        solver.hide_var(Var::from_index(1));
+
+        let mapping = solver.dump_var_mapping();
+        assert_eq!(mapping.global_from_user[1], None);
+        assert_eq!(mapping.global_from_user[2], Some(Var::from_index(2)));
+
+        let observed = solver.observe_internal_vars();
+        assert_eq!(observed.len(), 1);
+
+        let mapping = solver.dump_var_mapping();
+        assert_eq!(
+            mapping.global_from_user[observed[0].index()],
+            Some(Var::from_index(1))
+        );
+        assert_eq!(mapping.solver_from_global.len(), 4);
+    }
+
+    #[test]
+    fn preprocess_removes_subsumed_clauses() {
+        let formula = cnf_formula![
+            1, 2;
+            1, 2, 3;
+            -1, 3, 4;
+            -1, 3, 4, 5;
+            5, -6, -2;
+            -6, 7, 8, -2, 5;
+            6;
+            -7, 8, 9;
+            9, -8, -7, -5;
+        ];
+
+        let mut solver = Solver::new();
+        solver.enable_self_checking();
+        solver.add_formula(&formula);
+
+        let initial_count = solver.num_clauses();
+
+        solver.preprocess().unwrap();
+
+        let clauses = solver.clauses_iter().collect::<Vec<_>>();
+        assert!(clauses.len() < initial_count);
+
+        let contains_clause = |lits: &[Lit]| {
+            let mut lits = lits.to_vec();
+            lits.sort();
+            clauses.iter().any(|clause| {
+                let mut clause = clause.clone();
+                clause.sort();
+                clause == lits
+            })
+        };
+
+        assert!(contains_clause(&lits![1, 2]));
+        assert!(!contains_clause(&lits![1, 2, 3]));
+        assert!(contains_clause(&lits![-1, 3, 4]));
+        assert!(!contains_clause(&lits![-1, 3, 4, 5]));
+        assert!(!contains_clause(&lits![7, 8, -2, 5]));
+        assert!(contains_clause(&lits![-7, 8, 9]));
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        for clause in formula.iter() {
+            assert!(clause.iter().any(|lit| model.contains(lit)));
+        }
+    }
+
+    #[test]
+    fn preprocess_pure_literals() {
+        let formula = cnf_formula![
+            1, 2;
+            1, -3;
+            -2, 3;
+            2, 3, -4;
+            -2, -3, 4;
+            -2, 3, 4;
+            5, 2, -4;
+        ];
+
+        let mut solver = Solver::new();
+        solver.add_formula(&formula);
+        solver.witness_var(var!(1));
+        solver.witness_var(var!(5));
+        solver.freeze_var(var!(5));
+
+        solver.preprocess().unwrap();
+
+        // Values chosen by pure literal elimination are not implied
+        assert!(solver.implied_units().is_empty());
+
+        let clauses = solver.clauses_iter().collect::<Vec<_>>();
+        assert!(clauses.len() < formula.len());
+        // Unit clauses are included in the irredundant clauses
+        assert!(clauses
+            .iter()
+            .filter(|clause| clause.contains(&lit!(1)))
+            .all(|clause| clause.len() == 1));
+        assert!(clauses.iter().flatten().any(|&lit| lit == lit!(5)));
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        for clause in formula.iter() {
+            assert!(clause.iter().any(|lit| model.contains(lit)));
+        }
+        assert!(model.contains(&lit!(1)));
+    }
+
+    #[test]
+    fn preprocess_keeps_sampling_vars() {
+        let mut solver = Solver::new();
+        solver.add_clause(&lits![1, 2]);
+
+        solver.preprocess().unwrap();
+
+        assert!(solver.implied_units().is_empty());
+        assert_eq!(solver.backbone().ok(), Some(vec![]));
+
+        solver.add_clause(&lits![-1]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.implied_units(), lits![-1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample variable fixed by pure literal elimination")]
+    fn cannot_sample_pure_var() {
+        let mut solver = Solver::new();
+        solver.add_clause(&lits![1, 2]);
+        solver.witness_var(var!(1));
+
+        solver.preprocess().unwrap();
+
+        solver.sample_var(var!(1));
+    }
+
+    #[test]
+    fn simplify_subsumption_with_proof() {
+        let formula = cnf_formula![
+            1, 2, 3;
+            1, 2, 3, 4;
+            -1, 5, 6;
+            1, 5, 6, 7;
+            2, 4;
+            -2, 4, 8;
+        ];
+
+        let mut proof = vec![];
+
+        let mut solver = Solver::new();
+        solver.enable_self_checking();
+        solver.write_proof(&mut proof, ProofFormat::Varisat);
+        solver.add_formula(&formula);
+
+        solver.simplify_subsumption().unwrap();
+
+        let mut clauses = solver
+            .clauses_iter()
+            .map(|mut clause| {
+                clause.sort();
+                clause
+            })
+            .collect::<Vec<_>>();
+        clauses.sort();
+
+        // The superset of 1, 2, 3 is removed, resolving with -1, 5, 6 removes 1 from 1, 5, 6, 7
+        // and resolving with 2, 4 removes -2 from -2, 4, 8.
+        let mut expected = vec![
+            lits![1, 2, 3].to_vec(),
+            lits![5, 6, 7].to_vec(),
+            lits![-1, 5, 6].to_vec(),
+            lits![2, 4].to_vec(),
+            lits![4, 8].to_vec(),
+        ];
+        for clause in expected.iter_mut() {
+            clause.sort();
+        }
+        expected.sort();
+
+        assert_eq!(clauses, expected);
+
+        solver.close_proof().unwrap();
+        drop(solver);
+
+        let mut checker = varisat_checker::Checker::new();
+        checker.add_formula(&formula).unwrap();
+        checker.check_proof(&proof[..]).unwrap();
+    }
+
+    #[test]
+    fn empty_formula_is_sat() {
+        let mut solver = Solver::new();
+        solver.enable_self_checking();
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.model(), Some(vec![]));
+        assert_eq!(solver.stats().decisions, 0);
+
+        solver.add_clause(&[]);
+        assert_eq!(solver.solve().ok(), Some(false));
+        assert_eq!(solver.model(), None);
+
+        let mut solver = Solver::new();
+        solver.add_formula(&CnfFormula::new());
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.model(), Some(vec![]));
+    }
+
+    #[test]
+    fn empty_clause_is_unsat() {
+        let mut formula = pigeon_hole_formula(9, 8);
+        formula.add_clause(&[]);
+
+        for &format in [
+            ProofFormat::Varisat,
+            ProofFormat::Drat,
+            ProofFormat::BinaryDrat,
+        ]
+        .iter()
+        {
+            let mut proof = vec![];
+
+            let mut solver = Solver::new();
+            solver.enable_self_checking();
+            solver.write_proof(&mut proof, format);
+            solver.add_formula(&formula);
+
+            assert_eq!(solver.solve().ok(), Some(false));
+            assert_eq!(solver.sat_state(), SatState::Unsat);
+            assert_eq!(solver.stats().conflicts, 0);
+
+            solver.close_proof().unwrap();
+            drop(solver);
+
+            let mut checker = varisat_checker::Checker::new();
+            checker.add_formula(&formula).unwrap();
+            if format == ProofFormat::Varisat {
+                checker.check_proof(&proof[..]).unwrap();
+            } else {
+                checker.check_drat_proof(&proof[..]).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn add_dimacs_cnf_header() {
+        let mut solver = Solver::new();
+        let (header, counts) = solver
+            .add_dimacs_cnf_with_header(&b"c reserved vars\np cnf 5 3\n1 2 0\n-2 3 0\n-1 0\n"[..])
+            .unwrap();
+
+        let header = header.unwrap();
+        assert_eq!((header.var_count, header.clause_count), (5, 3));
+        assert_eq!((counts.var_count, counts.clause_count), (3, 3));
+        assert_eq!(solver.num_clauses(), 3);
+
+        let (header, counts) = solver.add_dimacs_cnf_with_header(&b"4 -3 0\n"[..]).unwrap();
+
+        assert!(header.is_none());
+        assert_eq!((counts.var_count, counts.clause_count), (4, 1));
+    }
+
+    /// Pigeon hole formula, which requires many conflicts to refute.
+    ///
+    /// Variable `pigeon * holes + hole` is true if the pigeon is in that hole.
+    fn pigeon_hole_formula(pigeons: usize, holes: usize) -> CnfFormula {
+        let var = |pigeon: usize, hole: usize| Var::from_index(pigeon * holes + hole);
+
+        let mut formula = CnfFormula::new();
+
+        for pigeon in 0..pigeons {
+            let clause: Vec<_> = (0..holes)
+                .map(|hole| var(pigeon, hole).positive())
+                .collect();
+            formula.add_clause(&clause);
+        }
+
+        for hole in 0..holes {
+            for a in 0..pigeons {
+                for b in 0..a {
+                    formula.add_clause(&[var(a, hole).negative(), var(b, hole).negative()]);
+                }
+            }
+        }
+
+        formula
+    }
+
+    #[test]
+    fn activity_of_conflict_vars() {
+        let mut solver = Solver::new();
+
+        let php_vars = 6 * 5;
+        solver.add_formula(&pigeon_hole_formula(6, 5));
+
+        let isolated = solver.new_var();
+        let other = solver.new_var();
+        solver.add_clause(&[isolated.positive(), other.positive()]);
+
+        let unused = solver.new_var();
+
+        solver.set_conflict_budget(Some(100));
+        assert_eq!(solver.solve_limited().ok(), Some(None));
+
+        let ranking = solver.activity_ranking();
+        assert_eq!(ranking.len(), php_vars + 2);
+        assert!(ranking.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(ranking[0].0.index() < php_vars);
+
+        assert_eq!(solver.var_activity(ranking[0].0), ranking[0].1);
+        assert!(solver.var_activity(ranking[0].0) > solver.var_activity(isolated));
+        assert_eq!(solver.var_activity(unused), 0.0);
+    }
+
+    #[test]
+    fn decision_heuristics_differ() {
+        let formula = pigeon_hole_formula(6, 5);
+
+        let mut decisions = vec![];
+
+        for &heuristic in [DecisionHeuristic::Vsids, DecisionHeuristic::Vmtf].iter() {
+            let mut solver = Solver::new();
+
+            let mut config = SolverConfigUpdate::new();
+            config.decision_heuristic = Some(heuristic);
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+
+            assert_eq!(solver.solve().ok(), Some(false));
+
+            decisions.push(solver.stats().decisions);
+        }
+
+        assert_ne!(decisions[0], decisions[1]);
+    }
+
+    #[test]
+    fn tseitin_non_decision_vars() {
+        let mut solver = Solver::new();
+
+        // Encode that the parity of the inputs is odd using a chain of Tseitin variables.
+        let inputs = solver.new_lit_vec(8);
+        let mut formula = CnfFormula::new();
+        let mut parity = inputs[0];
+
+        for &input in inputs[1..].iter() {
+            let aux = solver.new_lit();
+            solver.set_decision_var(aux.var(), false);
+            formula.add_clause(&[!aux, parity, input]);
+            formula.add_clause(&[!aux, !parity, !input]);
+            formula.add_clause(&[aux, !parity, input]);
+            formula.add_clause(&[aux, parity, !input]);
+            parity = aux;
+        }
+
+        formula.add_clause(&[parity]);
+        solver.add_formula(&formula);
+
+        for &input in inputs[..4].iter() {
+            solver.add_clause(&[!input]);
+        }
+
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        let model = solver.model().unwrap();
+        for clause in formula.iter() {
+            assert!(clause.iter().any(|lit| model.contains(lit)));
+        }
+
+        let true_inputs = inputs.iter().filter(|lit| model.contains(lit)).count();
+        assert_eq!(true_inputs % 2, 1);
+    }
+
+    #[test]
+    fn backbone_of_small_formula() {
+        let mut solver = Solver::new();
+
+        solver.add_formula(&cnf_formula![
+            1, 2;
+            1, -2;
+            -1, 3, 4;
+            -3, 5;
+            -4, 5;
+            6, 7;
+            -5, -8;
+        ]);
+
+        assert_eq!(solver.backbone().unwrap(), lits![1, 5, -8]);
+
+        // Assumptions are cleared afterwards.
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        solver.add_clause(&lits![-6]);
+        assert_eq!(solver.backbone().unwrap(), lits![1, 5, -6, 7, -8]);
+
+        solver.add_clause(&lits![-7]);
+        assert_eq!(solver.backbone().unwrap(), lits![]);
+    }
+
+    #[test]
+    fn unsat_core_clauses_under_assumptions() {
+        let mut solver = Solver::new();
+
+        solver.enable_unsat_core_clauses();
+
+        let formula = cnf_formula![
+            1, 2, 3;
+            -1, 4;
+            -4, 5;
+            5, 6;
+            -2, 4;
+            -3, -6;
+            7, 8;
+        ];
+
+        solver.add_formula(&formula);
+
+        assert_eq!(solver.unsat_core_clauses(), None);
+
+        solver.assume(&lits![-5, 7]);
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        let core = solver.unsat_core_clauses().unwrap();
+        assert_eq!(core, vec![0, 1, 2, 3, 4, 5]);
+
+        let mut core_solver = Solver::new();
+        for &index in core.iter() {
+            core_solver.add_clause(formula.iter().nth(index).unwrap());
+        }
+        core_solver.assume(solver.failed_core().unwrap());
+        assert_eq!(core_solver.solve().ok(), Some(false));
+
+        solver.assume(&[]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.unsat_core_clauses(), None);
+    }
+
+    #[test]
+    fn xor_chain_unsat() {
+        let mut solver = Solver::new();
+
+        solver.enable_self_checking();
+
+        // A chain of constraints that sums up to 0 = 1.
+        for i in 1..20 {
+            solver.add_xor_clause(&lits![i, i + 1, -(i + 2), i + 3, 30 + i, -(50 + i)], true);
+        }
+        for i in 1..20 {
+            solver.add_xor_clause(&lits![30 + i, 50 + i], true);
+        }
+        for i in 1..20 {
+            solver.add_xor_clause(&lits![i, i + 1, i + 2, i + 3], true);
+        }
+
+        assert_eq!(solver.solve().ok(), Some(false));
+    }
+
+    #[test]
+    fn deterministic_solving() {
+        let formula = cnf_formula![
+            -1, -2, -3; -1, -2, -4; -1, -2, -5; -1, -3, -4; -1, -3, -5; -1, -4, -5; -2, -3, -4;
+            -2, -3, -5; -2, -4, -5; -3, -4, -5; 1, 2, 5; 1, 2, 3; 1, 2, 4; 1, 5, 3; 1, 5, 4;
+            1, 3, 4; 2, 5, 3; 2, 5, 4; 2, 3, 4; 5, 3, 4;
+        ];
+
+        let mut proofs = vec![];
+
+        for _ in 0..2 {
+            let mut proof = vec![];
+
+            let mut solver = Solver::new();
+            solver.write_proof(&mut proof, ProofFormat::Varisat);
+            solver.add_formula(&formula);
+            assert_eq!(solver.solve().ok(), Some(false));
+            solver.close_proof().unwrap();
+            drop(solver);
+
+            proofs.push(proof);
+        }
+
+        assert_eq!(proofs[0], proofs[1]);
+    }
+
+    #[test]
+    fn add_clauses_allocates_vars_once() {
+        let mut formula = CnfFormula::new();
+        for index in 1..1000 {
+            formula.add_clause(&[Lit::from_dimacs(index), Lit::from_dimacs(-index - 1)]);
+        }
+        formula.add_clause(&lits![1000]);
+
+        let mut solver = Solver::new();
+        solver.add_clauses(formula.iter());
+
+        // Growing the variable count one at a time would leave spare capacity.
+        assert_eq!(solver.ctx.assignment.memory_usage(), 1000 * 2);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        assert!((1..=1000).all(|index| model.contains(&Lit::from_dimacs(index))));
+    }
+
+    #[test]
+    fn reserve_avoids_growth() {
+        let mut solver = Solver::new();
+        solver.reserve(1000, 998);
+
+        let assignment_memory = solver.ctx.assignment.memory_usage();
+        let clause_memory =
+            solver.ctx.clause_alloc.memory_usage() + solver.ctx.clause_db.memory_usage();
+
+        for index in 1..999 {
+            solver.add_clause(&[
+                Lit::from_dimacs(index),
+                Lit::from_dimacs(index + 1),
+                Lit::from_dimacs(-index - 2),
+            ]);
+        }
+
+        assert_eq!(solver.num_vars(), 1000);
+        assert_eq!(solver.ctx.assignment.memory_usage(), assignment_memory);
+        assert_eq!(
+            solver.ctx.clause_alloc.memory_usage() + solver.ctx.clause_db.memory_usage(),
+            clause_memory
+        );
+    }
+
+    #[test]
+    fn new_var_vec_distinct() {
+        let mut solver = Solver::new();
+
+        solver.add_clause(&lits![1, -3]);
+
+        let vars = solver.new_var_vec(4);
+        assert_eq!(vars.len(), 4);
+        assert_eq!(solver.num_vars(), 7);
+
+        let mut sorted = vars.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 4);
+        assert!(vars.iter().all(|&var| var != var!(1) && var != var!(3)));
+
+        let lits = solver.new_lit_vec(2);
+        assert!(lits
+            .iter()
+            .all(|lit| lit.is_positive() && !vars.contains(&lit.var())));
+
+        for &var in vars.iter() {
+            solver.add_clause(&[var.negative()]);
+        }
+        solver.add_clause(&[vars[0].positive(), lits[0], lits[1]]);
+        solver.add_clause(&[!lits[0]]);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        assert!(model.contains(&lits[1]));
+        assert!(vars.iter().all(|var| model.contains(&var.negative())));
+
+        assert!(solver.new_var_vec(0).is_empty());
+    }
+
+    #[test]
+    fn assumption_stack() {
+        let mut solver = Solver::new();
+
+        solver.enable_self_checking();
+
+        solver.add_formula(&cnf_formula![
+            -1, 2;
+            -2, 3;
+            -3, -4;
+        ]);
+
+        assert!(!solver.pop_assumption());
+
+        solver.push_assumption(lit!(1));
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.model().unwrap().contains(&lit!(3)));
+
+        solver.push_assumption(lit!(5));
+        assert_eq!(solver.solve().ok(), Some(true));
+
+        solver.push_assumption(lit!(4));
+        assert_eq!(solver.solve().ok(), Some(false));
+        let mut core = solver.failed_core().unwrap().to_owned();
+        core.sort();
+        assert_eq!(core, lits![1, 4]);
+
+        solver.push_assumption(lit!(-5));
+        assert_eq!(solver.failed_core(), None);
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        assert!(solver.pop_assumption());
+        assert!(solver.pop_assumption());
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        assert!(model.contains(&lit!(5)) && model.contains(&lit!(-4)));
+
+        assert!(solver.pop_assumption());
+        assert!(solver.pop_assumption());
+        assert!(!solver.pop_assumption());
+
+        solver.push_assumption(lit!(4));
+        solver.push_assumption(lit!(4));
+        assert!(solver.pop_assumption());
+        solver.push_assumption(lit!(3));
+        assert_eq!(solver.solve().ok(), Some(false));
+
+        solver.assume(&lits![-3]);
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert!(solver.model().unwrap().contains(&lit!(-1)));
+    }
+
+    #[test]
+    fn frozen_var_after_solve() {
+        let formula = cnf_formula![
+            1, 2;
+            1, 3;
+            -2, 4;
+        ];
+
+        for &(freeze, melt) in [(true, false), (true, true), (false, false)].iter() {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+            solver.witness_var(var!(1));
+
+            if freeze {
+                solver.freeze_var(var!(1));
+            }
+            if melt {
+                solver.melt_var(var!(1));
+            }
+
+            solver.preprocess().unwrap();
+
+            // The pure literal 1 of the witness variable is fixed unless the variable is frozen
+            let frozen = freeze && !melt;
+            let clauses = solver.clauses_iter().collect::<Vec<_>>();
+            assert_eq!(clauses.contains(&lits![1].to_vec()), !frozen);
+            assert_eq!(
+                clauses
+                    .iter()
+                    .any(|clause| clause.len() > 1 && clause.contains(&lit!(1))),
+                frozen
+            );
+
+            assert_eq!(solver.solve().ok(), Some(true));
+            let model = solver.model().unwrap();
+            for clause in formula.iter() {
+                assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+    }
+
+    #[test]
+    fn preferred_phase() {
+        let mut solver = Solver::new();
+
+        // Variables not used so far are ignored
+        solver.set_phase(lit!(-4));
+
+        solver.add_clause(&lits![1, 2, 3]);
+
+        for &lit in lits![1, 2, 3].iter() {
+            solver.set_phase(lit);
+        }
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        assert_eq!(solver.model().unwrap(), lits![1, 2, 3]);
+
+        for &var in vars![1, 2, 3].iter() {
+            solver.clear_phase(var);
+        }
+
+        // Adding a clause restarts the search
+        solver.add_clause(&lits![1, 2, 3]);
+
+        assert_eq!(solver.solve().ok(), Some(true));
+        let model = solver.model().unwrap();
+        assert_eq!(model.iter().filter(|lit| lit.is_positive()).count(), 1);
+    }
+
+    /// Whether a formula with an additional cardinality constraint is satisfiable.
+    fn brute_force_at_most_k(formula: &CnfFormula, lits: &[Lit], k: usize) -> bool {
+        let var_count = formula.var_count();
+        (0..(1usize << var_count)).any(|bits| {
+            let value = |lit: &Lit| ((bits >> lit.index()) & 1 == 1) == lit.is_positive();
+            formula.iter().all(|clause| clause.iter().any(value))
+                && lits.iter().filter(|lit| value(lit)).count() <= k
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn pigeon_hole_minimal_core(
+            (enable_row, columns, formula) in conditional_pigeon_hole(1..5usize, 1..5usize),
+        ) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+
+            solver.assume(&enable_row);
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+
+            let core = solver.minimize_core().unwrap();
+            prop_assert_eq!(core.len(), columns + 1);
+
+            let mut failed_core = solver.failed_core().unwrap().to_owned();
+            failed_core.sort();
+            prop_assert_eq!(&failed_core, &core);
+
+            for skipped in 0..core.len() {
+                let mut assumptions = core.clone();
+                assumptions.remove(skipped);
+                solver.assume(&assumptions);
+                prop_assert_eq!(solver.solve().ok(), Some(true));
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn collect_model(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            xor in collection::vec(lit(0..20usize), 1..5),
+        ) {
+            let mut collect_model = CollectModel::new();
+
+            let mut solver = Solver::new();
+            solver.add_proof_processor(&mut collect_model);
+            solver.add_formula(&formula);
+            solver.add_xor_clause(&xor, true);
+
+            if solver.solve().unwrap() {
+                let mut model = solver.model().unwrap();
+                drop(solver);
+
+                let mut collected = collect_model.model().unwrap().to_vec();
+
+                model.sort_unstable();
+                collected.sort_unstable();
+
+                prop_assert_eq!(collected, model);
+            }
+        }
+
+        #[test]
+        fn at_most_k(
+            formula in cnf_formula(1..8usize, 0..20, 1..5),
+            lits in collection::vec(lit(0..8usize), 0..12),
+            k in 0..4usize,
+            at_most_one: bool,
+        ) {
+            let k = if at_most_one { 1 } else { k };
+            let mut formula = formula;
+            formula.set_var_count(8);
+
+            let mut solver = Solver::new();
+
+            solver.enable_self_checking();
+
+            solver.add_formula(&formula);
+
+            if at_most_one {
+                solver.add_at_most_one(&lits);
+            } else {
+                solver.add_at_most_k(&lits, k);
+            }
+
+            let expected = brute_force_at_most_k(&formula, &lits, k);
+
+            prop_assert_eq!(solver.solve().ok(), Some(expected));
+
+            if expected {
+                let model = solver.model().unwrap();
+                prop_assert!(lits.iter().filter(|lit| model.contains(lit)).count() <= k);
+                for clause in formula.iter() {
+                    prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+                }
+                for lit in model.iter() {
+                    prop_assert!(lit.index() < 8);
+                }
+            }
+        }
+
+        #[test]
+        fn xor_system(
+            xors in collection::vec((collection::vec(lit(0..12usize), 1..10), bool::ANY), 1..10),
+        ) {
+            let mut solver = Solver::new();
+
+            solver.enable_self_checking();
+
+            for (lits, rhs) in xors.iter() {
+                solver.add_xor_clause(lits, *rhs);
+            }
+
+            if solver.solve().unwrap() {
+                let model = solver.model().unwrap();
+                for (lits, rhs) in xors.iter() {
+                    let parity = lits.iter().filter(|lit| model.contains(lit)).count() % 2 == 1;
+                    prop_assert_eq!(parity, *rhs);
+                }
+                for lit in model.iter() {
+                    prop_assert!(lit.index() < 12);
+                }
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_write_dimacs(formula in sgen_unsat_formula(1..7usize), solve_first: bool) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            if solve_first {
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+            }
+
+            let mut dimacs = vec![];
+            solver.write_dimacs(&mut dimacs).unwrap();
+
+            let mut solver = Solver::new();
+            solver.add_dimacs_cnf(&dimacs[..]).unwrap();
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sgen_unsat_add_clauses(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+            solver.enable_self_checking();
+            solver.add_clauses(formula.iter());
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sgen_unsat_clauses_iter(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            // Stop solving early to get a state with learned clauses.
+            solver.set_conflict_budget(Some(20));
+            let result = solver.solve();
+
+            let clauses: Vec<_> = solver.clauses_iter().collect();
+            let learned: Vec<_> = solver.learned_clauses_iter().collect();
+
+            let mut solver = Solver::new();
+            solver.add_formula(&CnfFormula::from(clauses.iter().map(|clause| &clause[..])));
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+
+            if result.is_err() {
+                let mut solver = Solver::new();
+                solver.add_formula(&CnfFormula::from(clauses.iter().chain(learned.iter())
+                    .map(|clause| &clause[..])));
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_num_clauses(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.num_vars(), formula.var_count());
+            prop_assert_eq!(solver.num_clauses(), solver.clauses_iter().count());
+
+            solver.set_conflict_budget(Some(20));
+            let _ = solver.solve();
+
+            prop_assert_eq!(solver.num_vars(), formula.var_count());
+            prop_assert_eq!(solver.num_clauses(), solver.clauses_iter().count());
+        }
+
+        #[test]
+        fn sat_clauses_iter(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+        ) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in solver.clauses_iter().chain(solver.learned_clauses_iter()) {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sat_write_dimacs(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+        ) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            let mut dimacs = vec![];
+            solver.write_dimacs(&mut dimacs).unwrap();
+
+            let exported = DimacsParser::parse(&dimacs[..]).unwrap();
+
+            for clause in exported.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+
+            let mut solver = Solver::new();
+            solver.add_formula(&exported);
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+        }
+
+        #[test]
+        fn sgen_unsat(
+            formula in sgen_unsat_formula(1..7usize),
+            test_schedule in proptest::bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            if test_schedule {
+                enable_test_schedule(&mut solver);
+            }
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sgen_unsat_checked(
+            formula in sgen_unsat_formula(1..7usize),
+            test_schedule in proptest::bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+
+            solver.enable_self_checking();
+
+            solver.add_formula(&formula);
+
+            if test_schedule {
+                enable_test_schedule(&mut solver);
+            }
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sat(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            test_schedule in proptest::bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            if test_schedule {
+                enable_test_schedule(&mut solver);
+            }
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn compact_equisatisfiable(formula in cnf_formula(1..60usize, 0..60, 1..4)) {
+            let (compact, original_vars) = formula.compact();
+
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+
+            let mut compact_solver = Solver::new();
+            compact_solver.add_formula(&compact);
+
+            let result = solver.solve().unwrap();
+            prop_assert_eq!(compact_solver.solve().unwrap(), result);
+
+            if result {
+                let model: Vec<Lit> = compact_solver
+                    .model()
+                    .unwrap()
+                    .into_iter()
+                    .map(|lit| lit.map_var(|var| original_vars[var.index()]))
+                    .collect();
+
+                for clause in formula.iter() {
+                    prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+                }
+            }
+        }
+
+        #[test]
+        fn sat_via_dimacs(formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0)) {
+            let mut solver = Solver::new();
+
+            let mut dimacs = vec![];
+
+            write_dimacs(&mut dimacs, &formula).unwrap();
+
+            solver.add_dimacs_cnf(&mut &dimacs[..]).unwrap();
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_memory_usage(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+            prop_assert_eq!(solver.peak_memory_usage(), 0);
+
+            let empty_usage = solver.memory_usage();
+
+            solver.add_formula(&formula);
+
+            let loaded_usage = solver.memory_usage();
+            prop_assert!(loaded_usage > empty_usage);
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+
+            prop_assert!(solver.peak_memory_usage() >= solver.memory_usage());
+        }
+
+        #[test]
+        fn sgen_unsat_progress_callback(formula in sgen_unsat_formula(7..9usize)) {
+            let mut reports = vec![];
+            let stats;
+
+            {
+                let mut solver = Solver::new();
+
+                let mut config = SolverConfigUpdate::new();
+                config.progress_interval = Some(10);
+                solver.config(&config).unwrap();
+
+                solver.set_progress_callback(|stats| reports.push(stats.clone()));
+
+                solver.add_formula(&formula);
+
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+
+                stats = solver.stats();
+            }
+
+            prop_assert_eq!(reports.len() as u64, (stats.conflicts - 1) / 10);
+            prop_assert!(stats.decisions > 0);
+            prop_assert!(stats.propagations > 0);
+
+            for (i, report) in reports.iter().enumerate() {
+                prop_assert_eq!(report.conflicts, (i as u64 + 1) * 10);
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_learned_histograms(formula in sgen_unsat_formula(7..9usize)) {
+            let mut solver = Solver::new();
+
+            let stats = solver.stats();
+            prop_assert!(stats.learned_len_histogram.is_empty());
+            prop_assert!(stats.lbd_histogram.is_empty());
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+
+            let stats = solver.stats();
+
+            let learned: u64 = stats.learned_len_histogram.iter().sum();
+            let long: u64 = stats.learned_len_histogram.iter().skip(3).sum();
+            let counted_glue: u64 = stats.lbd_histogram.iter().sum();
+
+            prop_assert!(learned > 0);
+            prop_assert!(learned <= stats.conflicts);
+            prop_assert_eq!(long, counted_glue);
+            prop_assert!(stats.learned_len_histogram.len() <= 32);
+            prop_assert!(stats.lbd_histogram.len() <= 32);
+        }
+
+        #[test]
+        fn sgen_unsat_no_restarts(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            let mut config = SolverConfigUpdate::new();
+            config.restart_strategy = Some(RestartStrategy::None);
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+            prop_assert_eq!(solver.ctx.schedule.restarts(), 0);
+        }
+
+        #[test]
+        fn sgen_unsat_vmtf(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            let mut config = SolverConfigUpdate::new();
+            config.decision_heuristic = Some(DecisionHeuristic::Vmtf);
+            solver.config(&config).unwrap();
+
+            solver.enable_self_checking();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sat_vmtf(formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0)) {
+            let mut solver = Solver::new();
+
+            let mut config = SolverConfigUpdate::new();
+            config.decision_heuristic = Some(DecisionHeuristic::Vmtf);
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sat_assignment_hint(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+        ) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+            let mut hint = solver.model().unwrap();
+
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+            solver.set_assignment_hint(&hint);
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+            prop_assert!(solver.stats().learned_len_histogram.is_empty());
+            prop_assert!(solver.stats().decisions <= hint.len() as u64);
+
+            let mut model = solver.model().unwrap();
+            hint.sort();
+            model.sort();
+            prop_assert_eq!(model, hint.clone());
+
+            // Hinting keeps the last result until solving again
+            let negated: Vec<_> = hint.iter().map(|&lit| !lit).collect();
+            let vars = solver.num_vars();
+            solver.set_assignment_hint(&negated);
+            solver.set_assignment_hint(&[Var::from_index(vars + 10).positive()]);
+            prop_assert_eq!(solver.num_vars(), vars);
+            prop_assert_eq!(solver.last_result(), Some(true));
+            let mut model = solver.model().unwrap();
+            model.sort();
+            prop_assert_eq!(model, hint.clone());
+
+            // Hinting the negated model after solving searches again
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+            let model = solver.model().unwrap();
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sat_non_decision_vars(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            disabled in collection::vec(bool::ANY, 20),
+            vmtf in bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+
+            if vmtf {
+                let mut config = SolverConfigUpdate::new();
+                config.decision_heuristic = Some(DecisionHeuristic::Vmtf);
+                solver.config(&config).unwrap();
+            }
+
+            for (index, &disabled) in disabled.iter().enumerate() {
+                if disabled {
+                    solver.set_decision_var(Var::from_index(index), false);
+                }
+            }
+
+            solver.add_formula(&formula);
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_switch_heuristic(
+            formula in sgen_unsat_formula(7..9usize),
+            vmtf_first in bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+
+            let heuristics = if vmtf_first {
+                [DecisionHeuristic::Vmtf, DecisionHeuristic::Vsids]
+            } else {
+                [DecisionHeuristic::Vsids, DecisionHeuristic::Vmtf]
+            };
+
+            let mut config = SolverConfigUpdate::new();
+            config.decision_heuristic = Some(heuristics[0]);
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+
+            solver.set_conflict_budget(Some(5));
+
+            let mut round = 0;
+
+            let result = loop {
+                match solver.solve_limited() {
+                    Ok(None) => (),
+                    result => break result,
+                }
+                round += 1;
+                config.decision_heuristic = Some(heuristics[round % 2]);
+                solver.config(&config).unwrap();
+            };
+
+            prop_assert_eq!(result.ok(), Some(Some(false)));
+        }
+
+        #[test]
+        fn sgen_unsat_max_local_clauses(formula in sgen_unsat_formula(9..11usize)) {
+            let mut solver = Solver::new();
+
+            let mut config = SolverConfigUpdate::new();
+            config.max_local_clauses = Some(Some(20));
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+
+            solver.set_conflict_budget(Some(1));
+
+            let mut max_locals = 0;
+
+            let result = loop {
+                max_locals = max_locals.max(solver.ctx.clause_db.count_by_tier(Tier::Local));
+                match solver.solve() {
+                    Err(SolverError::Interrupted) => (),
+                    result => break result,
+                }
+            };
+
+            prop_assert_eq!(result.ok(), Some(false));
+            prop_assert!(max_locals <= 21);
+        }
+
+        #[test]
+        fn sgen_unsat_glue_limits(formula in sgen_unsat_formula(9..11usize)) {
+            let run = |glue_limit: usize| {
+                let mut solver = Solver::new();
+
+                let mut config = SolverConfigUpdate::new();
+                config.core_glue_limit = Some(glue_limit);
+                config.mid_glue_limit = Some(glue_limit);
+                config.reduce_locals_interval = Some(20);
+                solver.config(&config).unwrap();
+
+                solver.add_formula(&formula);
+
+                solver.set_conflict_budget(Some(1));
+
+                let mut learned_counts = vec![];
+
+                let result = loop {
+                    let db = &solver.ctx.clause_db;
+                    learned_counts.push([Tier::Core, Tier::Mid, Tier::Local].map(|tier| {
+                        db.count_by_tier(tier)
+                    }));
+                    match solver.solve() {
+                        Err(SolverError::Interrupted) => (),
+                        result => break result,
+                    }
+                };
+
+                assert_eq!(result.ok(), Some(false));
+
+                learned_counts
+            };
+
+            let unprotected = run(0);
+
+            prop_assert!(unprotected.iter().all(|counts| counts[0] == 0 && counts[1] == 0));
+            prop_assert!(unprotected.windows(2).any(|pair| pair[1][2] < pair[0][2]));
+
+            let protected = run(1000);
+
+            prop_assert!(protected.iter().all(|counts| counts[1] == 0 && counts[2] == 0));
+        }
+
+        #[test]
+        fn reset_matches_fresh_solver(
+            sat_formulas in collection::vec(
+                sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+                1..4,
+            ),
+            unsat_formulas in collection::vec(sgen_unsat_formula(1..7usize), 1..4),
+        ) {
+            let mut reused = Solver::new();
+
+            for (formula, expected) in sat_formulas
+                .iter()
+                .map(|formula| (formula, true))
+                .chain(unsat_formulas.iter().map(|formula| (formula, false)))
+            {
+                let mut fresh = Solver::new();
+                fresh.add_formula(formula);
+                let fresh_result = fresh.solve().ok();
+
+                let memory_usage = reused.memory_usage();
+                reused.reset();
+                prop_assert!(reused.memory_usage() >= memory_usage);
+                reused.add_formula(formula);
+
+                prop_assert_eq!(reused.solve().ok(), fresh_result);
+                prop_assert_eq!(fresh_result, Some(expected));
+                prop_assert_eq!(reused.model(), fresh.model());
+                prop_assert_eq!(reused.num_vars(), fresh.num_vars());
+            }
+        }
+
+        #[test]
+        fn save_and_load_state(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            extra_clauses in collection::vec(collection::vec(lit(0..20usize), 1..4), 0..20),
+            solve_first in bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+            let mut config = SolverConfigUpdate::new();
+            config.restart_interval = Some(50);
+            solver.config(&config).unwrap();
+
+            solver.add_formula(&formula);
+            solver.hide_var(Var::from_index(0));
+            solver.witness_var(Var::from_index(1));
+
+            if solve_first {
+                prop_assert_eq!(solver.solve().ok(), Some(true));
+            }
+
+            let mut state = vec![];
+            solver.save_state(&mut state).unwrap();
+
+            let mut restored = Solver::load_state(&state[..]).unwrap();
+
+            prop_assert_eq!(restored.ctx.solver_config.restart_interval, 50);
+            prop_assert_eq!(restored.num_vars(), solver.num_vars());
+
+            for clause in extra_clauses.iter() {
+                // Witness variables cannot be constrained
+                if clause.iter().all(|lit| lit.index() != 1) {
+                    solver.add_clause(clause);
+                    restored.add_clause(clause);
+                }
+            }
+
+            let result = solver.solve().ok();
+            prop_assert_eq!(restored.solve().ok(), result);
+
+            if result == Some(true) {
+                let model = restored.model().unwrap();
+                let model_vars = |model: &[Lit]| model.iter().map(|lit| lit.var()).collect::<Vec<_>>();
+                prop_assert_eq!(model_vars(&model), model_vars(&solver.model().unwrap()));
+                for clause in formula.iter().chain(extra_clauses.iter().map(|clause| &clause[..])) {
+                    if clause.iter().all(|lit| lit.index() > 1) {
+                        prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn fork_solves_independently(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            solve_first in bool::ANY,
+        ) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+
+            if solve_first {
+                prop_assert_eq!(solver.solve().ok(), Some(true));
+            }
+
+            let mut forks = [solver.fork(), solver.fork()];
+
+            for fork in forks.iter() {
+                prop_assert_eq!(fork.num_vars(), solver.num_vars());
+                prop_assert_eq!(fork.sat_state(), SatState::Unknown);
+            }
+
+            let first_var = Var::from_index(0);
+            forks[0].add_clause(&[first_var.positive()]);
+            forks[1].add_clause(&[first_var.negative()]);
+
+            for fork in forks.iter_mut() {
+                if fork.solve().ok() == Some(true) {
+                    let model = fork.model().unwrap();
+                    for clause in formula.iter() {
+                        prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+                    }
+                }
+            }
+
+            prop_assert!(forks.iter().any(|fork| fork.sat_state() == SatState::Sat));
+
+            for (fork, lit) in forks.iter().zip([first_var.positive(), first_var.negative()]) {
+                if let Some(model) = fork.model() {
+                    prop_assert!(model.contains(&lit));
+                }
+            }
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+        }
+
+        #[test]
+        fn sgen_unsat_preprocess(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+            solver.enable_self_checking();
+            solver.add_formula(&formula);
+
+            solver.preprocess().unwrap();
+
+            prop_assert_eq!(solver.solve().ok(), Some(false));
+        }
+
+        #[test]
+        fn sat_preprocess(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+            duplicated in collection::vec((0..100usize, lit(0..20usize)), 0..20),
+        ) {
+            let mut input = formula.clone();
+            for &(index, lit) in duplicated.iter() {
+                if let Some(clause) = formula.iter().nth(index % formula.len().max(1)) {
+                    if !clause.iter().any(|clause_lit| clause_lit.var() == lit.var()) {
+                        let mut clause = clause.to_vec();
+                        clause.push(lit);
+                        input.add_clause(&clause);
+                    }
+                }
+            }
+
+            let mut solver = Solver::new();
+            solver.enable_self_checking();
+            solver.add_formula(&input);
+
+            let initial_count = solver.num_clauses();
+
+            solver.preprocess().unwrap();
+
+            prop_assert!(solver.num_clauses() <= initial_count);
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+            for clause in input.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sat_preprocess_pure_literals(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+        ) {
+            let mut solver = Solver::new();
+            solver.add_formula(&formula);
+
+            for index in (0..formula.var_count()).step_by(2) {
+                solver.witness_var(Var::from_index(index));
+            }
+
+            solver.preprocess().unwrap();
+
+            prop_assert_eq!(solver.solve().ok(), Some(true));
+
+            let model = solver.model().unwrap();
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_learned_clause_callback(formula in sgen_unsat_formula(1..7usize)) {
+            let mut learned = vec![];
+
+            {
+                let mut solver = Solver::new();
+                solver.on_learned_clause(|clause| learned.push(clause.to_vec()));
+                solver.add_formula(&formula);
+
+                prop_assert_eq!(solver.solve().ok(), Some(false));
+            }
+
+            prop_assert!(learned.last().is_some_and(|clause| clause.is_empty()));
+
+            for clause in learned.iter() {
+                prop_assert!(clause.iter().all(|lit| lit.index() < formula.var_count()));
+            }
+        }
+
+        #[test]
+        fn sgen_unsat_incremental_clauses(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            let mut last_state = Some(true);
+
+            for clause in formula.iter() {
+                solver.add_clause(clause);
+
+                let state = solver.solve().ok();
                 if state != last_state {
                     prop_assert_eq!(state, Some(false));
                     prop_assert_eq!(last_state, Some(true));
@@ -505,5 +3453,73 @@ mod tests {
 
             prop_assert_eq!(last_state, Some(false));
         }
+
+        #[test]
+        fn sgen_unsat_conflict_budget(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            solver.set_conflict_budget(Some(0));
+            prop_assert!(matches!(solver.solve(), Err(SolverError::Interrupted)));
+
+            solver.set_conflict_budget(Some(10));
+
+            let result = loop {
+                match solver.solve() {
+                    Err(SolverError::Interrupted) => (),
+                    result => break result,
+                }
+            };
+
+            prop_assert_eq!(result.ok(), Some(false));
+        }
+
+        #[test]
+        fn sgen_unsat_solve_limited(formula in sgen_unsat_formula(1..7usize)) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            solver.set_conflict_budget(Some(0));
+            prop_assert_eq!(solver.solve_limited().ok(), Some(None));
+
+            solver.set_conflict_budget(Some(10));
+
+            let result = loop {
+                match solver.solve_limited() {
+                    Ok(None) => (),
+                    result => break result,
+                }
+            };
+
+            prop_assert_eq!(result.ok(), Some(Some(false)));
+        }
+
+        #[test]
+        fn sat_propagation_budget(
+            formula in sat_formula(4..20usize, 10..100usize, 0.05..0.2, 0.9..1.0),
+        ) {
+            let mut solver = Solver::new();
+
+            solver.add_formula(&formula);
+
+            solver.set_propagation_budget(Some(20));
+
+            let result = loop {
+                match solver.solve() {
+                    Err(SolverError::Interrupted) => (),
+                    result => break result,
+                }
+            };
+
+            prop_assert_eq!(result.ok(), Some(true));
+
+            let model = solver.model().unwrap();
+
+            for clause in formula.iter() {
+                prop_assert!(clause.iter().any(|lit| model.contains(lit)));
+            }
+        }
     }
 }
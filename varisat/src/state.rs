@@ -2,20 +2,19 @@
 use crate::solver::SolverError;
 
 /// Satisfiability state.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 pub enum SatState {
+    /// The solver was not invoked since the last change or did not find an answer.
+    #[default]
     Unknown,
+    /// The formula is satisfiable under the current assumptions.
     Sat,
+    /// The formula is unsatisfiable independent of any assumptions.
     Unsat,
+    /// The formula is unsatisfiable under the current assumptions.
     UnsatUnderAssumptions,
 }
 
-impl Default for SatState {
-    fn default() -> SatState {
-        SatState::Unknown
-    }
-}
-
 /// Miscellaneous solver state.
 ///
 /// Anything larger or any larger group of related state variables should be moved into a separate
@@ -27,6 +26,10 @@ pub struct SolverState {
     pub solver_invoked: bool,
     pub state_is_invalid: bool,
     pub solver_error: Option<SolverError>,
+    /// Largest memory usage estimate observed during solving.
+    pub peak_memory_usage: usize,
+    /// Whether an assignment hint was set that the next search has to start from.
+    pub hint_pending: bool,
 }
 
 impl Default for SolverState {
@@ -37,6 +40,8 @@ impl Default for SolverState {
             solver_invoked: false,
             state_is_invalid: false,
             solver_error: None,
+            peak_memory_usage: 0,
+            hint_pending: false,
         }
     }
 }
@@ -0,0 +1,111 @@
+//! Solver statistics.
+use partial_ref::{partial, PartialRef};
+
+use crate::{
+    clause::Tier,
+    context::{parts::*, Context},
+    schedule::Schedule,
+};
+
+/// Number of buckets in the histograms of learned clauses.
+const HISTOGRAM_LEN: usize = 32;
+
+/// Statistics collected while solving.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Number of conflicts found so far.
+    pub conflicts: u64,
+    /// Number of restarts performed so far.
+    pub restarts: u64,
+    /// Number of decisions made so far, not counting assumptions.
+    pub decisions: u64,
+    /// Number of propagated assignments so far.
+    pub propagations: u64,
+    /// Number of solver variables not fixed at the top level.
+    pub vars: usize,
+    /// Number of binary clauses.
+    pub binary_clauses: usize,
+    /// Number of irredundant long clauses.
+    pub irred_clauses: usize,
+    /// Number of learned long clauses in the core tier.
+    pub core_clauses: usize,
+    /// Number of learned long clauses in the mid tier.
+    pub mid_clauses: usize,
+    /// Number of learned long clauses in the local tier.
+    pub local_clauses: usize,
+    /// Number of learned clauses by length.
+    ///
+    /// Entry `n` counts the learned clauses of length `n`. The vector has at most 32 entries and the
+    /// last one also counts all longer clauses.
+    pub learned_len_histogram: Vec<u64>,
+    /// Number of learned long clauses by glue level (LBD).
+    ///
+    /// Like [`learned_len_histogram`](SolverStats::learned_len_histogram) but indexed by the glue
+    /// level the clause had when it was learned. Unit and binary clauses are not counted.
+    pub lbd_histogram: Vec<u64>,
+}
+
+/// Statistics about learned clauses.
+#[derive(Default)]
+pub struct LearnStats {
+    len_histogram: Vec<u64>,
+    glue_histogram: Vec<u64>,
+}
+
+impl LearnStats {
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.len_histogram.clear();
+        self.glue_histogram.clear();
+    }
+
+    /// Count a learned clause of the given length.
+    pub fn count_learned_clause(&mut self, len: usize) {
+        count_in_histogram(&mut self.len_histogram, len);
+    }
+
+    /// Count a learned long clause of the given glue level.
+    pub fn count_glue(&mut self, glue: usize) {
+        count_in_histogram(&mut self.glue_histogram, glue);
+    }
+}
+
+/// Increment the bucket of a value, growing the histogram as needed.
+fn count_in_histogram(histogram: &mut Vec<u64>, value: usize) {
+    let bucket = value.min(HISTOGRAM_LEN - 1);
+    if histogram.len() <= bucket {
+        histogram.resize(bucket + 1, 0);
+    }
+    histogram[bucket] += 1;
+}
+
+/// Current statistics of the solver.
+pub fn solver_stats(
+    ctx: partial!(
+        Context,
+        AssignmentP,
+        BinaryClausesP,
+        ClauseDbP,
+        LearnStatsP,
+        TrailP
+    ),
+    schedule: &Schedule,
+) -> SolverStats {
+    let db = ctx.part(ClauseDbP);
+    let trail = ctx.part(TrailP);
+    let learn_stats = ctx.part(LearnStatsP);
+    SolverStats {
+        conflicts: schedule.conflicts(),
+        restarts: schedule.restarts(),
+        decisions: trail.decision_count(),
+        propagations: trail.propagations(),
+        vars: ctx.part(AssignmentP).assignment().len() - trail.top_level_assignment_count(),
+        binary_clauses: ctx.part(BinaryClausesP).count(),
+        irred_clauses: db.count_by_tier(Tier::Irred),
+        core_clauses: db.count_by_tier(Tier::Core),
+        mid_clauses: db.count_by_tier(Tier::Mid),
+        local_clauses: db.count_by_tier(Tier::Local),
+        learned_len_histogram: learn_stats.len_histogram.clone(),
+        lbd_histogram: learn_stats.glue_histogram.clone(),
+    }
+}
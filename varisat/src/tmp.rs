@@ -11,6 +11,14 @@ pub struct TmpData {
     pub lits_2: Vec<Lit>,
 }
 
+impl TmpData {
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.lits.clear();
+        self.lits_2.clear();
+    }
+}
+
 /// Temporary data that is automatically resized.
 ///
 /// This contains buffers that are automatically resized when the variable count of the solver
@@ -32,4 +40,15 @@ impl TmpFlags {
     pub fn set_var_count(&mut self, count: usize) {
         self.flags.resize(count * 2, false);
     }
+
+    /// Reserve capacity for the given variable count.
+    pub fn reserve_var_count(&mut self, count: usize) {
+        self.flags
+            .reserve((count * 2).saturating_sub(self.flags.len()));
+    }
+
+    /// Reset to the initial state, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.flags.clear();
+    }
 }
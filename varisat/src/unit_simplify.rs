@@ -89,12 +89,15 @@ pub fn unit_simplify<'a>(
         mut BinaryClausesP,
         mut ClauseAllocP,
         mut ClauseDbP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut VariablesP,
         mut WatchlistsP,
+        mut VmtfP,
         mut VsidsP,
         AssumptionsP,
+        SolverConfigP,
     ),
 ) {
     simplify_binary(ctx.borrow());
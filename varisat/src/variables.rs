@@ -1,5 +1,7 @@
 //! Variable mapping and metadata.
 
+use std::mem::size_of;
+
 use rustc_hash::FxHashSet as HashSet;
 
 use partial_ref::{partial, PartialRef};
@@ -19,6 +21,7 @@ use data::{SamplingMode, VarData};
 use var_map::{VarBiMap, VarBiMapMut, VarMap};
 
 /// Variable mapping and metadata.
+#[derive(Default)]
 pub struct Variables {
     /// Bidirectional mapping from user variables to global variables.
     ///
@@ -47,21 +50,28 @@ pub struct Variables {
     var_data: Vec<VarData>,
 }
 
-impl Default for Variables {
-    fn default() -> Variables {
-        Variables {
-            global_from_user: VarBiMap::default(),
-            solver_from_global: VarBiMap::default(),
-            user_freelist: Default::default(),
-            global_freelist: Default::default(),
-            solver_freelist: Default::default(),
-
-            var_data: vec![],
-        }
+impl Variables {
+    /// Estimated number of bytes used by the variable mappings and metadata.
+    pub fn memory_usage(&self) -> usize {
+        let freelists = self.user_freelist.capacity()
+            + self.global_freelist.capacity()
+            + self.solver_freelist.capacity();
+        self.global_from_user.memory_usage()
+            + self.solver_from_global.memory_usage()
+            + freelists * size_of::<Var>()
+            + self.var_data.capacity() * size_of::<VarData>()
+    }
+
+    /// Remove all variables, keeping the allocated capacity.
+    pub fn reset(&mut self) {
+        self.global_from_user.clear();
+        self.solver_from_global.clear();
+        self.user_freelist.clear();
+        self.global_freelist.clear();
+        self.solver_freelist.clear();
+        self.var_data.clear();
     }
-}
 
-impl Variables {
     /// Number of allocated solver variables.
     pub fn solver_watermark(&self) -> usize {
         self.global_from_solver().watermark()
@@ -94,41 +104,41 @@ impl Variables {
 
     /// The user to global mapping.
     pub fn global_from_user(&self) -> &VarMap {
-        &self.global_from_user.fwd()
+        self.global_from_user.fwd()
     }
 
     /// Mutable user to global mapping.
-    pub fn global_from_user_mut(&mut self) -> VarBiMapMut {
+    pub fn global_from_user_mut(&mut self) -> VarBiMapMut<'_> {
         self.global_from_user.fwd_mut()
     }
 
     /// The global to solver mapping.
     pub fn solver_from_global(&self) -> &VarMap {
-        &self.solver_from_global.fwd()
+        self.solver_from_global.fwd()
     }
 
     /// Mutable global to solver mapping.
-    pub fn solver_from_global_mut(&mut self) -> VarBiMapMut {
+    pub fn solver_from_global_mut(&mut self) -> VarBiMapMut<'_> {
         self.solver_from_global.fwd_mut()
     }
 
     /// The global to user mapping.
     pub fn user_from_global(&self) -> &VarMap {
-        &self.global_from_user.bwd()
+        self.global_from_user.bwd()
     }
 
     /// Mutable global to user mapping.
-    pub fn user_from_global_mut(&mut self) -> VarBiMapMut {
+    pub fn user_from_global_mut(&mut self) -> VarBiMapMut<'_> {
         self.global_from_user.bwd_mut()
     }
 
     /// The solver to global mapping.
     pub fn global_from_solver(&self) -> &VarMap {
-        &self.solver_from_global.bwd()
+        self.solver_from_global.bwd()
     }
 
     /// Mutable  solver to global mapping.
-    pub fn global_from_solver_mut(&mut self) -> VarBiMapMut {
+    pub fn global_from_solver_mut(&mut self) -> VarBiMapMut<'_> {
         self.solver_from_global.bwd_mut()
     }
 
@@ -191,7 +201,7 @@ impl Variables {
             .iter()
             .next()
             .cloned()
-            .unwrap_or_else(|| Var::from_index(self.global_watermark()))
+            .unwrap_or_else(|| checked_var_from_index(self.global_watermark()))
     }
 
     /// Get an unmapped global variable.
@@ -200,7 +210,7 @@ impl Variables {
             .iter()
             .next()
             .cloned()
-            .unwrap_or_else(|| Var::from_index(self.solver_watermark()))
+            .unwrap_or_else(|| checked_var_from_index(self.solver_watermark()))
     }
 
     /// Get an unmapped user variable.
@@ -209,8 +219,44 @@ impl Variables {
             .iter()
             .next()
             .cloned()
-            .unwrap_or_else(|| Var::from_index(self.user_watermark()))
+            .unwrap_or_else(|| checked_var_from_index(self.user_watermark()))
+    }
+}
+
+/// Create the variable with the given index, checking that it is supported.
+///
+/// Panics with a descriptive message if the index is not below [`Var::max_count`], as such a
+/// variable would alias another variable.
+fn checked_var_from_index(index: usize) -> Var {
+    if index >= Var::max_count() {
+        panic!(
+            "variable index {} exceeds the maximum supported variable count of {}",
+            index,
+            Var::max_count()
+        );
     }
+    Var::from_index(index)
+}
+
+/// Check that the variables of the given user literals are supported.
+///
+/// Panics with a descriptive message if a variable is not below [`Var::max_count`]. Clauses are
+/// checked before any of their variables are mapped, so that such a clause leaves the solver
+/// unchanged.
+pub fn ensure_var_count_from_slice(user_lits: &[Lit]) {
+    for &lit in user_lits {
+        checked_var_from_index(lit.index());
+    }
+}
+
+/// Reduce the user watermark.
+///
+/// This has the precondition that no user variable at or above `count` is mapped. Such user
+/// variables are removed from the freelist.
+pub fn truncate_user_vars(mut ctx: partial!(Context, mut VariablesP), count: usize) {
+    let variables = ctx.part_mut(VariablesP);
+    variables.global_from_user.truncate_fwd(count);
+    variables.user_freelist.retain(|user| user.index() < count);
 }
 
 /// Maps a user variable into a global variable.
@@ -221,6 +267,8 @@ pub fn global_from_user<'a>(
     user: Var,
     require_sampling: bool,
 ) -> Var {
+    checked_var_from_index(user.index());
+
     let variables = ctx.part_mut(VariablesP);
 
     if user.index() > variables.user_watermark() {
@@ -279,12 +327,15 @@ pub fn solver_from_global<'a>(
         mut AssignmentP,
         mut BinaryClausesP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpFlagsP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
     global: Var,
 ) -> Var {
@@ -334,12 +385,15 @@ pub fn solver_from_user<'a>(
         mut AssignmentP,
         mut BinaryClausesP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpFlagsP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
     user: Var,
     require_sampling: bool,
@@ -348,6 +402,68 @@ pub fn solver_from_user<'a>(
     solver_from_global(ctx.borrow(), global)
 }
 
+/// Maps multiple user variables to solver variables.
+///
+/// This allocates all missing global and solver variables at once, so the per variable data
+/// structures are resized only once. New solver variables are allocated in the order of
+/// `user_vars`.
+pub fn solver_from_user_vars<'a>(
+    mut ctx: partial!(
+        Context<'a>,
+        mut AnalyzeConflictP,
+        mut AssignmentP,
+        mut BinaryClausesP,
+        mut ImplGraphP,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut TmpFlagsP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        mut WatchlistsP,
+        SolverConfigP,
+    ),
+    user_vars: &[Var],
+    require_sampling: bool,
+) {
+    let mut new_vars = vec![];
+
+    let old_watermark = ctx.part(VariablesP).global_from_solver().watermark();
+
+    for &user in user_vars.iter() {
+        let global = global_from_user(ctx.borrow(), user, require_sampling);
+        let variables = ctx.part_mut(VariablesP);
+        if variables.solver_from_global().get(global).is_none() {
+            debug_assert!(!variables.var_data[global.index()].deleted);
+
+            let solver = variables.next_unmapped_solver();
+            variables.solver_from_global_mut().insert(solver, global);
+            variables.solver_freelist.remove(&solver);
+            new_vars.push((solver, global));
+        }
+    }
+
+    let new_watermark = ctx.part(VariablesP).global_from_solver().watermark();
+
+    if new_watermark > old_watermark {
+        set_var_count(ctx.borrow(), new_watermark);
+    }
+
+    for (solver, global) in new_vars {
+        initialize_solver_var(ctx.borrow(), solver, global);
+
+        proof::add_step(
+            ctx.borrow(),
+            false,
+            &ProofStep::SolverVarName {
+                global,
+                solver: Some(solver),
+            },
+        );
+    }
+}
+
 /// Allocates a currently unused user variable.
 ///
 /// This is either a user variable above any user variable used so far, or a user variable that was
@@ -369,12 +485,15 @@ pub fn solver_from_user_lits<'a>(
         mut AssignmentP,
         mut BinaryClausesP,
         mut ImplGraphP,
+        mut NonDecisionP,
         mut ProofP<'a>,
         mut SolverStateP,
         mut TmpFlagsP,
         mut VariablesP,
+        mut VmtfP,
         mut VsidsP,
         mut WatchlistsP,
+        SolverConfigP,
     ),
     solver_lits: &mut Vec<Lit>,
     user_lits: &[Lit],
@@ -407,6 +526,10 @@ pub fn set_sampling_mode<'a>(
         panic!("cannot change sampling mode of assumption variable")
     }
 
+    if var_data.pure && mode == SamplingMode::Sample {
+        panic!("cannot sample variable fixed by pure literal elimination")
+    }
+
     let previous_mode = var_data.sampling_mode;
 
     if previous_mode == mode {
@@ -485,8 +608,11 @@ pub fn initialize_solver_var(
         Context,
         mut AssignmentP,
         mut ImplGraphP,
+        mut NonDecisionP,
+        mut VmtfP,
         mut VsidsP,
-        VariablesP
+        SolverConfigP,
+        VariablesP,
     ),
     solver: Var,
     global: Var,
@@ -498,10 +624,12 @@ pub fn initialize_solver_var(
     // is important so that when new clauses containing this variable are added, load_clause knows
     // to reenqueue the assignment.
     ctx.part_mut(AssignmentP).set_var(solver, data.unit);
+    ctx.part_mut(AssignmentP)
+        .set_last_var_value(solver, data.phase.unwrap_or(false));
     if data.unit.is_some() {
         ctx.part_mut(ImplGraphP).update_removed_unit(solver);
     }
-    decision::initialize_var(ctx.borrow(), solver, data.unit.is_none());
+    decision::initialize_var(ctx.borrow(), solver, data.unit.is_none(), data.decision);
 
     // TODO unhiding beyond unit clauses
 }
@@ -510,7 +638,16 @@ pub fn initialize_solver_var(
 ///
 /// If the variable is isolated and hidden, the global variable is also removed.
 pub fn remove_solver_var<'a>(
-    mut ctx: partial!(Context<'a>, mut ProofP<'a>, mut SolverStateP, mut VariablesP, mut VsidsP),
+    mut ctx: partial!(
+        Context<'a>,
+        mut NonDecisionP,
+        mut ProofP<'a>,
+        mut SolverStateP,
+        mut VariablesP,
+        mut VmtfP,
+        mut VsidsP,
+        SolverConfigP,
+    ),
     solver: Var,
 ) {
     decision::remove_var(ctx.borrow(), solver);
@@ -574,12 +711,50 @@ mod tests {
     use proptest::{collection, prelude::*};
 
     use varisat_formula::{
+        lit::LitIdx,
         test::{sat_formula, sgen_unsat_formula},
-        ExtendFormula, Var,
+        ExtendFormula, Lit, Var,
     };
 
     use crate::solver::Solver;
 
+    use super::checked_var_from_index;
+
+    /// A literal of the first variable past [`Var::max_var`].
+    ///
+    /// `Var::from_index` rejects this in debug builds, so the literal is constructed from its code.
+    fn lit_past_max_var() -> Lit {
+        let code = (Var::max_count() * 2) as LitIdx;
+        // This is safe as Lit and LitIdx have the same representation
+        unsafe { std::mem::transmute::<LitIdx, Lit>(code) }
+    }
+
+    #[test]
+    fn max_var_index() {
+        assert_eq!(checked_var_from_index(Var::max_count() - 1), Var::max_var());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum supported variable count")]
+    fn var_index_past_max() {
+        checked_var_from_index(Var::max_count());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum supported variable count")]
+    fn add_clause_past_max_var() {
+        let mut solver = Solver::new();
+        solver.add_clause(&[Lit::from_dimacs(1), lit_past_max_var()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum supported variable count")]
+    fn add_clauses_past_max_var() {
+        let mut solver = Solver::new();
+        let clause = [lit_past_max_var()];
+        solver.add_clauses([&clause[..]]);
+    }
+
     #[test]
     #[should_panic(expected = "cannot change sampling mode of assumption variable")]
     fn cannot_hide_assumed_vars() {
@@ -620,7 +795,7 @@ mod tests {
 
             for clause in unsat_formula.iter() {
                 tmp.clear();
-                tmp.extend_from_slice(&clause);
+                tmp.extend_from_slice(clause);
                 tmp.push(cond.negative());
                 solver.add_clause(&tmp);
             }
@@ -44,6 +44,16 @@ pub struct VarData {
     pub assumed: bool,
     /// Whether the global variable was deleted.
     pub deleted: bool,
+    /// Preferred polarity for decisions, set by the user.
+    pub phase: Option<bool>,
+    /// Whether the variable is protected from being eliminated by simplifications.
+    pub frozen: bool,
+    /// Whether the variable can be chosen for decisions before all other variables are assigned.
+    pub decision: bool,
+    /// Whether the value of the variable was chosen by pure literal elimination.
+    ///
+    /// Such a value is assigned at the top level, but is not implied by the formula.
+    pub pure: bool,
 }
 
 impl Default for VarData {
@@ -54,6 +64,10 @@ impl Default for VarData {
             isolated: true,
             assumed: false,
             deleted: true,
+            phase: None,
+            frozen: false,
+            decision: true,
+            pure: false,
         }
     }
 }
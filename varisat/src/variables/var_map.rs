@@ -1,5 +1,7 @@
 //! Mappings between variable names
 
+use std::mem::size_of;
+
 use varisat_formula::{lit::LitIdx, Var};
 
 const NO_VAR_IDX: LitIdx = Var::max_count() as LitIdx;
@@ -11,6 +13,11 @@ pub struct VarMap {
 }
 
 impl VarMap {
+    /// Estimated number of bytes used by the mapping.
+    pub fn memory_usage(&self) -> usize {
+        self.mapping.capacity() * size_of::<LitIdx>()
+    }
+
     /// Look up a variable in the mapping
     pub fn get(&self, from: Var) -> Option<Var> {
         match self.mapping.get(from.index()).cloned() {
@@ -47,6 +54,28 @@ impl VarMap {
     pub fn watermark(&self) -> usize {
         self.mapping.len()
     }
+
+    /// The mapping of every variable below the watermark.
+    pub fn to_vec(&self) -> Vec<Option<Var>> {
+        (0..self.watermark())
+            .map(|index| self.get(Var::from_index(index)))
+            .collect()
+    }
+
+    /// Reduce the watermark.
+    ///
+    /// This has the precondition that no variable at or above `len` is mapped.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.mapping.len() {
+            debug_assert!(self.mapping[len..].iter().all(|&index| index == NO_VAR_IDX));
+            self.mapping.truncate(len);
+        }
+    }
+
+    /// Remove all mappings, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.mapping.clear();
+    }
 }
 
 /// A bidirectional mapping between variables.
@@ -60,6 +89,11 @@ pub struct VarBiMap {
 }
 
 impl VarBiMap {
+    /// Estimated number of bytes used by both directions of the mapping.
+    pub fn memory_usage(&self) -> usize {
+        self.fwd.memory_usage() + self.bwd.memory_usage()
+    }
+
     /// Access the forward mapping.
     pub fn fwd(&self) -> &VarMap {
         &self.fwd
@@ -70,8 +104,22 @@ impl VarBiMap {
         &self.bwd
     }
 
+    /// Reduce the watermark of the forward mapping.
+    ///
+    /// This has the precondition that no variable at or above `len` is mapped in forward
+    /// direction.
+    pub fn truncate_fwd(&mut self, len: usize) {
+        self.fwd.truncate(len);
+    }
+
+    /// Remove all mappings in both directions, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.fwd.clear();
+        self.bwd.clear();
+    }
+
     /// Mutate the mapping in forward direction.
-    pub fn fwd_mut(&mut self) -> VarBiMapMut {
+    pub fn fwd_mut(&mut self) -> VarBiMapMut<'_> {
         VarBiMapMut {
             fwd: &mut self.fwd,
             bwd: &mut self.bwd,
@@ -79,7 +127,7 @@ impl VarBiMap {
     }
 
     /// Mutate the mapping in backward direction.
-    pub fn bwd_mut(&mut self) -> VarBiMapMut {
+    pub fn bwd_mut(&mut self) -> VarBiMapMut<'_> {
         VarBiMapMut {
             fwd: &mut self.bwd,
             bwd: &mut self.fwd,
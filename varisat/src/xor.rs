@@ -0,0 +1,128 @@
+//! CNF encoding of XOR constraints.
+use varisat_formula::{ExtendFormula, Lit, Var};
+
+/// Maximal number of variables of an XOR constraint that is encoded without auxiliary variables.
+///
+/// Directly encoding an XOR constraint over n variables requires 2^(n-1) clauses.
+const MAX_DIRECT_LEN: usize = 4;
+
+/// Add clauses that encode an XOR constraint.
+///
+/// The constraint is satisfied if the number of true literals in `lits` has the parity given by
+/// `rhs`, i.e. is odd if `rhs` is true. Long constraints are split into smaller constraints using
+/// auxiliary variables, which are allocated using [`ExtendFormula::new_var`] and returned.
+pub fn add_xor_clause(formula: &mut impl ExtendFormula, lits: &[Lit], rhs: bool) -> Vec<Var> {
+    // Move all negations into the parity and cancel out repeated variables.
+    let mut parity = rhs;
+    let mut vars: Vec<Var> = lits
+        .iter()
+        .map(|&lit| {
+            parity ^= lit.is_negative();
+            lit.var()
+        })
+        .collect();
+
+    vars.sort_unstable();
+
+    let mut unique_vars = vec![];
+    for var in vars {
+        if unique_vars.last() == Some(&var) {
+            unique_vars.pop();
+        } else {
+            unique_vars.push(var);
+        }
+    }
+
+    let mut aux_vars = vec![];
+    let mut vars = unique_vars;
+
+    while vars.len() > MAX_DIRECT_LEN {
+        // Replace a prefix of the constraint with an auxiliary variable equal to its parity.
+        let aux_var = formula.new_var();
+        aux_vars.push(aux_var);
+
+        let mut prefix: Vec<Var> = vars.drain(..MAX_DIRECT_LEN - 1).collect();
+        prefix.push(aux_var);
+        add_direct_xor_clause(formula, &prefix, false);
+
+        vars.push(aux_var);
+    }
+
+    add_direct_xor_clause(formula, &vars, parity);
+
+    aux_vars
+}
+
+/// Add the clauses of an XOR constraint without introducing auxiliary variables.
+///
+/// Each clause excludes one assignment of the wrong parity.
+fn add_direct_xor_clause(formula: &mut impl ExtendFormula, vars: &[Var], rhs: bool) {
+    let mut clause = vec![];
+    for negations in 0..(1usize << vars.len()) {
+        // The clause excludes the assignment that sets exactly the negated literals to true.
+        if (negations.count_ones() % 2 == 1) != rhs {
+            clause.clear();
+            clause.extend(
+                vars.iter()
+                    .enumerate()
+                    .map(|(index, &var)| var.lit((negations >> index) & 1 == 0)),
+            );
+            formula.add_clause(&clause);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection, prelude::*};
+
+    use varisat_formula::{lit::strategy::lit, CnfFormula};
+
+    /// Enumerate all assignments of the first `var_count` variables.
+    fn assignments(var_count: usize) -> impl Iterator<Item = Vec<bool>> {
+        (0..(1usize << var_count)).map(move |bits| {
+            (0..var_count)
+                .map(|index| (bits >> index) & 1 == 1)
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn xor_encoding(
+            lits in collection::vec(lit(0..6usize), 0..8),
+            rhs in proptest::bool::ANY,
+        ) {
+            let mut formula = CnfFormula::new();
+            formula.set_var_count(6);
+
+            let aux_vars = add_xor_clause(&mut formula, &lits, rhs);
+
+            for assignment in assignments(6) {
+                let parity = lits
+                    .iter()
+                    .filter(|lit| assignment[lit.index()] == lit.is_positive())
+                    .count() % 2 == 1;
+
+                // Check whether there is an assignment of the auxiliary variables satisfying the
+                // formula.
+                let satisfiable = assignments(aux_vars.len()).any(|aux_assignment| {
+                    let value = |lit: Lit| {
+                        let var_value = match aux_vars.iter().position(|&var| var == lit.var()) {
+                            Some(position) => aux_assignment[position],
+                            None => assignment[lit.index()],
+                        };
+                        var_value == lit.is_positive()
+                    };
+                    formula
+                        .iter()
+                        .all(|clause| clause.iter().any(|&lit| value(lit)))
+                });
+
+                prop_assert_eq!(satisfiable, parity == rhs);
+            }
+        }
+    }
+}